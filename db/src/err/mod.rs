@@ -21,6 +21,34 @@ pub enum Error {
 	#[error("Column family is not valid")]
 	DsColumnFamilyIsNotValid,
 
+	/// There is no adapter registered for the datastore scheme
+	#[error("No adapter registered for scheme '{0}'")]
+	DsAdapterNotFound(String),
+
+	/// The scheme is handled by a built-in adapter
+	#[error("Scheme '{0}' is reserved for a built-in adapter")]
+	DsSchemeReserved(String),
+
+	/// Listing keyspaces is not supported by the datastore
+	#[error("Keyspaces can't be listed for this datastore")]
+	DsKeyspacesNotSupported,
+
+	/// The maintenance operation is not available for the datastore
+	#[error("'{0}' is not supported by this datastore")]
+	DsOperationNotSupported(&'static str),
+
+	/// A checkpoint is never written over existing files
+	#[error("Checkpoint destination '{0}' already exists")]
+	DsCheckpointExists(String),
+
+	/// The keyspace was created with types that can't be handled
+	#[error("Table '{0}' was created with types that are not supported")]
+	DsTableTypeNotSupported(String),
+
+	/// There was a problem with a storage plugin
+	#[error("There was a problem with a storage plugin: {0}")]
+	Plugin(String),
+
 	/// There was a problem with a datastore transaction
 	#[error("There was a problem with a datastore transaction: {0}")]
 	Tx(String),
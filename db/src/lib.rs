@@ -19,4 +19,10 @@ pub mod tests;
 pub use crate::err::*;
 pub use crate::interface::*;
 pub use crate::model::*;
-pub use crate::storage::{Datastore, DatastoreRef, Transaction};
+#[cfg(feature = "plugin")]
+pub use crate::storage::plugin::load_plugin;
+pub use crate::storage::plugin::{abi, CAbiAdapter, PluginLibrary};
+pub use crate::storage::{
+	register_adapter, registered_schemes, unregister_adapter, AdapterFactory, Datastore,
+	DatastoreRef, Snapshot, Subscription, Transaction,
+};
@@ -1,8 +1,12 @@
+// Accessors of the storage adapter wrapped by an adapter, its first field unless named
 macro_rules! impl_new_type_adapter {
 	($DbType: ty) => {
+		impl_new_type_adapter!($DbType, 0);
+	};
+	($DbType: ty, $field: tt) => {
 		#[allow(dead_code)]
 		pub fn get_inner(self: &Self) -> &StorageAdapter<$DbType> {
-			&self.0
+			&self.$field
 		}
 
 		#[allow(dead_code)]
@@ -16,7 +20,7 @@ macro_rules! impl_new_type_adapter {
 
 		#[allow(dead_code)]
 		pub fn get_mut_inner(self: &mut Self) -> &mut StorageAdapter<$DbType> {
-			&mut self.0
+			&mut self.$field
 		}
 
 		#[allow(dead_code)]
@@ -21,6 +21,8 @@ macro_rules! full_adapter_test_impl {
 		define_test!(should_set_key, $code);
 		#[cfg(test)]
 		define_test!(should_put_key, $code);
+		#[cfg(test)]
+		define_test!(should_cas_key, $code);
 	};
 }
 
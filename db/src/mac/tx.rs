@@ -12,6 +12,10 @@ macro_rules! impl_global_transaction {
 							..
 						} => ds.closed(),
 					)*
+					Transaction {
+						inner: Inner::Plugin(tx),
+						..
+					} => tx.closed(),
 				}
 			}
 
@@ -25,6 +29,10 @@ macro_rules! impl_global_transaction {
 							..
 						} => ds.cancel().await,
 					)*
+					Transaction {
+						inner: Inner::Plugin(tx),
+						..
+					} => tx.cancel().await,
 				}
 			}
 
@@ -38,6 +46,10 @@ macro_rules! impl_global_transaction {
 							..
 						} => ds.count(tags).await,
 					)*
+					Transaction {
+						inner: Inner::Plugin(tx),
+						..
+					} => tx.count(tags).await,
 				}
 			}
 
@@ -51,6 +63,10 @@ macro_rules! impl_global_transaction {
 							..
 						} => ds.commit().await,
 					)*
+					Transaction {
+						inner: Inner::Plugin(tx),
+						..
+					} => tx.commit().await,
 				}
 			}
 
@@ -64,6 +80,10 @@ macro_rules! impl_global_transaction {
 							..
 						} => ds.exi(key, tags).await,
 					)*
+					Transaction {
+						inner: Inner::Plugin(tx),
+						..
+					} => tx.exi(key.into(), tags).await,
 				}
 			}
 
@@ -77,6 +97,10 @@ macro_rules! impl_global_transaction {
 							..
 						} => ds.get(key, tags).await,
 					)*
+					Transaction {
+						inner: Inner::Plugin(tx),
+						..
+					} => tx.get(key.into(), tags).await,
 				}
 			}
 
@@ -95,6 +119,10 @@ macro_rules! impl_global_transaction {
 							..
 						} => ds.set(key, val, tags).await,
 					)*
+					Transaction {
+						inner: Inner::Plugin(tx),
+						..
+					} => tx.set(key.into(), val.into(), tags).await,
 				}
 			}
 
@@ -113,6 +141,33 @@ macro_rules! impl_global_transaction {
 							..
 						} => ds.put(key, val, tags).await,
 					)*
+					Transaction {
+						inner: Inner::Plugin(tx),
+						..
+					} => tx.put(key.into(), val.into(), tags).await,
+				}
+			}
+
+			/// Update a key only if its current value is `expected`
+			async fn cas<K: Into<Key> + Send, V: Into<Key> + Send>(
+				&mut self,
+				key: K,
+				expected: Option<V>,
+				new: V,
+				tags: TagBucket
+			) -> Result<(), Error> {
+				match self {
+					$(
+						#[cfg(feature = $feat)]
+						Transaction {
+							inner: Inner::$x(ds),
+							..
+						} => ds.cas(key, expected, new, tags).await,
+					)*
+					Transaction {
+						inner: Inner::Plugin(tx),
+						..
+					} => tx.cas(key.into(), expected.map(|v| v.into()), new.into(), tags).await,
 				}
 			}
 
@@ -126,6 +181,10 @@ macro_rules! impl_global_transaction {
 							..
 						} => ds.del(key, tags).await,
 					)*
+					Transaction {
+						inner: Inner::Plugin(tx),
+						..
+					} => tx.del(key.into(), tags).await,
 				}
 			}
 
@@ -145,6 +204,10 @@ macro_rules! impl_global_transaction {
 							..
 						} => ds.prefix_iterate(prefix, tags).await,
 					)*
+					Transaction {
+						inner: Inner::Plugin(tx),
+						..
+					} => tx.prefix_iterate(prefix.into(), tags).await,
 				}
 			}
 
@@ -164,17 +227,50 @@ macro_rules! impl_global_transaction {
 							..
 						} => ds.suffix_iterate(suffix, tags).await,
 					)*
+					Transaction {
+						inner: Inner::Plugin(tx),
+						..
+					} => tx.suffix_iterate(suffix.into(), tags).await,
+				}
+			}
+
+			async fn range_iterate<S>(
+				&self,
+				start: S,
+				limit: usize,
+				tags: TagBucket,
+			) -> Result<Vec<Result<(Val, Val), Error>>, Error>
+			where
+				S: Into<Key> + Send,
+			{
+				match self {
+					$(
+						#[cfg(feature = $feat)]
+						Transaction {
+							inner: Inner::$x(ds),
+							..
+						} => ds.range_iterate(start, limit, tags).await,
+					)*
+					Transaction {
+						inner: Inner::Plugin(tx),
+						..
+					} => tx.range_iterate(start.into(), limit, tags).await,
 				}
 			}
 
 			async fn iterate(&self, tags: TagBucket) -> Result<Vec<Result<(Val, Val), Error>>, Error> {
 				match self {
 					$(
+						#[cfg(feature = $feat)]
 						Transaction {
 							inner: Inner::$x(ds),
 							..
 						} => ds.iterate(tags).await,
 					)*
+					Transaction {
+						inner: Inner::Plugin(tx),
+						..
+					} => tx.iterate(tags).await,
 				}
 			}
 		}
@@ -1,6 +1,8 @@
 use async_trait::async_trait;
 
-use crate::{err::Error, util::get_absolute_path, SimpleTransaction};
+use crate::{
+	err::Error, util::get_absolute_path, DatastoreStats, SimpleSnapshot, SimpleTransaction,
+};
 use std::{pin::Pin, sync::Arc};
 
 #[derive(Debug, Clone)]
@@ -47,6 +49,11 @@ pub trait DatastoreAdapter {
 	// Set `rw` default to false means readable but not readable
 	async fn transaction(&self, rw: bool) -> Result<Self::Transaction, Error>;
 
+	type Snapshot: SimpleSnapshot;
+	// # Take a snapshot of the database
+	// The snapshot keeps the database open until it is dropped
+	fn snapshot(&self) -> Result<Self::Snapshot, Error>;
+
 	fn default() -> Self
 	where
 		Self: Sized,
@@ -61,5 +68,51 @@ pub trait DatastoreAdapter {
 		todo!()
 	}
 
+	// List keyspaces (column families, tables or trees) available in the database
+	fn keyspaces(&self) -> Result<Vec<String>, Error> {
+		Err(Error::DsKeyspacesNotSupported)
+	}
+
+	// Describe the types stored in a keyspace, for engines whose keyspaces are typed
+	fn keyspace_type(&self, _keyspace: &str) -> Result<Option<String>, Error> {
+		Ok(None)
+	}
+
+	// Physical statistics of the database, engines without statistics report nothing
+	fn stats(&self) -> Result<DatastoreStats, Error> {
+		Ok(DatastoreStats::default())
+	}
+
+	// Compact the keys between `from` and `to` of a keyspace, or of every keyspace when none
+	// is given. Open bounds compact from the first or up to the last key.
+	fn compact_range(
+		&self,
+		_keyspace: Option<&str>,
+		_from: Option<&[u8]>,
+		_to: Option<&[u8]>,
+	) -> Result<(), Error> {
+		Err(Error::DsOperationNotSupported("compact_range"))
+	}
+
+	// Write buffered changes to disk
+	fn flush(&self) -> Result<(), Error> {
+		Err(Error::DsOperationNotSupported("flush"))
+	}
+
+	// Write a consistent copy of the database to `dest`, which must not exist yet
+	fn checkpoint(&self, _dest: &str) -> Result<(), Error> {
+		Err(Error::DsOperationNotSupported("checkpoint"))
+	}
+
+	// Create an empty keyspace, engines creating keyspaces on their first write do nothing
+	fn create_keyspace(&self, _keyspace: &str) -> Result<(), Error> {
+		Err(Error::DsOperationNotSupported("create_keyspace"))
+	}
+
+	// Effective options the database was opened with, as name and value pairs
+	fn options(&self) -> Vec<(String, String)> {
+		vec![]
+	}
+
 	fn path(&self) -> &str;
 }
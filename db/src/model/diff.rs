@@ -0,0 +1,208 @@
+use std::{cmp::Ordering, collections::VecDeque, iter::Peekable};
+
+use crate::{
+	err::Error,
+	interface::{
+		kv::{Key, Val},
+		KeyValuePair,
+	},
+};
+
+/// # Key difference
+/// Key that differs between two keyspaces, the left keyspace is the one compared against
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyDiff {
+	// Only found on the right
+	Added {
+		key: Key,
+		value: Val,
+	},
+	// Only found on the left
+	Removed {
+		key: Key,
+		value: Val,
+	},
+	Changed {
+		key: Key,
+		left: Val,
+		right: Val,
+	},
+}
+
+impl KeyDiff {
+	pub fn key(&self) -> &Key {
+		match self {
+			KeyDiff::Added {
+				key,
+				..
+			}
+			| KeyDiff::Removed {
+				key,
+				..
+			}
+			| KeyDiff::Changed {
+				key,
+				..
+			} => key,
+		}
+	}
+}
+
+/// Differences of the values of a key, a side without the key has no values. A key with a
+/// single value on both sides is changed, the values of multimap keys are compared as sets:
+/// values only found on the left are removed and values only found on the right are added.
+pub fn diff_values(key: &[u8], mut left: Vec<Val>, mut right: Vec<Val>) -> Vec<KeyDiff> {
+	if let ([left], [right]) = (left.as_slice(), right.as_slice()) {
+		return match left == right {
+			true => vec![],
+			false => vec![KeyDiff::Changed {
+				key: key.to_vec(),
+				left: left.clone(),
+				right: right.clone(),
+			}],
+		};
+	}
+	left.sort();
+	right.sort();
+	let (mut left, mut right) = (left.into_iter().peekable(), right.into_iter().peekable());
+	let mut diffs = vec![];
+	loop {
+		let order = match (left.peek(), right.peek()) {
+			(None, None) => return diffs,
+			(Some(_), None) => Ordering::Less,
+			(None, Some(_)) => Ordering::Greater,
+			(Some(left), Some(right)) => left.cmp(right),
+		};
+		match order {
+			Ordering::Less => diffs.extend(left.next().map(|value| KeyDiff::Removed {
+				key: key.to_vec(),
+				value,
+			})),
+			Ordering::Greater => diffs.extend(right.next().map(|value| KeyDiff::Added {
+				key: key.to_vec(),
+				value,
+			})),
+			Ordering::Equal => {
+				left.next();
+				right.next();
+			}
+		}
+	}
+}
+
+/// Values of consecutive pairs sharing the same key, multimap tables yield a pair for each
+/// value of a key
+struct Groups<I: Iterator> {
+	pairs: Peekable<I>,
+}
+
+impl<I> Iterator for Groups<I>
+where
+	I: Iterator<Item = Result<KeyValuePair, Error>>,
+{
+	type Item = Result<(Key, Vec<Val>), Error>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let (key, value) = match self.pairs.next()? {
+			Ok(pair) => pair,
+			Err(err) => return Some(Err(err)),
+		};
+		let mut values = vec![value];
+		let same_key =
+			|pair: &Result<KeyValuePair, Error>| matches!(pair, Ok((next, _)) if next == &key);
+		while let Some(Ok((_, value))) = self.pairs.next_if(same_key) {
+			values.push(value);
+		}
+		Some(Ok((key, values)))
+	}
+}
+
+/// Walks two iterators of pairs ordered by key and yields every key that differs. Errors of
+/// either side are yielded as they are reached.
+pub struct DiffIter<L, R>
+where
+	L: Iterator<Item = Result<KeyValuePair, Error>>,
+	R: Iterator<Item = Result<KeyValuePair, Error>>,
+{
+	left: Peekable<Groups<L>>,
+	right: Peekable<Groups<R>>,
+	// Differences of the last key compared, a multimap key may have several
+	pending: VecDeque<KeyDiff>,
+}
+
+/// Compare two iterators of pairs, both must be ordered by key bytes
+pub fn diff_pairs<L, R>(left: L, right: R) -> DiffIter<L::IntoIter, R::IntoIter>
+where
+	L: IntoIterator<Item = Result<KeyValuePair, Error>>,
+	R: IntoIterator<Item = Result<KeyValuePair, Error>>,
+{
+	let left = Groups {
+		pairs: left.into_iter().peekable(),
+	};
+	let right = Groups {
+		pairs: right.into_iter().peekable(),
+	};
+	DiffIter {
+		left: left.peekable(),
+		right: right.peekable(),
+		pending: VecDeque::new(),
+	}
+}
+
+/// Compare two scans in any order, e.g. of engines with a custom comparator. The pairs are
+/// sorted by key before they are compared.
+pub fn diff_scans(
+	left: Vec<Result<KeyValuePair, Error>>,
+	right: Vec<Result<KeyValuePair, Error>>,
+) -> Result<Vec<KeyDiff>, Error> {
+	let mut left = left.into_iter().collect::<Result<Vec<_>, Error>>()?;
+	let mut right = right.into_iter().collect::<Result<Vec<_>, Error>>()?;
+	// Stable sorts keep the values of a multimap key together
+	left.sort_by(|a, b| a.0.cmp(&b.0));
+	right.sort_by(|a, b| a.0.cmp(&b.0));
+	diff_pairs(left.into_iter().map(Ok), right.into_iter().map(Ok)).collect()
+}
+
+impl<L, R> Iterator for DiffIter<L, R>
+where
+	L: Iterator<Item = Result<KeyValuePair, Error>>,
+	R: Iterator<Item = Result<KeyValuePair, Error>>,
+{
+	type Item = Result<KeyDiff, Error>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			if let Some(diff) = self.pending.pop_front() {
+				return Some(Ok(diff));
+			}
+			let order = match (self.left.peek(), self.right.peek()) {
+				(None, None) => return None,
+				(Some(Err(_)), _) => {
+					return self.left.next().map(|group| group.map(|_| unreachable!()))
+				}
+				(_, Some(Err(_))) => {
+					return self.right.next().map(|group| group.map(|_| unreachable!()))
+				}
+				(Some(Ok(_)), None) => Ordering::Less,
+				(None, Some(Ok(_))) => Ordering::Greater,
+				(Some(Ok((left, _))), Some(Ok((right, _)))) => left.cmp(right),
+			};
+			let (key, left, right) = match order {
+				Ordering::Less => {
+					let (key, values) = self.left.next()?.ok()?;
+					(key, values, vec![])
+				}
+				Ordering::Greater => {
+					let (key, values) = self.right.next()?.ok()?;
+					(key, vec![], values)
+				}
+				Ordering::Equal => {
+					let (key, left) = self.left.next()?.ok()?;
+					let (_, right) = self.right.next()?.ok()?;
+					(key, left, right)
+				}
+			};
+			self.pending = diff_values(&key, left, right).into();
+		}
+	}
+}
@@ -0,0 +1,33 @@
+/// What an import does with a key that is already in the keyspace
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConflictPolicy {
+	#[default]
+	Overwrite,
+	// Keys are written with `put`, existing keys are left untouched
+	Skip,
+	// The import stops with `Error::TxKeyAlreadyExists`
+	Fail,
+}
+
+#[derive(Debug, Clone)]
+pub struct ImportOptions {
+	// Pairs written by each transaction
+	pub batch_size: usize,
+	pub conflict: ConflictPolicy,
+}
+
+impl Default for ImportOptions {
+	fn default() -> Self {
+		ImportOptions {
+			batch_size: 1000,
+			conflict: ConflictPolicy::default(),
+		}
+	}
+}
+
+/// Pairs handled by an import once a batch is committed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportProgress {
+	pub imported: u64,
+	pub skipped: u64,
+}
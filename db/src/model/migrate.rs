@@ -0,0 +1,91 @@
+use crate::interface::Key;
+
+/// Keyspace read and written without tags, whatever name the engine gives it
+pub const DEFAULT_KEYSPACE: &str = "default";
+
+/// Position reached by a migration: the keyspaces before `keyspace`, its keys before `key` in
+/// the order of the engine and the first `values` pairs of `key` are copied. Keys have more
+/// than one pair in keyspaces such as redb multimap tables.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrateCheckpoint {
+	pub keyspace: String,
+	pub key: Key,
+	pub values: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct MigrateOptions {
+	// Pairs read and written by each transaction, one batch is held in memory while copying
+	pub batch_size: usize,
+	// Checkpoint reported by an interrupted migration to resume from
+	pub resume: Option<MigrateCheckpoint>,
+}
+
+impl Default for MigrateOptions {
+	fn default() -> Self {
+		MigrateOptions {
+			batch_size: 1000,
+			resume: None,
+		}
+	}
+}
+
+/// Pairs copied by a migration once a batch is committed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrateProgress {
+	pub migrated: u64,
+	pub checkpoint: MigrateCheckpoint,
+}
+
+/// Number of pairs of a keyspace and a checksum which doesn't depend on their order, so
+/// engines sorting keys differently can be compared
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KeyspaceChecksum {
+	pub count: u64,
+	pub checksum: u64,
+}
+
+impl KeyspaceChecksum {
+	pub fn add(&mut self, key: &[u8], value: &[u8]) {
+		self.count += 1;
+		self.checksum = self.checksum.wrapping_add(pair_hash(key, value));
+	}
+}
+
+// FNV-1a of the length-prefixed key and value
+fn pair_hash(key: &[u8], value: &[u8]) -> u64 {
+	let mut hash: u64 = 0xcbf29ce484222325;
+	for bytes in [key, value] {
+		for byte in (bytes.len() as u64).to_be_bytes().iter().chain(bytes) {
+			hash ^= *byte as u64;
+			hash = hash.wrapping_mul(0x100000001b3);
+		}
+	}
+	hash
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyspaceReport {
+	pub keyspace: String,
+	pub source: KeyspaceChecksum,
+	pub target: KeyspaceChecksum,
+}
+
+impl KeyspaceReport {
+	pub fn verified(&self) -> bool {
+		self.source == self.target
+	}
+}
+
+/// Pairs copied by a migration and the checksums of each keyspace on both sides
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MigrateReport {
+	pub migrated: u64,
+	pub keyspaces: Vec<KeyspaceReport>,
+}
+
+impl MigrateReport {
+	pub fn verified(&self) -> bool {
+		self.keyspaces.iter().all(|keyspace| keyspace.verified())
+	}
+}
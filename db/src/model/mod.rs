@@ -1,8 +1,24 @@
 /// Model
 mod adapter;
+mod diff;
+mod import;
+mod migrate;
+mod options;
+mod plugin;
+mod snapshot;
+mod stats;
 mod tag;
 mod tx;
+mod watch;
 
 pub use adapter::*;
+pub use diff::*;
+pub use import::*;
+pub use migrate::*;
+pub use options::*;
+pub use plugin::*;
+pub use snapshot::*;
+pub use stats::*;
 pub use tag::*;
 pub use tx::*;
+pub use watch::*;
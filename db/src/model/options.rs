@@ -0,0 +1,33 @@
+use serde::Deserialize;
+
+/// Order of keys in the database, it must match the comparator the database was created with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Comparator {
+	#[default]
+	Bytewise,
+	ReverseBytewise,
+}
+
+/// Values larger than `min_blob_size` are stored in blob files instead of SST files
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct BlobOptions {
+	pub min_blob_size: u64,
+	pub blob_file_size: Option<u64>,
+	pub enable_blob_gc: bool,
+	pub blob_gc_age_cutoff: Option<f64>,
+}
+
+/// # Datastore options
+/// Settings a database has to be opened with, e.g. `{ "comparator": "reverse_bytewise",
+/// "ttl": 3600 }`. Engines ignore the options they don't support.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct DatastoreOptions {
+	pub max_open_files: Option<i32>,
+	pub comparator: Comparator,
+	// Time to live of values in seconds, for databases created with `DBWithTTL`
+	pub ttl: Option<u64>,
+	pub blob: Option<BlobOptions>,
+}
@@ -0,0 +1,94 @@
+use async_trait::async_trait;
+
+use crate::{
+	err::Error,
+	interface::{Key, KeyValuePair, Val},
+	TagBucket,
+};
+
+/// # Plugin adapter
+/// Object safe counterpart of `DatastoreAdapter`. Storage engines living outside of EDMA
+/// implement this trait and register a factory for their URI scheme using `register_adapter`,
+/// `Datastore::new` then resolves paths like `<scheme>:<path>` to the registered adapter.
+#[async_trait]
+pub trait PluginAdapter: Send + Sync {
+	// Create new database transaction
+	async fn transaction(&self, rw: bool) -> Result<Box<dyn PluginTransaction>, Error>;
+
+	// List keyspaces available in the database
+	fn keyspaces(&self) -> Result<Vec<String>, Error>;
+
+	fn path(&self) -> &str;
+}
+
+/// # Plugin transaction
+/// Object safe counterpart of `SimpleTransaction`, keys and values are passed as owned bytes.
+#[async_trait(?Send)]
+pub trait PluginTransaction {
+	// Check if closed
+	fn closed(&self) -> bool;
+
+	// Cancel a transaction
+	async fn cancel(&mut self) -> Result<(), Error>;
+
+	// Count number of items
+	async fn count(&mut self, tags: TagBucket) -> Result<usize, Error>;
+
+	// Commit a transaction
+	async fn commit(&mut self) -> Result<(), Error>;
+
+	// Check if a key exists
+	async fn exi(&self, key: Key, tags: TagBucket) -> Result<bool, Error>;
+
+	/// Fetch a key from the database
+	async fn get(&self, key: Key, tags: TagBucket) -> Result<Option<Val>, Error>;
+
+	/// Insert or update a key in the database
+	async fn set(&mut self, key: Key, val: Val, tags: TagBucket) -> Result<(), Error>;
+
+	/// Insert a key if it doesn't exist in the database, `Error::TxKeyAlreadyExists` otherwise
+	async fn put(&mut self, key: Key, val: Val, tags: TagBucket) -> Result<(), Error>;
+
+	/// Update a key only if its current value is `expected`, `None` expects a missing key
+	async fn cas(
+		&mut self,
+		key: Key,
+		expected: Option<Val>,
+		new: Val,
+		tags: TagBucket,
+	) -> Result<(), Error>;
+
+	/// Delete a key
+	async fn del(&mut self, key: Key, tags: TagBucket) -> Result<(), Error>;
+
+	// Iterate elements in key value store
+	async fn iterate(&self, tags: TagBucket) -> Result<Vec<Result<KeyValuePair, Error>>, Error>;
+
+	// Iterate elements with prefix in key value store
+	async fn prefix_iterate(
+		&self,
+		prefix: Key,
+		tags: TagBucket,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error>;
+
+	// Iterate elements with suffix in key value store
+	async fn suffix_iterate(
+		&self,
+		suffix: Key,
+		tags: TagBucket,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error>;
+
+	// Iterate at most `limit` elements from `start`, the C ABI has no range scan so the whole
+	// keyspace is read unless the plugin overrides it
+	async fn range_iterate(
+		&self,
+		start: Key,
+		limit: usize,
+		tags: TagBucket,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error> {
+		let pairs = self.iterate(tags).await?.into_iter().collect::<Result<Vec<_>, _>>()?;
+		let mut pairs: Vec<_> = pairs.into_iter().filter(|(k, _)| k >= &start).collect();
+		pairs.sort_by(|a, b| a.0.cmp(&b.0));
+		Ok(pairs.into_iter().take(limit).map(Ok).collect())
+	}
+}
@@ -0,0 +1,47 @@
+use crate::{
+	err::Error,
+	interface::{
+		kv::{Key, Val},
+		KeyValuePair,
+	},
+	TagBucket,
+};
+
+/// # Database snapshot
+/// Read only view of a datastore. Every read sees the data as it was when the snapshot was
+/// taken, changes written afterwards by this or another process are not visible.
+pub trait SimpleSnapshot {
+	/// Fetch a key from the snapshot
+	fn get(&self, key: Key, tags: TagBucket) -> Result<Option<Val>, Error>;
+
+	// Iterate elements of a keyspace
+	fn iterate(&self, tags: TagBucket) -> Result<Vec<Result<KeyValuePair, Error>>, Error>;
+
+	// Iterate elements with prefix of a keyspace
+	fn prefix_iterate(
+		&self,
+		prefix: Key,
+		tags: TagBucket,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error> {
+		let pairs = self.iterate(tags)?;
+		let matches = |pair: &Result<KeyValuePair, Error>| match pair {
+			Ok((k, _)) => k.starts_with(&prefix),
+			Err(_) => true,
+		};
+		Ok(pairs.into_iter().filter(matches).collect())
+	}
+
+	// Iterate elements with suffix of a keyspace
+	fn suffix_iterate(
+		&self,
+		suffix: Key,
+		tags: TagBucket,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error> {
+		let pairs = self.iterate(tags)?;
+		let matches = |pair: &Result<KeyValuePair, Error>| match pair {
+			Ok((k, _)) => k.ends_with(&suffix),
+			Err(_) => true,
+		};
+		Ok(pairs.into_iter().filter(matches).collect())
+	}
+}
@@ -0,0 +1,22 @@
+/// Files stored at a level of a LSM tree
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LevelStats {
+	pub level: i32,
+	pub files: u64,
+	pub size: u64,
+	pub entries: u64,
+}
+
+/// # Datastore statistics
+/// Physical layout of a datastore. Values an engine can't report are left empty, engine
+/// specific values such as redb fragmented bytes are listed in `properties`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DatastoreStats {
+	pub size_on_disk: Option<u64>,
+	pub estimated_keys: Option<u64>,
+	pub live_data_size: Option<u64>,
+	pub levels: Vec<LevelStats>,
+	pub properties: Vec<(String, String)>,
+	// Statistics dump of the engine, e.g. `rocksdb.stats`
+	pub raw: Option<String>,
+}
@@ -32,6 +32,10 @@ impl TagBucket {
 		self.0.insert(key, val);
 	}
 
+	pub fn is_empty(&self) -> bool {
+		self.0.is_empty()
+	}
+
 	pub fn unchecked_get(&self, key: TagKey) -> TagValue {
 		self.0.get(key).unwrap().clone()
 	}
@@ -80,7 +80,7 @@ pub trait SimpleTransaction {
 		tags: TagBucket,
 	) -> Result<(), Error>;
 
-	/// Insert a key if it doesn't exist in the database
+	/// Insert a key if it doesn't exist in the database, `Error::TxKeyAlreadyExists` otherwise
 	async fn put<K: Into<Key> + Send, V: Into<Key> + Send>(
 		&mut self,
 		key: K,
@@ -88,6 +88,15 @@ pub trait SimpleTransaction {
 		tags: TagBucket,
 	) -> Result<(), Error>;
 
+	/// Update a key only if its current value is `expected`, `None` expects a missing key
+	async fn cas<K: Into<Key> + Send, V: Into<Key> + Send>(
+		&mut self,
+		key: K,
+		expected: Option<V>,
+		new: V,
+		tags: TagBucket,
+	) -> Result<(), Error>;
+
 	/// Delete a key
 	async fn del<K: Into<Key> + Send>(&mut self, key: K, tags: TagBucket) -> Result<(), Error>;
 
@@ -107,4 +116,13 @@ pub trait SimpleTransaction {
 		suffix: S,
 		tags: TagBucket,
 	) -> Result<Vec<Result<KeyValuePair, Error>>, Error>;
+
+	// Iterate at most `limit` elements from the key `start` or the first key after it, in the
+	// order of the engine. An empty `start` iterates from the first key.
+	async fn range_iterate<S: Into<Key> + Send>(
+		&self,
+		start: S,
+		limit: usize,
+		tags: TagBucket,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error>;
 }
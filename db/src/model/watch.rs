@@ -0,0 +1,39 @@
+use crate::interface::kv::{Key, Val};
+
+/// # Watch event
+/// Change of a key seen by a subscription since it was last polled
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatchEvent {
+	Insert {
+		key: Key,
+		value: Val,
+	},
+	Update {
+		key: Key,
+		old: Val,
+		new: Val,
+	},
+	Delete {
+		key: Key,
+		old: Val,
+	},
+}
+
+impl WatchEvent {
+	pub fn key(&self) -> &Key {
+		match self {
+			WatchEvent::Insert {
+				key,
+				..
+			}
+			| WatchEvent::Update {
+				key,
+				..
+			}
+			| WatchEvent::Delete {
+				key,
+				..
+			} => key,
+		}
+	}
+}
@@ -1,5 +1,19 @@
-use crate::model::DatastoreAdapter;
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+
+use crate::interface::Key;
+use crate::interface::KeyValuePair;
+use crate::interface::Val;
+use crate::model::{
+	diff_values, ConflictPolicy, DatastoreAdapter, DatastoreOptions, DatastoreStats, ImportOptions,
+	ImportProgress, KeyDiff, KeyspaceChecksum, KeyspaceReport, MigrateCheckpoint, MigrateOptions,
+	MigrateProgress, MigrateReport, SimpleTransaction, DEFAULT_KEYSPACE,
+};
 use crate::Error;
+use crate::PluginAdapter;
+use crate::Snapshot;
+use crate::Subscription;
+use crate::TagBucket;
 use crate::Transaction;
 
 #[cfg(feature = "kv-redb")]
@@ -7,8 +21,92 @@ use super::ReDBAdapter;
 
 #[cfg(feature = "kv-rocksdb")]
 use super::RocksDBAdapter;
+#[cfg(feature = "kv-sled")]
 use super::SledAdapter;
 
+use super::registry::create_adapter;
+use super::watch::Source;
+
+// Pairs read at once by a diff from each side
+const DIFF_PAGE_SIZE: usize = 1000;
+// Pairs read at once by a range scan
+const RANGE_PAGE_SIZE: usize = 1024;
+
+// Start of the page after `pairs`, read from `start` leaving out its first `values` pairs:
+// the last key of the page and the number of its pairs read so far
+fn next_page(pairs: &[KeyValuePair], start: Key, values: usize) -> (Key, usize) {
+	let last = match pairs.last() {
+		Some((key, _)) => key.clone(),
+		None => return (start, values),
+	};
+	let last_values = pairs.iter().rev().take_while(|(key, _)| key == &last).count();
+	match last_values == pairs.len() && last == start {
+		true => (last, values + last_values),
+		false => (last, last_values),
+	}
+}
+
+/// Keys of a keyspace with their values, read a page at a time in the order of the engine
+struct PagedKeys<'a> {
+	ds: &'a Datastore,
+	tags: TagBucket,
+	pairs: VecDeque<KeyValuePair>,
+	// Last key read and the number of its values read so far, the next page starts there
+	last: Key,
+	values: usize,
+	done: bool,
+}
+
+impl<'a> PagedKeys<'a> {
+	fn new(ds: &'a Datastore, tags: TagBucket) -> Self {
+		PagedKeys {
+			ds,
+			tags,
+			pairs: VecDeque::new(),
+			last: vec![],
+			values: 0,
+			done: false,
+		}
+	}
+
+	// The next page is read once every pair of the page before is read
+	async fn fill(&mut self) -> Result<(), Error> {
+		if self.pairs.is_empty() && !self.done {
+			let page =
+				self.ds.read_page(&self.last, self.values, DIFF_PAGE_SIZE, &self.tags).await?;
+			self.done = page.is_empty();
+			self.pairs = page.into();
+		}
+		Ok(())
+	}
+
+	// Next key with all of its values, multimap tables read a pair for each value of a key
+	async fn next(&mut self) -> Result<Option<(Key, Vec<Val>)>, Error> {
+		self.fill().await?;
+		let (key, value) = match self.pairs.pop_front() {
+			Some(pair) => pair,
+			None => return Ok(None),
+		};
+		if key < self.last {
+			let path = self.ds.path();
+			return Err(Error::Ds(format!("Keys of {} aren't ordered by bytes", path)));
+		}
+		self.last = key.clone();
+		self.values = 1;
+		let mut values = vec![value];
+		loop {
+			self.fill().await?;
+			match self.pairs.front() {
+				Some((next, _)) if next == &key => {}
+				_ => break,
+			}
+			values.extend(self.pairs.pop_front().map(|(_, value)| value));
+			self.values += 1;
+		}
+		Ok(Some((key, values)))
+	}
+}
+
 #[derive(Copy, Clone)]
 pub struct DatastoreRef<'a> {
 	pub db: &'a Datastore,
@@ -30,6 +128,7 @@ pub enum Inner {
 	ReDB(ReDBAdapter),
 	#[cfg(feature = "kv-sled")]
 	Sled(SledAdapter),
+	Plugin(Box<dyn PluginAdapter>),
 }
 
 pub struct Datastore {
@@ -44,49 +143,532 @@ impl Default for Datastore {
 
 impl Datastore {
 	pub fn new(path: &str) -> Datastore {
-		match path {
+		Datastore::new_with_options(path, &DatastoreOptions::default())
+	}
+
+	// Open a datastore with the options it was created with, options not supported by the
+	// engine are ignored
+	pub fn new_with_options(path: &str, options: &DatastoreOptions) -> Datastore {
+		Datastore::try_new_with_options(path, options).unwrap()
+	}
+
+	/// Open a datastore like `new_with_options`, returning the error of an engine which can't
+	/// open it instead of panicking, e.g. of a database locked by another process
+	#[cfg_attr(not(feature = "kv-rocksdb"), allow(unused_variables))]
+	pub fn try_new_with_options(
+		path: &str,
+		options: &DatastoreOptions,
+	) -> Result<Datastore, Error> {
+		let inner = match path {
 			#[cfg(feature = "kv-rocksdb")]
-			s if s.starts_with("rocksdb:") => {
-				let db = RocksDBAdapter::new(s, None).unwrap();
+			s if s.starts_with("rocksdb:") => Inner::RocksDB(RocksDBAdapter::new_with_options(s, options)?),
+			#[cfg(feature = "kv-redb")]
+			s if s.starts_with("redb:") => Inner::ReDB(ReDBAdapter::new(s)?),
+			#[cfg(feature = "kv-sled")]
+			s if s.starts_with("sled:") => Inner::Sled(SledAdapter::new(s)?),
+			s => Inner::Plugin(create_adapter(s)?),
+		};
+		Ok(Datastore {
+			inner,
+		})
+	}
+
+	pub fn borrow(&self) -> DatastoreRef {
+		DatastoreRef::new(self)
+	}
+
+	pub fn path(&self) -> &str {
+		macro_rules! impl_transaction_method {
+			($($x: ident feat $f: expr),*) => {
+				match &self.inner {
+					$(
+						#[cfg(feature = $f)]
+						Inner::$x(v) => {
+							v.path()
+						}
+					)*
+					Inner::Plugin(v) => v.path(),
+				}
+			};
+		}
+		impl_transaction_method!(
+			RocksDB feat "kv-rocksdb",
+			ReDB feat "kv-redb",
+			Sled feat "kv-sled"
+		)
+	}
+
+	pub fn keyspaces(&self) -> Result<Vec<String>, Error> {
+		macro_rules! impl_transaction_method {
+			($($x: ident feat $f: expr),*) => {
+				match &self.inner {
+					$(
+						#[cfg(feature = $f)]
+						Inner::$x(v) => {
+							v.keyspaces()
+						}
+					)*
+					Inner::Plugin(v) => v.keyspaces(),
+				}
+			};
+		}
+		impl_transaction_method!(
+			RocksDB feat "kv-rocksdb",
+			ReDB feat "kv-redb",
+			Sled feat "kv-sled"
+		)
+	}
+
+	pub fn keyspace_type(&self, keyspace: &str) -> Result<Option<String>, Error> {
+		macro_rules! impl_transaction_method {
+			($($x: ident feat $f: expr),*) => {
+				match &self.inner {
+					$(
+						#[cfg(feature = $f)]
+						Inner::$x(v) => {
+							v.keyspace_type(keyspace)
+						}
+					)*
+					Inner::Plugin(_) => Ok(None),
+				}
+			};
+		}
+		impl_transaction_method!(
+			RocksDB feat "kv-rocksdb",
+			ReDB feat "kv-redb",
+			Sled feat "kv-sled"
+		)
+	}
+
+	pub fn stats(&self) -> Result<DatastoreStats, Error> {
+		macro_rules! impl_transaction_method {
+			($($x: ident feat $f: expr),*) => {
+				match &self.inner {
+					$(
+						#[cfg(feature = $f)]
+						Inner::$x(v) => {
+							v.stats()
+						}
+					)*
+					Inner::Plugin(_) => Ok(DatastoreStats::default()),
+				}
+			};
+		}
+		impl_transaction_method!(
+			RocksDB feat "kv-rocksdb",
+			ReDB feat "kv-redb",
+			Sled feat "kv-sled"
+		)
+	}
+
+	pub fn compact_range(
+		&self,
+		keyspace: Option<&str>,
+		from: Option<&[u8]>,
+		to: Option<&[u8]>,
+	) -> Result<(), Error> {
+		macro_rules! impl_transaction_method {
+			($($x: ident feat $f: expr),*) => {
+				match &self.inner {
+					$(
+						#[cfg(feature = $f)]
+						Inner::$x(v) => {
+							v.compact_range(keyspace, from, to)
+						}
+					)*
+					Inner::Plugin(_) => Err(Error::DsOperationNotSupported("compact_range")),
+				}
+			};
+		}
+		impl_transaction_method!(
+			RocksDB feat "kv-rocksdb",
+			ReDB feat "kv-redb",
+			Sled feat "kv-sled"
+		)
+	}
+
+	pub fn flush(&self) -> Result<(), Error> {
+		macro_rules! impl_transaction_method {
+			($($x: ident feat $f: expr),*) => {
+				match &self.inner {
+					$(
+						#[cfg(feature = $f)]
+						Inner::$x(v) => {
+							v.flush()
+						}
+					)*
+					Inner::Plugin(_) => Err(Error::DsOperationNotSupported("flush")),
+				}
+			};
+		}
+		impl_transaction_method!(
+			RocksDB feat "kv-rocksdb",
+			ReDB feat "kv-redb",
+			Sled feat "kv-sled"
+		)
+	}
+
+	pub fn checkpoint(&self, dest: &str) -> Result<(), Error> {
+		macro_rules! impl_transaction_method {
+			($($x: ident feat $f: expr),*) => {
+				match &self.inner {
+					$(
+						#[cfg(feature = $f)]
+						Inner::$x(v) => {
+							v.checkpoint(dest)
+						}
+					)*
+					Inner::Plugin(_) => Err(Error::DsOperationNotSupported("checkpoint")),
+				}
+			};
+		}
+		impl_transaction_method!(
+			RocksDB feat "kv-rocksdb",
+			ReDB feat "kv-redb",
+			Sled feat "kv-sled"
+		)
+	}
+
+	pub fn create_keyspace(&self, keyspace: &str) -> Result<(), Error> {
+		macro_rules! impl_transaction_method {
+			($($x: ident feat $f: expr),*) => {
+				match &self.inner {
+					$(
+						#[cfg(feature = $f)]
+						Inner::$x(v) => {
+							v.create_keyspace(keyspace)
+						}
+					)*
+					// Plugins are given the keyspace with every write
+					Inner::Plugin(_) => Ok(()),
+				}
+			};
+		}
+		impl_transaction_method!(
+			RocksDB feat "kv-rocksdb",
+			ReDB feat "kv-redb",
+			Sled feat "kv-sled"
+		)
+	}
 
-				Datastore {
-					inner: Inner::RocksDB(db),
+	// Take a snapshot to read a consistent state of the datastore across scans
+	pub fn snapshot(&self) -> Result<Snapshot, Error> {
+		macro_rules! impl_transaction_method {
+			($($x: ident feat $f: expr),*) => {
+				match &self.inner {
+					$(
+						#[cfg(feature = $f)]
+						Inner::$x(v) => {
+							let snapshot = v.snapshot()?;
+							Ok(Snapshot {
+								inner: super::snapshot::Inner::$x(snapshot),
+							})
+						}
+					)*
+					Inner::Plugin(_) => Err(Error::DsOperationNotSupported("snapshot")),
 				}
+			};
+		}
+		impl_transaction_method!(
+			RocksDB feat "kv-rocksdb",
+			ReDB feat "kv-redb",
+			Sled feat "kv-sled"
+		)
+	}
+
+	/// Compare a keyspace of this datastore with a keyspace of another datastore of any engine.
+	/// Both sides are read a page at a time and must be ordered by key bytes. Pages are read in
+	/// separate transactions, a write made during the comparison may only be found in part.
+	pub async fn diff(
+		&self,
+		tags: TagBucket,
+		other: &Datastore,
+		other_tags: TagBucket,
+	) -> Result<Vec<KeyDiff>, Error> {
+		let (mut left, mut right) = (PagedKeys::new(self, tags), PagedKeys::new(other, other_tags));
+		let (mut next_left, mut next_right) = (left.next().await?, right.next().await?);
+		let mut diffs = vec![];
+		loop {
+			let (key, left_values, right_values) = match (next_left.take(), next_right.take()) {
+				(None, None) => return Ok(diffs),
+				(Some((key, values)), None) => (key, values, vec![]),
+				(None, Some((key, values))) => (key, vec![], values),
+				(Some((left_key, left_values)), Some((right_key, right_values))) => {
+					match left_key.cmp(&right_key) {
+						Ordering::Less => {
+							next_right = Some((right_key, right_values));
+							(left_key, left_values, vec![])
+						}
+						Ordering::Greater => {
+							next_left = Some((left_key, left_values));
+							(right_key, vec![], right_values)
+						}
+						Ordering::Equal => (left_key, left_values, right_values),
+					}
+				}
+			};
+			diffs.extend(diff_values(&key, left_values, right_values));
+			if next_left.is_none() {
+				next_left = left.next().await?;
 			}
-			#[cfg(feature = "kv-redb")]
-			s if s.starts_with("redb:") => {
-				let db = ReDBAdapter::new(s).unwrap();
+			if next_right.is_none() {
+				next_right = right.next().await?;
+			}
+		}
+	}
 
-				Datastore {
-					inner: Inner::ReDB(db),
+	/// Write pairs to a keyspace in transactions of `batch_size` pairs, `progress` is called
+	/// after each batch is committed. A failed import keeps the batches committed before the
+	/// failure, Sled also keeps the pairs of the failed batch written before the failure.
+	pub async fn import<I, F>(
+		&self,
+		pairs: I,
+		tags: TagBucket,
+		options: &ImportOptions,
+		mut progress: F,
+	) -> Result<ImportProgress, Error>
+	where
+		I: IntoIterator<Item = KeyValuePair>,
+		F: FnMut(&ImportProgress),
+	{
+		let mut done = ImportProgress::default();
+		let mut pairs = pairs.into_iter().peekable();
+		while pairs.peek().is_some() {
+			let mut tx = self.transaction(true).await?;
+			let mut batch = ImportProgress::default();
+			for (key, value) in pairs.by_ref().take(options.batch_size.max(1)) {
+				let result = match options.conflict {
+					ConflictPolicy::Overwrite => tx.set(key, value, tags.clone()).await,
+					ConflictPolicy::Skip | ConflictPolicy::Fail => {
+						tx.put(key, value, tags.clone()).await
+					}
+				};
+				match result {
+					Ok(_) => batch.imported += 1,
+					Err(Error::TxKeyAlreadyExists) if options.conflict == ConflictPolicy::Skip => {
+						batch.skipped += 1
+					}
+					Err(err) => {
+						tx.cancel().await?;
+						return Err(err);
+					}
 				}
 			}
+			tx.commit().await?;
+			done.imported += batch.imported;
+			done.skipped += batch.skipped;
+			progress(&done);
+		}
+		Ok(done)
+	}
+
+	/// Tags reading and writing a keyspace, the default keyspace has none
+	pub fn keyspace_tags(&self, keyspace: &str) -> TagBucket {
+		let mut tags = crate::tag!();
+		match (&self.inner, keyspace) {
+			(_, DEFAULT_KEYSPACE) => {}
 			#[cfg(feature = "kv-sled")]
-			s if s.starts_with("sled:") => {
-				let db = SledAdapter::new(s).unwrap();
+			(Inner::Sled(_), keyspace) => tags.insert("tree", keyspace.to_string()),
+			(_, keyspace) => tags.insert("column_family", keyspace.to_string()),
+		}
+		tags
+	}
+
+	// Keyspaces copied by a migration, the default keyspace first so every engine has one
+	fn migrated_keyspaces(&self) -> Result<Vec<String>, Error> {
+		let mut keyspaces = self.keyspaces()?;
+		keyspaces.retain(|keyspace| keyspace != DEFAULT_KEYSPACE);
+		keyspaces.sort();
+		keyspaces.insert(0, DEFAULT_KEYSPACE.to_string());
+		Ok(keyspaces)
+	}
+
+	// Read at most `limit` pairs of a keyspace from `start` in the order of the engine, leaving
+	// out the first `skip` pairs of `start` which are already read. A default keyspace which
+	// doesn't exist yet, like the default table of redb before its first write, reads as empty
+	async fn read_page(
+		&self,
+		start: &[u8],
+		skip: usize,
+		limit: usize,
+		tags: &TagBucket,
+	) -> Result<Vec<KeyValuePair>, Error> {
+		let tx = self.transaction(false).await?;
+		let pairs = match tx.range_iterate(start, skip + limit, tags.clone()).await {
+			Ok(pairs) => pairs.into_iter().collect::<Result<Vec<_>, _>>()?,
+			Err(Error::DsNoColumnFamilyFound) if tags.is_empty() => vec![],
+			Err(err) => return Err(err),
+		};
+		let read = pairs.iter().take(skip).take_while(|(key, _)| key == start).count();
+		Ok(pairs.into_iter().skip(read).take(limit).collect())
+	}
 
-				Datastore {
-					inner: Inner::Sled(db),
+	/// Pairs from `start` in the order of the engine for as long as their keys are `within`
+	/// the range, read a page at a time. Paging stops at a page which doesn't move past the
+	/// last key read, so an engine which can't seek exactly to a key still ends the scan
+	pub async fn range_while<F: Fn(&[u8]) -> bool>(
+		&self,
+		start: &[u8],
+		tags: TagBucket,
+		within: F,
+	) -> Result<Vec<KeyValuePair>, Error> {
+		let mut result = vec![];
+		let (mut start, mut values) = (start.to_vec(), 0);
+		loop {
+			let pairs = self.read_page(&start, values, RANGE_PAGE_SIZE, &tags).await?;
+			let full = pairs.len() == RANGE_PAGE_SIZE;
+			let last = start.clone();
+			for (key, value) in pairs {
+				if !within(&key) || key < last {
+					return Ok(result);
 				}
+				values = match key == start {
+					true => values + 1,
+					false => 1,
+				};
+				start = key.clone();
+				result.push((key, value));
+			}
+			if !full {
+				return Ok(result);
 			}
-			_ => unimplemented!(),
 		}
 	}
 
-	pub fn borrow(&self) -> DatastoreRef {
-		DatastoreRef::new(self)
+	// Count and checksum a keyspace with a scan of its own, independent of the pages copied.
+	// The scan reads pages of the same size as the copy, so it holds a page at a time
+	async fn keyspace_checksum(
+		&self,
+		tags: &TagBucket,
+		limit: usize,
+	) -> Result<KeyspaceChecksum, Error> {
+		let mut sum = KeyspaceChecksum::default();
+		let (mut start, mut values) = (vec![], 0);
+		loop {
+			let pairs = self.read_page(&start, values, limit, tags).await?;
+			if pairs.is_empty() {
+				return Ok(sum);
+			}
+			(start, values) = next_page(&pairs, start, values);
+			for (key, value) in pairs {
+				sum.add(&key, &value);
+			}
+		}
 	}
 
-	pub fn path(&self) -> &str {
+	/// Copy every keyspace to another datastore of any engine, keyspaces keep their names and
+	/// are created on the target when missing. Pairs are read and written by batches of
+	/// `batch_size`, `progress` is called after each batch is committed with a checkpoint to
+	/// resume an interrupted migration from. Counts and checksums of each keyspace are compared
+	/// once every keyspace is copied.
+	pub async fn migrate<F: FnMut(&MigrateProgress)>(
+		&self,
+		target: &Datastore,
+		options: &MigrateOptions,
+		mut progress: F,
+	) -> Result<MigrateReport, Error> {
+		let keyspaces = self.migrated_keyspaces()?;
+		let limit = options.batch_size.max(1);
+		let first = match &options.resume {
+			Some(checkpoint) => {
+				let position =
+					keyspaces.iter().position(|keyspace| keyspace == &checkpoint.keyspace);
+				position.ok_or_else(|| {
+					Error::Ds(format!("Keyspace {} to resume from not found", checkpoint.keyspace))
+				})?
+			}
+			None => 0,
+		};
+
+		let mut report = MigrateReport::default();
+		for (index, keyspace) in keyspaces.iter().enumerate().skip(first) {
+			let (tags, target_tags) =
+				(self.keyspace_tags(keyspace), target.keyspace_tags(keyspace));
+			if keyspace != DEFAULT_KEYSPACE {
+				target.create_keyspace(keyspace)?;
+			}
+			// Pages follow the order of the engine, each starts at the last key of the page
+			// before it, whose pairs copied so far are skipped
+			let (mut start, mut values) = match &options.resume {
+				Some(checkpoint) if index == first => (checkpoint.key.clone(), checkpoint.values),
+				_ => (vec![], 0),
+			};
+			loop {
+				let pairs = self.read_page(&start, values, limit, &tags).await?;
+				if pairs.is_empty() {
+					break;
+				}
+				(start, values) = next_page(&pairs, start, values);
+				let copied = pairs.len() as u64;
+				let mut tx = target.transaction(true).await?;
+				for (key, value) in pairs {
+					if let Err(err) = tx.set(key, value, target_tags.clone()).await {
+						tx.cancel().await?;
+						return Err(err);
+					}
+				}
+				tx.commit().await?;
+				report.migrated += copied;
+				progress(&MigrateProgress {
+					migrated: report.migrated,
+					checkpoint: MigrateCheckpoint {
+						keyspace: keyspace.clone(),
+						key: start.clone(),
+						values,
+					},
+				});
+			}
+		}
+
+		// Keyspaces copied before an interruption are verified as well
+		for keyspace in keyspaces.iter() {
+			let (tags, target_tags) =
+				(self.keyspace_tags(keyspace), target.keyspace_tags(keyspace));
+			report.keyspaces.push(KeyspaceReport {
+				keyspace: keyspace.clone(),
+				source: self.keyspace_checksum(&tags, limit).await?,
+				target: target.keyspace_checksum(&target_tags, limit).await?,
+			});
+		}
+		Ok(report)
+	}
+
+	/// Subscribe to inserts, updates and deletes of keys starting with the prefix
+	pub async fn watch<P: Into<Key>>(
+		&self,
+		prefix: P,
+		tags: TagBucket,
+	) -> Result<Subscription, Error> {
+		let prefix = prefix.into();
+		let source = match &self.inner {
+			#[cfg(feature = "kv-sled")]
+			Inner::Sled(v) => Source::Sled(v.watch_prefix(&prefix, &tags)?),
+			_ => Source::Poll,
+		};
+		Subscription::new(self, prefix, tags, source).await
+	}
+
+	/// Subscribe to changes of keys starting with the prefix by scanning them on every poll.
+	/// Polls may be given the same database opened again, so it isn't kept open in between.
+	pub async fn poll_watch<P: Into<Key>>(
+		&self,
+		prefix: P,
+		tags: TagBucket,
+	) -> Result<Subscription, Error> {
+		Subscription::new(self, prefix.into(), tags, Source::Poll).await
+	}
+
+	pub fn options(&self) -> Vec<(String, String)> {
 		macro_rules! impl_transaction_method {
 			($($x: ident feat $f: expr),*) => {
 				match &self.inner {
 					$(
 						#[cfg(feature = $f)]
 						Inner::$x(v) => {
-							v.path()
+							v.options()
 						}
 					)*
+					Inner::Plugin(_) => vec![],
 				}
 			};
 		}
@@ -110,6 +692,12 @@ impl Datastore {
 							})
 						}
 					)*
+					Inner::Plugin(v) => {
+						let tx = v.transaction(write).await?;
+						Ok(Transaction {
+							inner: super::tx::Inner::Plugin(tx),
+						})
+					}
 				}
 			};
 		}
@@ -125,10 +713,15 @@ impl Datastore {
 mod test {
 	use crate::{
 		constant::{ColumnFamily, KEYSPACES},
-		tag, SimpleTransaction,
+		diff_scans, register_adapter, tag,
+		tests::MemoryAdapter,
+		ConflictPolicy, Error, ImportOptions, ImportProgress, KeyDiff, MigrateOptions,
+		MigrateProgress, PluginAdapter, SimpleTransaction, WatchEvent,
 	};
+	#[cfg(feature = "kv-rocksdb")]
+	use crate::{Comparator, DatastoreOptions};
 
-	use super::Datastore;
+	use super::{Datastore, DIFF_PAGE_SIZE, RANGE_PAGE_SIZE};
 
 	#[tokio::test]
 	async fn should_rocksdb_create_with_cf() {
@@ -155,6 +748,33 @@ mod test {
 		tx.commit().await.unwrap();
 	}
 
+	#[cfg(feature = "kv-rocksdb")]
+	#[tokio::test]
+	async fn should_rocksdb_open_with_options() {
+		let options = DatastoreOptions {
+			comparator: Comparator::ReverseBytewise,
+			ttl: Some(3600),
+			..Default::default()
+		};
+		let path = crate::util::generate_path("rocksdb", None);
+		let db = Datastore::new_with_options(&path, &options);
+
+		let cf_name = KEYSPACES.get(&ColumnFamily::TestSuite).unwrap();
+		let tags = tag!("column_family" => cf_name.clone());
+		let mut tx = db.transaction(true).await.unwrap();
+		tx.set("a", "first", tags.clone()).await.unwrap();
+		tx.set("b", "second", tags.clone()).await.unwrap();
+		tx.commit().await.unwrap();
+
+		// Keys are ordered by the comparator and values are read without their timestamp
+		let tx = db.transaction(false).await.unwrap();
+		let iter = tx.iterate(tags.clone()).await.unwrap();
+		let keys: Vec<_> = iter.into_iter().map(|pair| pair.unwrap().0).collect();
+		assert_eq!(keys, vec![b"b".to_vec(), b"a".to_vec()]);
+		let val = tx.get("a", tags).await.unwrap();
+		assert_eq!(val, Some(b"first".to_vec()));
+	}
+
 	#[tokio::test]
 	async fn should_redb_create() {
 		let db = Datastore::new("redb:../temp/redb");
@@ -177,6 +797,127 @@ mod test {
 		tx.commit().await.unwrap();
 	}
 
+	#[cfg(feature = "kv-redb")]
+	#[tokio::test]
+	async fn should_redb_read_typed_table() {
+		let path = crate::util::generate_path("redb", None);
+		{
+			let abs_path = crate::util::get_absolute_path(&path["redb:".len()..]);
+			let db = unsafe { redb::Database::create(abs_path.as_str()).unwrap() };
+			let tx = db.begin_write().unwrap();
+			{
+				let def = redb::TableDefinition::<u64, &str>::new("users");
+				let mut table = tx.open_table(def).unwrap();
+				table.insert(&1, &"alice").unwrap();
+				table.insert(&2, &"bob").unwrap();
+			}
+			tx.commit().unwrap();
+		}
+
+		let db = Datastore::new(&path);
+		assert!(db.keyspaces().unwrap().contains(&"users".to_string()));
+		assert_eq!(db.keyspace_type("users").unwrap(), Some("Table<u64, &str>".to_string()));
+
+		let tx = db.transaction(false).await.unwrap();
+		let tags = tag!("column_family" => "users".to_string());
+		let iter = tx.iterate(tags.clone()).await.unwrap();
+		assert!(iter.len() == 2);
+		let val = tx.get(2u64.to_be_bytes(), tags).await.unwrap();
+		assert_eq!(val, Some(b"bob".to_vec()));
+	}
+
+	#[cfg(feature = "kv-redb")]
+	#[tokio::test]
+	async fn should_redb_order_typed_keys() {
+		let path = crate::util::generate_path("redb", None);
+		{
+			let abs_path = crate::util::get_absolute_path(&path["redb:".len()..]);
+			let db = unsafe { redb::Database::create(abs_path.as_str()).unwrap() };
+			let tx = db.begin_write().unwrap();
+			{
+				let def = redb::TableDefinition::<u64, u64>::new("counts");
+				let mut table = tx.open_table(def).unwrap();
+				for key in 0..20u64 {
+					table.insert(&key, &(key * 2)).unwrap();
+				}
+			}
+			tx.commit().unwrap();
+		}
+
+		// Integers are big endian, 10 sorts after 2 as it does in the table
+		let db = Datastore::new(&path);
+		let tx = db.transaction(false).await.unwrap();
+		let tags = tag!("column_family" => "counts".to_string());
+		let keys: Vec<_> =
+			tx.iterate(tags.clone()).await.unwrap().into_iter().map(|p| p.unwrap().0).collect();
+		let expected: Vec<_> = (0..20u64).map(|key| key.to_be_bytes().to_vec()).collect();
+		assert_eq!(keys, expected);
+		let page = tx.range_iterate(9u64.to_be_bytes(), 3, tags.clone()).await.unwrap();
+		let page: Vec<_> = page.into_iter().map(|pair| pair.unwrap()).collect();
+		assert_eq!(page[0], (9u64.to_be_bytes().to_vec(), 18u64.to_be_bytes().to_vec()));
+		assert_eq!(page[2].0, 11u64.to_be_bytes());
+		let diff = db.diff(tags.clone(), &db, tags).await.unwrap();
+		assert!(diff.is_empty());
+	}
+
+	#[cfg(feature = "kv-redb")]
+	#[tokio::test]
+	async fn should_redb_range_typed_table() {
+		let path = crate::util::generate_path("redb", None);
+		let count = RANGE_PAGE_SIZE as u64 * 2 + 10;
+		{
+			let abs_path = crate::util::get_absolute_path(&path["redb:".len()..]);
+			let db = unsafe { redb::Database::create(abs_path.as_str()).unwrap() };
+			let tx = db.begin_write().unwrap();
+			{
+				let def = redb::TableDefinition::<u64, u64>::new("counts");
+				let mut table = tx.open_table(def).unwrap();
+				for key in 0..count {
+					table.insert(&key, &key).unwrap();
+				}
+			}
+			tx.commit().unwrap();
+		}
+
+		// Pages past the first one move on from the last key instead of starting over
+		let db = Datastore::new(&path);
+		let tags = tag!("column_family" => "counts".to_string());
+		let end = (count - 5).to_be_bytes();
+		let pairs = db.range_while(&2u64.to_be_bytes(), tags, |key| key < &end[..]).await.unwrap();
+		let keys: Vec<_> = pairs.into_iter().map(|(key, _)| key).collect();
+		let expected: Vec<_> = (2..count - 5).map(|key| key.to_be_bytes().to_vec()).collect();
+		assert_eq!(keys, expected);
+	}
+
+	#[cfg(feature = "kv-redb")]
+	#[tokio::test]
+	async fn should_redb_read_multimap_table() {
+		let path = crate::util::generate_path("redb", None);
+		{
+			let abs_path = crate::util::get_absolute_path(&path["redb:".len()..]);
+			let db = unsafe { redb::Database::create(abs_path.as_str()).unwrap() };
+			let tx = db.begin_write().unwrap();
+			{
+				let def = redb::MultimapTableDefinition::<&str, u64>::new("scores");
+				let mut table = tx.open_multimap_table(def).unwrap();
+				table.insert(&"alice", &1).unwrap();
+				table.insert(&"alice", &2).unwrap();
+				table.insert(&"bob", &3).unwrap();
+			}
+			tx.commit().unwrap();
+		}
+
+		let db = Datastore::new(&path);
+		assert!(db.keyspaces().unwrap().contains(&"scores".to_string()));
+		let table_type = db.keyspace_type("scores").unwrap();
+		assert_eq!(table_type, Some("MultimapTable<&str, u64>".to_string()));
+
+		let tx = db.transaction(false).await.unwrap();
+		let tags = tag!("column_family" => "scores".to_string());
+		let iter = tx.prefix_iterate("alice", tags).await.unwrap();
+		assert!(iter.len() == 2);
+	}
+
 	#[tokio::test]
 	async fn should_sled_create() {
 		let db = Datastore::new("sled:../temp");
@@ -223,5 +964,461 @@ mod test {
 		let iter = tx.iterate(tags.clone()).await.unwrap();
 		assert!(iter.len() == 3);
 		tx.commit().await.unwrap();
+		assert!(db.keyspaces().unwrap().contains(tree_name));
+	}
+
+	#[tokio::test]
+	async fn should_sled_stats() {
+		let db = Datastore::new("sled:../temp/stats");
+		let mut tx = db.transaction(true).await.unwrap();
+		tx.set("stats key", "stats value", tag!()).await.unwrap();
+		tx.commit().await.unwrap();
+
+		let stats = db.stats().unwrap();
+		assert!(stats.size_on_disk.is_some());
+		assert!(stats.estimated_keys.unwrap() >= 1);
+		assert!(stats.levels.is_empty());
+	}
+
+	#[tokio::test]
+	async fn should_sled_snapshot() {
+		let db = Datastore::new("sled:../temp/snapshot");
+		let mut tx = db.transaction(true).await.unwrap();
+		tx.set("snapshot key", "snapshot value", tag!()).await.unwrap();
+		tx.del("snapshot key 2", tag!()).await.unwrap();
+		tx.commit().await.unwrap();
+
+		let snapshot = db.snapshot().unwrap();
+		let len = snapshot.iterate(tag!()).unwrap().len();
+		let mut tx = db.transaction(true).await.unwrap();
+		tx.set("snapshot key", "new value", tag!()).await.unwrap();
+		tx.set("snapshot key 2", "snapshot value 2", tag!()).await.unwrap();
+		tx.commit().await.unwrap();
+
+		assert_eq!(snapshot.iterate(tag!()).unwrap().len(), len);
+		assert_eq!(snapshot.prefix_iterate("snapshot key 2", tag!()).unwrap().len(), 0);
+		let val = snapshot.get("snapshot key", tag!()).unwrap();
+		assert_eq!(val, Some("snapshot value".as_bytes().to_vec()));
+		assert!(db.snapshot().unwrap().get("snapshot key 2", tag!()).unwrap().is_some());
+	}
+
+	#[tokio::test]
+	async fn should_sled_snapshot_before_read() {
+		let db = Datastore::new("sled:../temp/snapshot-read");
+		let tags = tag!("tree" => "snapshot tree".to_string());
+		let mut tx = db.transaction(true).await.unwrap();
+		for pair in tx.iterate(tags.clone()).await.unwrap() {
+			tx.del(pair.unwrap().0, tags.clone()).await.unwrap();
+		}
+		tx.set("tree key", "tree value", tags.clone()).await.unwrap();
+		tx.set("tree key 2", "tree value 2", tags.clone()).await.unwrap();
+		tx.commit().await.unwrap();
+
+		// Nothing is read before the writes, the snapshot still sees the state it was taken at
+		let snapshot = db.snapshot().unwrap();
+		let mut tx = db.transaction(true).await.unwrap();
+		tx.set("tree key", "new tree value", tags.clone()).await.unwrap();
+		tx.del("tree key 2", tags.clone()).await.unwrap();
+		tx.set("tree key 3", "tree value 3", tags.clone()).await.unwrap();
+		tx.commit().await.unwrap();
+
+		let val = snapshot.get("tree key", tags.clone()).unwrap();
+		assert_eq!(val, Some("tree value".as_bytes().to_vec()));
+		let pairs: Vec<_> =
+			snapshot.iterate(tags.clone()).unwrap().into_iter().map(|p| p.unwrap().0).collect();
+		assert_eq!(pairs, vec![b"tree key".to_vec(), b"tree key 2".to_vec()]);
+		let created = tag!("tree" => "tree created later".to_string());
+		assert!(snapshot.iterate(created).unwrap().is_empty());
+	}
+
+	#[tokio::test]
+	async fn should_sled_checkpoint() {
+		let db = Datastore::new("sled:../temp/checkpoint");
+		let tree = tag!("tree" => "checkpoint tree".to_string());
+		let mut tx = db.transaction(true).await.unwrap();
+		tx.set("checkpoint key", "checkpoint value", tag!()).await.unwrap();
+		tx.set("tree key", "tree value", tree.clone()).await.unwrap();
+		tx.commit().await.unwrap();
+		assert!(db.flush().is_ok());
+		assert!(matches!(
+			db.compact_range(None, None, None),
+			Err(Error::DsOperationNotSupported(_))
+		));
+
+		let dest = format!("../temp/checkpoint-{}", std::process::id());
+		db.checkpoint(&dest).unwrap();
+		assert!(matches!(db.checkpoint(&dest), Err(Error::DsCheckpointExists(_))));
+
+		let copy = Datastore::new(&format!("sled:{}", dest));
+		let tx = copy.transaction(false).await.unwrap();
+		let val = tx.get("checkpoint key", tag!()).await.unwrap();
+		assert_eq!(val, Some("checkpoint value".as_bytes().to_vec()));
+		let val = tx.get("tree key", tree).await.unwrap();
+		assert_eq!(val, Some("tree value".as_bytes().to_vec()));
+		std::fs::remove_dir_all(&dest).unwrap();
+	}
+
+	#[tokio::test]
+	async fn should_sled_watch() {
+		let db = Datastore::new("sled:../temp/watch");
+		let mut tx = db.transaction(true).await.unwrap();
+		tx.set("watch a", "1", tag!()).await.unwrap();
+		tx.del("watch b", tag!()).await.unwrap();
+		tx.commit().await.unwrap();
+
+		let mut subscription = db.watch("watch ", tag!()).await.unwrap();
+		assert_eq!(subscription.rows().count(), 1);
+		assert!(subscription.poll(&db).await.unwrap().is_empty());
+
+		let mut tx = db.transaction(true).await.unwrap();
+		tx.set("watch a", "2", tag!()).await.unwrap();
+		tx.set("watch b", "1", tag!()).await.unwrap();
+		tx.set("unwatched", "1", tag!()).await.unwrap();
+		tx.del("watch a", tag!()).await.unwrap();
+		tx.commit().await.unwrap();
+
+		let events = subscription.poll(&db).await.unwrap();
+		let (a, b) = ("watch a".as_bytes().to_vec(), "watch b".as_bytes().to_vec());
+		assert_eq!(
+			events,
+			vec![
+				WatchEvent::Update {
+					key: a.to_vec(),
+					old: "1".as_bytes().to_vec(),
+					new: "2".as_bytes().to_vec(),
+				},
+				WatchEvent::Insert {
+					key: b.to_vec(),
+					value: "1".as_bytes().to_vec(),
+				},
+				WatchEvent::Delete {
+					key: a,
+					old: "2".as_bytes().to_vec(),
+				},
+			]
+		);
+		assert_eq!(subscription.rows().map(|(k, _)| k.to_vec()).collect::<Vec<_>>(), vec![b]);
+	}
+
+	#[tokio::test]
+	async fn should_sled_poll_watch() {
+		let path = crate::util::generate_path("sled", None);
+		let db = Datastore::new(&path);
+		let mut subscription = db.poll_watch("watch ", tag!()).await.unwrap();
+		drop(db);
+
+		// The database is closed between polls, another writer can open it meanwhile
+		let db = Datastore::new(&path);
+		let mut tx = db.transaction(true).await.unwrap();
+		tx.set("watch a", "1", tag!()).await.unwrap();
+		tx.commit().await.unwrap();
+		drop((tx, db));
+
+		let events = subscription.poll(&Datastore::new(&path)).await.unwrap();
+		assert_eq!(
+			events,
+			vec![WatchEvent::Insert {
+				key: "watch a".as_bytes().to_vec(),
+				value: "1".as_bytes().to_vec(),
+			}]
+		);
+	}
+
+	#[tokio::test]
+	async fn should_sled_range() {
+		let db = Datastore::new("sled:../temp/range");
+		let mut tx = db.transaction(true).await.unwrap();
+		for pair in tx.iterate(tag!()).await.unwrap() {
+			tx.del(pair.unwrap().0, tag!()).await.unwrap();
+		}
+		for index in 0..RANGE_PAGE_SIZE * 2 + 10 {
+			tx.set(format!("key {:05}", index), "value", tag!()).await.unwrap();
+		}
+		tx.commit().await.unwrap();
+
+		let end = b"key 02050".to_vec();
+		let pairs = db.range_while(b"key 00003", tag!(), |key| key < &end[..]).await.unwrap();
+		assert_eq!(pairs.len(), 2047);
+		assert_eq!(pairs[0].0, b"key 00003");
+		assert_eq!(pairs[2046].0, b"key 02049");
+	}
+
+	#[tokio::test]
+	async fn should_sled_diff() {
+		let left = Datastore::new("sled:../temp/diff-left");
+		let right = Datastore::new("sled:../temp/diff-right");
+		for db in [&left, &right] {
+			let mut tx = db.transaction(true).await.unwrap();
+			for pair in tx.iterate(tag!()).await.unwrap() {
+				tx.del(pair.unwrap().0, tag!()).await.unwrap();
+			}
+			tx.set("same", "value", tag!()).await.unwrap();
+			// Keys past the first page of a diff
+			for index in 0..DIFF_PAGE_SIZE {
+				tx.set(format!("page {:04}", index), "value", tag!()).await.unwrap();
+			}
+			tx.commit().await.unwrap();
+		}
+		let mut tx = left.transaction(true).await.unwrap();
+		tx.set("page 9999", "value", tag!()).await.unwrap();
+		tx.set("changed", "left", tag!()).await.unwrap();
+		tx.set("removed", "value", tag!()).await.unwrap();
+		tx.commit().await.unwrap();
+		let mut tx = right.transaction(true).await.unwrap();
+		tx.set("added", "value", tag!()).await.unwrap();
+		tx.set("changed", "right", tag!()).await.unwrap();
+		tx.commit().await.unwrap();
+
+		let diffs = left.diff(tag!(), &right, tag!()).await.unwrap();
+		assert_eq!(
+			diffs,
+			vec![
+				KeyDiff::Added {
+					key: "added".as_bytes().to_vec(),
+					value: "value".as_bytes().to_vec(),
+				},
+				KeyDiff::Changed {
+					key: "changed".as_bytes().to_vec(),
+					left: "left".as_bytes().to_vec(),
+					right: "right".as_bytes().to_vec(),
+				},
+				KeyDiff::Removed {
+					key: "page 9999".as_bytes().to_vec(),
+					value: "value".as_bytes().to_vec(),
+				},
+				KeyDiff::Removed {
+					key: "removed".as_bytes().to_vec(),
+					value: "value".as_bytes().to_vec(),
+				},
+			]
+		);
+		assert!(right.diff(tag!(), &right, tag!()).await.unwrap().is_empty());
+	}
+
+	#[test]
+	fn should_diff_multimap_values() {
+		let pairs = |pairs: &[(&str, &str)]| {
+			let pairs =
+				pairs.iter().map(|(k, v)| Ok((k.as_bytes().to_vec(), v.as_bytes().to_vec())));
+			pairs.collect::<Vec<_>>()
+		};
+		let left = pairs(&[("a", "1"), ("a", "2"), ("a", "2"), ("b", "1")]);
+		let right = pairs(&[("a", "2"), ("a", "3"), ("a", "1"), ("b", "1"), ("b", "2")]);
+		assert_eq!(
+			diff_scans(left, right).unwrap(),
+			vec![
+				KeyDiff::Removed {
+					key: "a".as_bytes().to_vec(),
+					value: "2".as_bytes().to_vec(),
+				},
+				KeyDiff::Added {
+					key: "a".as_bytes().to_vec(),
+					value: "3".as_bytes().to_vec(),
+				},
+				KeyDiff::Added {
+					key: "b".as_bytes().to_vec(),
+					value: "2".as_bytes().to_vec(),
+				},
+			]
+		);
+	}
+
+	#[tokio::test]
+	async fn should_sled_migrate() {
+		for path in ["../temp/migrate-source", "../temp/migrate-target"] {
+			let _ = std::fs::remove_dir_all(path);
+		}
+		let source = Datastore::new("sled:../temp/migrate-source");
+		let target = Datastore::new("sled:../temp/migrate-target");
+		let users = tag!("tree" => "users".to_string());
+		let mut tx = source.transaction(true).await.unwrap();
+		for i in 0..3u8 {
+			tx.set(vec![i], vec![i], tag!()).await.unwrap();
+			tx.set(vec![i, i], vec![i], users.clone()).await.unwrap();
+		}
+		tx.commit().await.unwrap();
+
+		let options = MigrateOptions {
+			batch_size: 2,
+			..Default::default()
+		};
+		let mut checkpoints = vec![];
+		let progress = |p: &MigrateProgress| checkpoints.push(p.checkpoint.clone());
+		let report = source.migrate(&target, &options, progress).await.unwrap();
+		assert_eq!(report.migrated, 6);
+		assert!(report.verified());
+		assert_eq!(report.keyspaces.len(), 2);
+		assert_eq!(checkpoints.len(), 4);
+		let tx = target.transaction(false).await.unwrap();
+		assert_eq!(tx.get(vec![2, 2], users.clone()).await.unwrap(), Some(vec![2]));
+
+		// Resuming after the first batch of the users tree copies its last key only
+		let options = MigrateOptions {
+			batch_size: 2,
+			resume: Some(checkpoints[2].clone()),
+		};
+		let report = source.migrate(&target, &options, |_| {}).await.unwrap();
+		assert_eq!(report.migrated, 1);
+		assert!(report.verified());
+
+		let mut tx = target.transaction(true).await.unwrap();
+		tx.del(vec![0], tag!()).await.unwrap();
+		tx.commit().await.unwrap();
+		let report = source.migrate(&target, &options, |_| {}).await.unwrap();
+		assert!(!report.verified());
+		assert_eq!(report.keyspaces[0].target.count, 2);
+	}
+
+	#[cfg(feature = "kv-rocksdb")]
+	#[tokio::test]
+	async fn should_rocksdb_migrate_reverse_comparator() {
+		let options = DatastoreOptions {
+			comparator: Comparator::ReverseBytewise,
+			..Default::default()
+		};
+		let source =
+			Datastore::new_with_options(&crate::util::generate_path("rocksdb", None), &options);
+		let target = Datastore::new(&crate::util::generate_path("sled", None));
+		let mut tx = source.transaction(true).await.unwrap();
+		for i in 0..5u8 {
+			tx.set(vec![i], vec![i], tag!()).await.unwrap();
+		}
+		tx.commit().await.unwrap();
+
+		// Pages follow the reverse order, from the largest key
+		let options = MigrateOptions {
+			batch_size: 2,
+			..Default::default()
+		};
+		let mut checkpoints = vec![];
+		let progress = |p: &MigrateProgress| checkpoints.push(p.checkpoint.key.clone());
+		let report = source.migrate(&target, &options, progress).await.unwrap();
+		assert_eq!(report.migrated, 5);
+		assert_eq!(checkpoints, vec![vec![3], vec![1], vec![0]]);
+		assert_eq!(report.keyspaces[0].source.count, 5);
+		assert!(report.verified());
+	}
+
+	#[cfg(feature = "kv-redb")]
+	#[tokio::test]
+	async fn should_redb_migrate_multimap_table() {
+		let path = crate::util::generate_path("redb", None);
+		{
+			let abs_path = crate::util::get_absolute_path(&path["redb:".len()..]);
+			let db = unsafe { redb::Database::create(abs_path.as_str()).unwrap() };
+			let tx = db.begin_write().unwrap();
+			{
+				let def = redb::MultimapTableDefinition::<&str, u64>::new("scores");
+				let mut table = tx.open_multimap_table(def).unwrap();
+				for score in 1..4 {
+					table.insert(&"alice", &score).unwrap();
+				}
+				table.insert(&"bob", &4).unwrap();
+			}
+			tx.commit().unwrap();
+		}
+		let source = Datastore::new(&path);
+		let target = Datastore::new(&crate::util::generate_path("sled", None));
+
+		// Pages end in the middle of the values of alice, none of them is skipped
+		let options = MigrateOptions {
+			batch_size: 2,
+			..Default::default()
+		};
+		let mut checkpoints = vec![];
+		let progress = |p: &MigrateProgress| checkpoints.push(p.checkpoint.clone());
+		let report = source.migrate(&target, &options, progress).await.unwrap();
+		assert_eq!(report.migrated, 4);
+		let values: Vec<_> = checkpoints.iter().map(|checkpoint| checkpoint.values).collect();
+		assert_eq!(values, vec![2, 1]);
+		// A tree keeps one value by key, the verification reports the values lost
+		let scores = report.keyspaces.iter().find(|k| k.keyspace == "scores").unwrap();
+		assert_eq!(scores.source.count, 4);
+		assert_eq!(scores.target.count, 2);
+		assert!(!report.verified());
+	}
+
+	#[tokio::test]
+	async fn should_sled_import() {
+		let db = Datastore::new("sled:../temp/import");
+		let tags = tag!("tree" => "import".to_string());
+		let mut tx = db.transaction(true).await.unwrap();
+		for pair in tx.iterate(tags.clone()).await.unwrap() {
+			tx.del(pair.unwrap().0, tags.clone()).await.unwrap();
+		}
+		tx.commit().await.unwrap();
+
+		let pairs: Vec<_> = (0..5u8).map(|i| (vec![i], vec![i])).collect();
+		let options = ImportOptions {
+			batch_size: 2,
+			conflict: ConflictPolicy::Fail,
+		};
+		let mut batches = vec![];
+		let progress = |p: &ImportProgress| batches.push(p.imported);
+		db.import(pairs.to_vec(), tags.clone(), &options, progress).await.unwrap();
+		assert_eq!(batches, vec![2, 4, 5]);
+
+		let result = db.import(pairs.to_vec(), tags.clone(), &options, |_| {}).await;
+		assert!(matches!(result, Err(Error::TxKeyAlreadyExists)));
+
+		let changed = vec![(vec![0], vec![9]), (vec![5], vec![5])];
+		let options = ImportOptions {
+			conflict: ConflictPolicy::Skip,
+			..Default::default()
+		};
+		let done = db.import(changed.to_vec(), tags.clone(), &options, |_| {}).await.unwrap();
+		assert_eq!(
+			done,
+			ImportProgress {
+				imported: 1,
+				skipped: 1
+			}
+		);
+		let tx = db.transaction(false).await.unwrap();
+		assert_eq!(tx.get(vec![0], tags.clone()).await.unwrap(), Some(vec![0]));
+
+		let done =
+			db.import(changed, tags.clone(), &ImportOptions::default(), |_| {}).await.unwrap();
+		assert_eq!(done.imported, 2);
+		let tx = db.transaction(false).await.unwrap();
+		assert_eq!(tx.get(vec![0], tags).await.unwrap(), Some(vec![9]));
+	}
+
+	#[tokio::test]
+	async fn should_plugin_create() {
+		register_adapter("memory", |path| {
+			Ok(Box::new(MemoryAdapter::new(path)) as Box<dyn PluginAdapter>)
+		})
+		.unwrap();
+		assert!(matches!(
+			register_adapter("sled", |path| {
+				Ok(Box::new(MemoryAdapter::new(path)) as Box<dyn PluginAdapter>)
+			}),
+			Err(Error::DsSchemeReserved(_))
+		));
+
+		let db = Datastore::new("memory:plugin");
+		assert_eq!(db.path(), "plugin");
+
+		let key1 = "plugin => hello world";
+		let key2 = "plugin => this is a key";
+		let val1 = "plugin => mock value";
+		let val2 = "plugin => mock value 2";
+
+		let mut tx = db.transaction(true).await.unwrap();
+		tx.set(key1, val1, tag!()).await.unwrap();
+		tx.put(key2, val2, tag!()).await.unwrap();
+		assert!(tx.put(key2, val2, tag!()).await.is_err());
+		let iter = tx.prefix_iterate("plugin", tag!()).await.unwrap();
+		assert!(iter.len() == 2);
+		tx.commit().await.unwrap();
+
+		let mut subscription = db.watch("plugin", tag!()).await.unwrap();
+		assert_eq!(subscription.rows().count(), 2);
+		let mut tx = db.transaction(true).await.unwrap();
+		tx.del(key1, tag!()).await.unwrap();
+		tx.commit().await.unwrap();
+		let events = subscription.poll(&db).await.unwrap();
+		assert!(matches!(events[..], [WatchEvent::Delete { .. }]));
 	}
 }
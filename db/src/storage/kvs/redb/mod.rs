@@ -1,14 +1,20 @@
+mod snapshot;
+mod table;
 pub mod tx;
 pub mod ty;
 
 use async_trait::async_trait;
-use redb::Database;
+use redb::{Database, TableDefinition};
+pub use snapshot::*;
+use std::{fs, path::Path};
+use table::read_snapshot_typed_table;
 pub use tx::*;
 pub use ty::*;
 
 use crate::{
 	util::{generate_path, get_absolute_path},
-	DBTransaction, DatastoreAdapter, Error, StorageAdapter, StorageAdapterName, StorageVariant,
+	DBTransaction, DatastoreAdapter, DatastoreStats, Error, StorageAdapter, StorageAdapterName,
+	StorageVariant,
 };
 pub struct ReDBAdapter(StorageAdapter<DBType>);
 
@@ -35,6 +41,7 @@ impl ReDBAdapter {
 #[async_trait]
 impl DatastoreAdapter for ReDBAdapter {
 	type Transaction = ReDBTransaction;
+	type Snapshot = ReDBSnapshot;
 
 	fn default() -> Self {
 		let path = &generate_path("redb", None);
@@ -49,6 +56,96 @@ impl DatastoreAdapter for ReDBAdapter {
 		&self.0.path
 	}
 
+	fn keyspaces(&self) -> Result<Vec<String>, Error> {
+		let inner = self.get_initialized_inner().unwrap();
+		let tx = inner.db_instance.begin_read()?;
+		let tables = tx.list_tables()?;
+		let multimap_tables = tx.list_multimap_tables()?;
+		Ok(tables.chain(multimap_tables).collect())
+	}
+
+	fn keyspace_type(&self, keyspace: &str) -> Result<Option<String>, Error> {
+		let inner = self.get_initialized_inner().unwrap();
+		let tx = inner.db_instance.begin_read()?;
+		let def = TableDefinition::<&[u8], &[u8]>::new(keyspace);
+		let type_name = match tx.open_table(def) {
+			Ok(_) => Some(format!("Table<{}, {}>", "&[u8]", "&[u8]")),
+			// No pair is read, only the types the table opens with
+			Err(redb::Error::TableTypeMismatch(_)) => {
+				read_snapshot_typed_table(&tx, keyspace, &[], 0)?.map(|table| table.type_name())
+			}
+			// A read transaction doesn't create tables, a missing table has no type
+			Err(_) => None,
+		};
+		Ok(type_name)
+	}
+
+	fn stats(&self) -> Result<DatastoreStats, Error> {
+		let inner = self.get_initialized_inner().unwrap();
+		let tx = inner.db_instance.begin_write()?;
+		let stats = tx.stats()?;
+		tx.abort()?;
+
+		let properties = [
+			("stored_bytes", stats.stored_bytes()),
+			("metadata_bytes", stats.metadata_bytes()),
+			("fragmented_bytes", stats.fragmented_bytes()),
+			("tree_height", stats.tree_height()),
+			("leaf_pages", stats.leaf_pages()),
+			("branch_pages", stats.branch_pages()),
+			("free_pages", stats.free_pages()),
+			("page_size", stats.page_size()),
+		];
+		Ok(DatastoreStats {
+			size_on_disk: fs::metadata(&inner.path).ok().map(|meta| meta.len()),
+			live_data_size: Some(stats.stored_bytes() as u64),
+			properties: properties.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+			..Default::default()
+		})
+	}
+
+	// Redb only has `Database::compact` from 1.x on, which the adapter isn't ported to yet.
+	// Until then pages freed by deletes are reused by later writes but the file never shrinks.
+	fn compact_range(
+		&self,
+		_keyspace: Option<&str>,
+		_from: Option<&[u8]>,
+		_to: Option<&[u8]>,
+	) -> Result<(), Error> {
+		Err(Error::DsOperationNotSupported("compact_range"))
+	}
+
+	fn flush(&self) -> Result<(), Error> {
+		// Every commit is written to disk, nothing is buffered
+		Ok(())
+	}
+
+	fn checkpoint(&self, dest: &str) -> Result<(), Error> {
+		if Path::new(dest).exists() {
+			return Err(Error::DsCheckpointExists(dest.to_string()));
+		}
+		let inner = self.get_initialized_inner().unwrap();
+		// Holding the write transaction keeps other writers out while the file is copied
+		let tx = inner.db_instance.begin_write()?;
+		let copied = fs::copy(&inner.path, dest);
+		tx.abort()?;
+		copied.map(|_| ()).map_err(|err| Error::Ds(err.to_string()))
+	}
+
+	fn create_keyspace(&self, keyspace: &str) -> Result<(), Error> {
+		let inner = self.get_initialized_inner().unwrap();
+		let tx = inner.db_instance.begin_write()?;
+		// Opening a table in a write transaction creates it
+		tx.open_table(TableDefinition::<&[u8], &[u8]>::new(keyspace))?;
+		tx.commit()?;
+		Ok(())
+	}
+
+	fn snapshot(&self) -> Result<ReDBSnapshot, Error> {
+		let db = &self.get_initialized_inner().unwrap().db_instance;
+		ReDBSnapshot::new(db.clone())
+	}
+
 	async fn transaction(&self, w: bool) -> Result<Self::Transaction, Error> {
 		let inner = self.get_initialized_inner().unwrap();
 		let db = &inner.db_instance;
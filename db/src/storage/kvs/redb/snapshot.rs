@@ -0,0 +1,78 @@
+use std::{pin::Pin, sync::Arc};
+
+use redb::{ReadableTable, TableDefinition};
+
+use super::{
+	table::read_snapshot_typed_table,
+	ty::{DBType, SnapshotType},
+};
+use crate::{
+	interface::{Key, KeyValuePair, Val},
+	Error, SimpleSnapshot, TagBucket,
+};
+
+type TableKey = &'static [u8];
+type TableValue = &'static [u8];
+
+/// Snapshot of a Redb database. Redb read transactions are isolated from every later commit,
+/// the read transaction is kept for as long as the snapshot lives.
+pub struct ReDBSnapshot {
+	// Declared before the database so the transaction is closed first
+	tx: SnapshotType,
+	_db: Pin<Arc<DBType>>,
+}
+
+impl ReDBSnapshot {
+	pub fn new(db: Pin<Arc<DBType>>) -> Result<Self, Error> {
+		let tx = unsafe { extend_tx_lifetime(db.begin_read()?) };
+		Ok(ReDBSnapshot {
+			tx,
+			_db: db,
+		})
+	}
+
+	fn scan_table(&self, name: &str) -> Result<Vec<KeyValuePair>, Error> {
+		let def = TableDefinition::<TableKey, TableValue>::new(name);
+		match self.tx.open_table(def) {
+			Ok(t) => Ok(t.iter()?.map(|(k, v)| (k.to_vec(), v.to_vec())).collect()),
+			Err(redb::Error::TableTypeMismatch(_)) => {
+				match read_snapshot_typed_table(&self.tx, name, &[], usize::MAX)? {
+					Some(table) => Ok(table.pairs),
+					None => Err(Error::DsTableTypeNotSupported(name.to_string())),
+				}
+			}
+			Err(_) => Err(Error::DsNoColumnFamilyFound),
+		}
+	}
+}
+
+fn get_table_name(tags: &TagBucket) -> String {
+	tags.get("column_family").unwrap_or_else(|| "default".to_string())
+}
+
+impl SimpleSnapshot for ReDBSnapshot {
+	fn get(&self, key: Key, tags: TagBucket) -> Result<Option<Val>, Error> {
+		let name = get_table_name(&tags);
+		let def = TableDefinition::<TableKey, TableValue>::new(&name);
+		match self.tx.open_table(def) {
+			Ok(table) => Ok(table.get(&key)?.map(|v| v.to_vec())),
+			// Typed tables are read from the key, the first value of a multimap key is returned
+			Err(redb::Error::TableTypeMismatch(_)) => {
+				let table = read_snapshot_typed_table(&self.tx, &name, &key, 1)?;
+				let pairs = table.map(|table| table.pairs).unwrap_or_default();
+				Ok(pairs.into_iter().find(|(k, _)| *k == key).map(|(_, v)| v))
+			}
+			Err(_) => Err(Error::DsNoColumnFamilyFound),
+		}
+	}
+
+	fn iterate(&self, tags: TagBucket) -> Result<Vec<Result<KeyValuePair, Error>>, Error> {
+		let name = get_table_name(&tags);
+		let pairs = self.scan_table(&name)?;
+		Ok(pairs.into_iter().map(Ok).collect())
+	}
+}
+
+unsafe fn extend_tx_lifetime(tx: redb::ReadTransaction<'_>) -> SnapshotType {
+	std::mem::transmute::<redb::ReadTransaction<'_>, SnapshotType>(tx)
+}
@@ -0,0 +1,231 @@
+use redb::{MultimapTableDefinition, ReadableMultimapTable, ReadableTable, TableDefinition};
+
+use crate::{interface::KeyValuePair, Error};
+
+/// Encode a decoded redb value into bytes which keep the order of the table. Byte slices are
+/// kept as is, strings are stored as UTF-8 and integers as big endian bytes, the TUI formats
+/// them with the templates of their type.
+pub(crate) trait Encode {
+	fn encode(&self) -> Vec<u8>;
+
+	// Bytes of the first element of a pair, strings end with a zero byte so a shorter string
+	// still sorts first
+	fn encode_first(&self) -> Vec<u8> {
+		let mut bytes = self.encode();
+		bytes.push(0);
+		bytes
+	}
+}
+
+impl Encode for [u8] {
+	fn encode(&self) -> Vec<u8> {
+		self.to_vec()
+	}
+}
+
+impl Encode for str {
+	fn encode(&self) -> Vec<u8> {
+		self.as_bytes().to_vec()
+	}
+}
+
+impl Encode for u64 {
+	fn encode(&self) -> Vec<u8> {
+		self.to_be_bytes().to_vec()
+	}
+
+	fn encode_first(&self) -> Vec<u8> {
+		self.encode()
+	}
+}
+
+impl<T: Encode + ?Sized> Encode for &T {
+	fn encode(&self) -> Vec<u8> {
+		(*self).encode()
+	}
+
+	fn encode_first(&self) -> Vec<u8> {
+		(*self).encode_first()
+	}
+}
+
+impl<A: Encode, B: Encode> Encode for (A, B) {
+	fn encode(&self) -> Vec<u8> {
+		[self.0.encode_first(), self.1.encode()].concat()
+	}
+}
+
+/// Smallest key of a type whose encoded bytes can be greater than or equal to `start`, typed
+/// tables are read from there and the keys before `start` are skipped
+pub(crate) trait StartKey<'a>: Sized {
+	fn start(start: &'a [u8]) -> Self;
+}
+
+impl<'a> StartKey<'a> for &'a [u8] {
+	fn start(start: &'a [u8]) -> Self {
+		start
+	}
+}
+
+impl<'a> StartKey<'a> for &'a str {
+	fn start(start: &'a [u8]) -> Self {
+		match std::str::from_utf8(start) {
+			Ok(start) => start,
+			Err(err) => std::str::from_utf8(&start[..err.valid_up_to()]).unwrap_or_default(),
+		}
+	}
+}
+
+impl<'a> StartKey<'a> for u64 {
+	fn start(start: &'a [u8]) -> Self {
+		let mut bytes = [0; 8];
+		let len = start.len().min(8);
+		bytes[..len].copy_from_slice(&start[..len]);
+		u64::from_be_bytes(bytes)
+	}
+}
+
+impl<'a, B: StartKey<'a>> StartKey<'a> for (u64, B) {
+	fn start(start: &'a [u8]) -> Self {
+		(u64::start(start), B::start(start.get(8..).unwrap_or_default()))
+	}
+}
+
+impl<'a, B: StartKey<'a>> StartKey<'a> for (&'a str, B) {
+	fn start(start: &'a [u8]) -> Self {
+		match start.iter().position(|byte| *byte == 0) {
+			Some(end) => (<&str>::start(&start[..end]), B::start(&start[end + 1..])),
+			None => (<&str>::start(start), B::start(&[])),
+		}
+	}
+}
+
+/// Key and value types of a table along with the pairs read from it. Multimap tables are
+/// flattened, a key is repeated for each of its values.
+pub(crate) struct TypedTable {
+	pub key_type: String,
+	pub value_type: String,
+	pub multimap: bool,
+	pub pairs: Vec<KeyValuePair>,
+}
+
+impl TypedTable {
+	pub fn type_name(&self) -> String {
+		let table = if self.multimap {
+			"MultimapTable"
+		} else {
+			"Table"
+		};
+		format!("{}<{}, {}>", table, self.key_type, self.value_type)
+	}
+}
+
+// Rust type as written in source code, e.g. `(u64,&str)` becomes `(u64, &str)`
+fn type_name(ty: &str) -> String {
+	ty.replace(' ', "").replace(',', ", ")
+}
+
+// Typed keys keep their order once encoded, a page is read from the range of the table
+macro_rules! try_read_table {
+	($tx: expr, $name: expr, $start: expr, $limit: expr, $k: ty, $v: ty) => {
+		match $tx.open_table(TableDefinition::<$k, $v>::new($name)) {
+			Ok(table) => {
+				let range = table.range::<$k>(<$k as StartKey>::start($start)..)?;
+				let pairs = range.map(|(k, v)| (k.encode(), v.encode()));
+				let pairs = pairs.skip_while(|(k, _)| k.as_slice() < $start).take($limit);
+				return Ok(Some(TypedTable {
+					key_type: type_name(stringify!($k)),
+					value_type: type_name(stringify!($v)),
+					multimap: false,
+					pairs: pairs.collect(),
+				}));
+			}
+			Err(redb::Error::TableTypeMismatch(_)) => {}
+			Err(err) => return Err(err.into()),
+		}
+	};
+}
+
+macro_rules! try_read_multimap_table {
+	($tx: expr, $name: expr, $start: expr, $limit: expr, $k: ty, $v: ty) => {
+		match $tx.open_multimap_table(MultimapTableDefinition::<$k, $v>::new($name)) {
+			Ok(table) => {
+				let range = table.range::<$k>(<$k as StartKey>::start($start)..)?;
+				let keys = range.map(|(k, values)| (k.encode(), values));
+				let keys = keys.skip_while(|(k, _)| k.as_slice() < $start);
+				let pairs =
+					keys.flat_map(|(k, values)| values.map(move |v| (k.clone(), v.encode())));
+				return Ok(Some(TypedTable {
+					key_type: type_name(stringify!($k)),
+					value_type: type_name(stringify!($v)),
+					multimap: true,
+					pairs: pairs.take($limit).collect(),
+				}));
+			}
+			Err(redb::Error::TableTypeMismatch(_)) => {}
+			Err(err) => return Err(err.into()),
+		}
+	};
+}
+
+macro_rules! try_read_values {
+	($read: ident, $tx: expr, $name: expr, $start: expr, $limit: expr, $k: ty, [$($v: ty),*]) => {
+		$(
+			$read!($tx, $name, $start, $limit, $k, $v);
+		)*
+	};
+}
+
+macro_rules! try_read_tables {
+	($read: ident, $tx: expr, $name: expr, $start: expr, $limit: expr, [$($k: ty),*], $values: tt) => {
+		$(
+			try_read_values!($read, $tx, $name, $start, $limit, $k, $values);
+		)*
+	};
+}
+
+macro_rules! impl_read_typed_table {
+	($(#[$attr: meta])* $fn: ident, $tx: ty) => {
+		$(#[$attr])*
+		pub(crate) fn $fn(
+			tx: &$tx,
+			name: &str,
+			start: &[u8],
+			limit: usize,
+		) -> Result<Option<TypedTable>, Error> {
+			macro_rules! try_read_supported_types {
+				($read: ident) => {
+					try_read_tables!(
+						$read,
+						tx,
+						name,
+						start,
+						limit,
+						[&[u8], &str, u64, (u64, u64), (u64, &str), (&str, u64), (&str, &str)],
+						[&[u8], &str, u64]
+					)
+				};
+			}
+
+			try_read_supported_types!(try_read_table);
+			try_read_supported_types!(try_read_multimap_table);
+
+			Ok(None)
+		}
+	};
+}
+
+impl_read_typed_table!(
+	/// Read at most `limit` pairs from `start` of a table whose key and value types are not
+	/// `&[u8]`, or of a multimap table. Redb checks the types a table is opened with against the
+	/// ones stored in its metadata, so the supported combinations (`&[u8]`, `&str` and `u64`
+	/// keys and values, pairs of `u64` and `&str` keys) are tried until the table opens.
+	/// Returns `None` if the types are not supported.
+	read_typed_table,
+	redb::WriteTransaction<'_>
+);
+impl_read_typed_table!(
+	/// Same as `read_typed_table`, reading the table from a snapshot
+	read_snapshot_typed_table,
+	redb::ReadTransaction<'_>
+);
@@ -1,39 +1,62 @@
 use async_trait::async_trait;
-use redb::{RangeIter, ReadableTable, TableDefinition};
+use redb::{ReadableTable, TableDefinition};
 
 use crate::{
 	interface::{Key, KeyValuePair, Val},
 	DBTransaction, Error, SimpleTransaction, TagBucket, CF,
 };
 
-use super::ty::{DBType, TxType};
+use super::{
+	table::read_typed_table,
+	ty::{DBType, TxType},
+};
 
 type TableKey = &'static [u8];
 type TableValue = &'static [u8];
 
-fn filter_with_prefix(
-	iterator: RangeIter<TableKey, TableValue>,
-	prefix: Vec<u8>,
-) -> impl Iterator<Item = (&[u8], &[u8])> + '_ {
-	iterator.filter(move |item| -> bool {
-		let (k, _) = *item;
-		k.starts_with(&prefix)
-	})
+fn get_table_name(cf: CF) -> String {
+	let default = "default".as_bytes().to_vec();
+	String::from_utf8(cf.unwrap_or(default)).unwrap()
 }
 
-fn filter_with_suffix(
-	iterator: RangeIter<TableKey, TableValue>,
-	suffix: Vec<u8>,
-) -> impl Iterator<Item = (&[u8], &[u8])> + '_ {
-	iterator.filter(move |item| -> bool {
-		let (k, _) = *item;
-		k.ends_with(&suffix)
-	})
+fn table_error(err: &redb::Error, name: &str) -> Error {
+	match err {
+		redb::Error::TableTypeMismatch(_) => Error::DsTableTypeNotSupported(name.to_string()),
+		_ => Error::DsNoColumnFamilyFound,
+	}
 }
 
-fn get_table_name(cf: CF) -> String {
-	let default = "default".as_bytes().to_vec();
-	String::from_utf8(cf.unwrap_or(default)).unwrap()
+// Read all pairs of a table, tables created with other types than `&[u8]` are decoded
+fn scan_table(tx: &TxType, name: &str) -> Result<Vec<KeyValuePair>, Error> {
+	range_table(tx, name, &[], usize::MAX)
+}
+
+// Read at most `limit` pairs from `start`, typed tables are read from their own range as their
+// encoded keys keep the order of the table
+fn range_table(
+	tx: &TxType,
+	name: &str,
+	start: &[u8],
+	limit: usize,
+) -> Result<Vec<KeyValuePair>, Error> {
+	let def = TableDefinition::<TableKey, TableValue>::new(name);
+	match tx.open_table(def) {
+		Ok(t) => {
+			let range = t.range::<&[u8]>(start..)?;
+			Ok(range.take(limit).map(|(k, v)| (k.to_vec(), v.to_vec())).collect())
+		}
+		Err(redb::Error::TableTypeMismatch(_)) => match read_typed_table(tx, name, start, limit)? {
+			Some(table) => Ok(table.pairs),
+			None => Err(Error::DsTableTypeNotSupported(name.to_string())),
+		},
+		Err(_) => Err(Error::DsNoColumnFamilyFound),
+	}
+}
+
+// Find a key in a typed table, the first value of a multimap key is returned
+fn find_in_typed_table(tx: &TxType, name: &str, key: &[u8]) -> Result<Option<Val>, Error> {
+	let pairs = range_table(tx, name, key, 1)?;
+	Ok(pairs.into_iter().find(|(k, _)| k == key).map(|(_, v)| v))
 }
 
 #[async_trait(?Send)]
@@ -56,6 +79,7 @@ impl SimpleTransaction for DBTransaction<DBType, TxType> {
 
 		match table {
 			Ok(t) => Ok(t.len()?),
+			Err(redb::Error::TableTypeMismatch(_)) => Ok(scan_table(tx, &name)?.len()),
 			Err(_) => Err(Error::DsNoColumnFamilyFound),
 		}
 	}
@@ -118,6 +142,9 @@ impl SimpleTransaction for DBTransaction<DBType, TxType> {
 		let key = key.into();
 		match table {
 			Ok(t) => Ok(t.get(&key)?.is_some()),
+			Err(redb::Error::TableTypeMismatch(_)) => {
+				Ok(find_in_typed_table(tx, &name, &key)?.is_some())
+			}
 			Err(_) => Err(Error::DsNoColumnFamilyFound),
 		}
 	}
@@ -136,9 +163,15 @@ impl SimpleTransaction for DBTransaction<DBType, TxType> {
 		let cf = tags.get_bytes("column_family");
 		let name = get_table_name(cf);
 		let def = TableDefinition::<TableKey, TableValue>::new(&name);
-		let table = &tx.open_table(def).unwrap();
-
 		let key = key.into();
+		let table = match tx.open_table(def) {
+			Ok(t) => t,
+			Err(redb::Error::TableTypeMismatch(_)) => {
+				return find_in_typed_table(tx, &name, &key);
+			}
+			Err(err) => return Err(table_error(&err, &name)),
+		};
+
 		let result = table.get(&key).unwrap();
 		Ok(result.map(|v| v.to_vec()))
 	}
@@ -168,7 +201,7 @@ impl SimpleTransaction for DBTransaction<DBType, TxType> {
 		let mut table = tx.open_table(def);
 		match table.as_mut() {
 			Ok(t) => t.insert(&key, &val)?,
-			Err(_) => return Err(Error::DsNoColumnFamilyFound),
+			Err(err) => return Err(table_error(err, &name)),
 		};
 
 		Ok(())
@@ -195,13 +228,53 @@ impl SimpleTransaction for DBTransaction<DBType, TxType> {
 		let cf = tags.get_bytes("column_family");
 		let name = get_table_name(cf);
 		let def = TableDefinition::<TableKey, TableValue>::new(&name);
-		let mut table = tx.open_table(def)?;
+		let mut table = tx.open_table(def).map_err(|err| table_error(&err, &name))?;
 
 		let (key, val) = (key.into(), val.into());
 
 		match table.get(&key)? {
 			None => table.insert(&key, &val)?,
-			_ => return Err(Error::TxConditionNotMet),
+			_ => return Err(Error::TxKeyAlreadyExists),
+		};
+
+		Ok(())
+	}
+
+	// Compare and swap the value of a key within the write transaction
+	async fn cas<K, V>(
+		&mut self,
+		key: K,
+		expected: Option<V>,
+		new: V,
+		tags: TagBucket,
+	) -> Result<(), Error>
+	where
+		K: Into<Key> + Send,
+		V: Into<Key> + Send,
+	{
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		// Check to see if transaction is writable
+		if !self.writable {
+			return Err(Error::TxReadonly);
+		}
+
+		let guarded_tx = self.tx.lock().await;
+		let tx = guarded_tx.as_ref().unwrap();
+
+		let cf = tags.get_bytes("column_family");
+		let name = get_table_name(cf);
+		let def = TableDefinition::<TableKey, TableValue>::new(&name);
+		let mut table = tx.open_table(def).map_err(|err| table_error(&err, &name))?;
+
+		let (key, expected, new) = (key.into(), expected.map(|v| v.into()), new.into());
+
+		let current = table.get(&key)?.map(|v| v.to_vec());
+		match current == expected {
+			true => table.insert(&key, &new)?,
+			false => return Err(Error::TxConditionNotMet),
 		};
 
 		Ok(())
@@ -233,7 +306,7 @@ impl SimpleTransaction for DBTransaction<DBType, TxType> {
 
 		match table.as_mut() {
 			Ok(t) => t.remove(&key)?,
-			Err(_) => return Err(Error::DsNoColumnFamilyFound),
+			Err(err) => return Err(table_error(err, &name)),
 		};
 
 		Ok(())
@@ -249,20 +322,9 @@ impl SimpleTransaction for DBTransaction<DBType, TxType> {
 
 		let cf = tags.get_bytes("column_family");
 		let name = get_table_name(cf);
-		let def = TableDefinition::<TableKey, TableValue>::new(&name);
-		let table = tx.open_table(def);
-
-		let iterator = match table.as_ref() {
-			Ok(t) => t.iter()?,
-			Err(_) => return Err(Error::DsNoColumnFamilyFound),
-		};
+		let pairs = scan_table(tx, &name)?;
 
-		Ok(iterator
-			.map(|p| {
-				let (k, v) = p;
-				Ok((k.to_vec(), v.to_vec()))
-			})
-			.collect())
+		Ok(pairs.into_iter().map(Ok).collect())
 	}
 
 	async fn prefix_iterate<P>(
@@ -282,20 +344,10 @@ impl SimpleTransaction for DBTransaction<DBType, TxType> {
 
 		let cf = tags.get_bytes("column_family");
 		let name = get_table_name(cf);
-		let def = TableDefinition::<TableKey, TableValue>::new(&name);
-		let table = tx.open_table(def);
-
-		let iterator = table.as_ref().unwrap().iter()?;
+		let pairs = scan_table(tx, &name)?;
 
 		let prefix: Key = prefix.into();
-		let filtered_iterator = filter_with_prefix(iterator, prefix);
-
-		Ok(filtered_iterator
-			.map(|pair| {
-				let (k, v) = pair;
-				Ok((k.to_vec(), v.to_vec()))
-			})
-			.collect())
+		Ok(pairs.into_iter().filter(|(k, _)| k.starts_with(&prefix)).map(Ok).collect())
 	}
 
 	async fn suffix_iterate<S>(
@@ -315,18 +367,33 @@ impl SimpleTransaction for DBTransaction<DBType, TxType> {
 
 		let cf = tags.get_bytes("column_family");
 		let name = get_table_name(cf);
-		let def = TableDefinition::<TableKey, TableValue>::new(&name);
-		let table = tx.open_table(def);
+		let pairs = scan_table(tx, &name)?;
 
-		let iterator = table.as_ref().unwrap().iter()?;
 		let suffix: Key = suffix.into();
-		let filtered_iterator = filter_with_suffix(iterator, suffix);
-
-		Ok(filtered_iterator
-			.map(|pair| {
-				let (k, v) = pair;
-				Ok((k.to_vec(), v.to_vec()))
-			})
-			.collect())
+		Ok(pairs.into_iter().filter(|(k, _)| k.ends_with(&suffix)).map(Ok).collect())
+	}
+
+	async fn range_iterate<S>(
+		&self,
+		start: S,
+		limit: usize,
+		tags: TagBucket,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error>
+	where
+		S: Into<Key> + Send,
+	{
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		let guarded_tx = self.tx.lock().await;
+		let tx = guarded_tx.as_ref().unwrap();
+
+		let cf = tags.get_bytes("column_family");
+		let name = get_table_name(cf);
+		let start: Key = start.into();
+		let pairs = range_table(tx, &name, &start, limit)?;
+
+		Ok(pairs.into_iter().map(Ok).collect())
 	}
 }
@@ -1,45 +1,98 @@
+mod options;
+mod snapshot;
+mod ttl;
 pub mod tx;
 pub mod ty;
 
 use async_trait::async_trait;
+pub use snapshot::*;
 pub use tx::*;
 pub use ty::*;
 
 use crate::{
 	constant::CF_NAMES,
 	err::Error,
-	model::{DBTransaction, DatastoreAdapter, StorageAdapter, StorageAdapterName},
+	model::{
+		Comparator, DBTransaction, DatastoreAdapter, DatastoreOptions, DatastoreStats, LevelStats,
+		StorageAdapter, StorageAdapterName,
+	},
 	util::generate_path,
 	StorageVariant,
 };
-use rocksdb::{DBCompactionStyle, OptimisticTransactionDB, Options};
+use options::{OptionsFile, RocksDBOptions};
+use rocksdb::{
+	checkpoint::Checkpoint, ColumnFamilyDescriptor, OptimisticTransactionDB, Options,
+	DEFAULT_COLUMN_FAMILY_NAME,
+};
+use std::{collections::BTreeMap, fs, path::Path};
 
+/// Adapter with the options the database was opened with, and the effective RocksDB options
 #[derive(Debug)]
-pub struct RocksDBAdapter(StorageAdapter<DBType>);
+pub struct RocksDBAdapter {
+	inner: StorageAdapter<DBType>,
+	options: DatastoreOptions,
+	// Settings RocksDB runs with, after the options file and EDMA options are applied
+	effective: BTreeMap<String, String>,
+}
 
 #[cfg(feature = "test-suite")]
 crate::full_adapter_test_impl!(RocksDBAdapter::default());
 
 impl RocksDBAdapter {
-	impl_new_type_adapter!(DBType);
+	impl_new_type_adapter!(DBType, inner);
+
+	// Column families other than the default one, every column family of the database is
+	// opened, not only EDMA ones
+	fn column_families(&self) -> Result<Vec<String>, Error> {
+		let mut names = self.keyspaces()?;
+		names.retain(|name| name != DEFAULT_COLUMN_FAMILY_NAME);
+		Ok(names)
+	}
 
 	pub fn new(path: &str, max_open_files: Option<i32>) -> Result<RocksDBAdapter, Error> {
+		let options = DatastoreOptions {
+			max_open_files,
+			..Default::default()
+		};
+		RocksDBAdapter::new_with_options(path, &options)
+	}
+
+	pub fn new_with_options(
+		path: &str,
+		options: &DatastoreOptions,
+	) -> Result<RocksDBAdapter, Error> {
 		let path = &path["rocksdb:".len()..];
-		let opts = get_options(max_open_files);
-		let cf_names = CF_NAMES.iter();
-		let db_instance = OptimisticTransactionDB::open_cf(&opts, path, cf_names)?;
-		Ok(RocksDBAdapter(StorageAdapter::<DBType>::new(
-			StorageAdapterName::RocksDB,
-			path.to_string(),
-			db_instance,
-			StorageVariant::KeyValueStore,
-		)?))
+		let RocksDBOptions {
+			opts,
+			effective,
+		} = get_options(options, path);
+		// Column families need the same comparator, TTL and blob settings as the database,
+		// column families created after EDMA ones are opened as well
+		let mut names: Vec<String> = CF_NAMES.iter().map(|name| name.to_string()).collect();
+		for name in OptimisticTransactionDB::list_cf(&opts, path).unwrap_or_default() {
+			if !names.contains(&name) {
+				names.push(name);
+			}
+		}
+		let cfs = names.iter().map(|name| ColumnFamilyDescriptor::new(name, opts.clone()));
+		let db_instance = OptimisticTransactionDB::open_cf_descriptors(&opts, path, cfs)?;
+		Ok(RocksDBAdapter {
+			inner: StorageAdapter::<DBType>::new(
+				StorageAdapterName::RocksDB,
+				path.to_string(),
+				db_instance,
+				StorageVariant::KeyValueStore,
+			)?,
+			options: options.clone(),
+			effective,
+		})
 	}
 }
 
 #[async_trait]
 impl DatastoreAdapter for RocksDBAdapter {
 	type Transaction = RocksDBTransaction;
+	type Snapshot = RocksDBSnapshot;
 
 	fn default() -> Self {
 		let path = &generate_path("rocksdb", None);
@@ -51,7 +104,118 @@ impl DatastoreAdapter for RocksDBAdapter {
 	}
 
 	fn path(&self) -> &str {
-		&self.0.path
+		&self.inner.path
+	}
+
+	fn keyspaces(&self) -> Result<Vec<String>, Error> {
+		let opts = Options::default();
+		Ok(OptimisticTransactionDB::list_cf(&opts, &self.inner.path)?)
+	}
+
+	fn stats(&self) -> Result<DatastoreStats, Error> {
+		let db = &self.get_initialized_inner().unwrap().db_instance;
+
+		// SST files of every column family, grouped by level
+		let mut levels = BTreeMap::<i32, LevelStats>::new();
+		for file in db.live_files()? {
+			let level = levels.entry(file.level).or_insert(LevelStats {
+				level: file.level,
+				..Default::default()
+			});
+			level.files += 1;
+			level.size += file.size as u64;
+			level.entries += file.num_entries;
+		}
+
+		// Estimations are summed over every column family
+		let cfs = self.column_families()?;
+		let mut properties = BTreeMap::<&str, u64>::new();
+		let names = [
+			"rocksdb.estimate-num-keys",
+			"rocksdb.estimate-live-data-size",
+			"rocksdb.total-sst-files-size",
+			"rocksdb.cur-size-all-mem-tables",
+			"rocksdb.estimate-pending-compaction-bytes",
+		];
+		for name in names {
+			let mut value = db.property_int_value(name)?.unwrap_or(0);
+			for cf in cfs.iter().filter_map(|cf| db.cf_handle(cf)) {
+				value += db.property_int_value_cf(&cf, name)?.unwrap_or(0);
+			}
+			properties.insert(name, value);
+		}
+
+		Ok(DatastoreStats {
+			size_on_disk: Some(dir_size(Path::new(&self.inner.path))),
+			estimated_keys: properties.get("rocksdb.estimate-num-keys").copied(),
+			live_data_size: properties.get("rocksdb.estimate-live-data-size").copied(),
+			levels: levels.into_values().collect(),
+			properties: properties.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+			raw: db.property_value("rocksdb.stats")?,
+		})
+	}
+
+	fn compact_range(
+		&self,
+		keyspace: Option<&str>,
+		from: Option<&[u8]>,
+		to: Option<&[u8]>,
+	) -> Result<(), Error> {
+		let db = &self.get_initialized_inner().unwrap().db_instance;
+		match keyspace {
+			Some(name) => {
+				let cf = db.cf_handle(name).ok_or(Error::DsColumnFamilyIsNotValid)?;
+				db.compact_range_cf(&cf, from, to);
+			}
+			None => {
+				db.compact_range(from, to);
+				for cf in self.column_families()?.iter().filter_map(|cf| db.cf_handle(cf)) {
+					db.compact_range_cf(&cf, from, to);
+				}
+			}
+		}
+		Ok(())
+	}
+
+	fn flush(&self) -> Result<(), Error> {
+		let db = &self.get_initialized_inner().unwrap().db_instance;
+		db.flush()?;
+		for cf in self.column_families()?.iter().filter_map(|cf| db.cf_handle(cf)) {
+			db.flush_cf(&cf)?;
+		}
+		Ok(())
+	}
+
+	fn checkpoint(&self, dest: &str) -> Result<(), Error> {
+		if Path::new(dest).exists() {
+			return Err(Error::DsCheckpointExists(dest.to_string()));
+		}
+		let db = &self.get_initialized_inner().unwrap().db_instance;
+		// SST files are hard linked when the destination is on the same file system
+		Checkpoint::new(&**db)?.create_checkpoint(dest)?;
+		Ok(())
+	}
+
+	fn create_keyspace(&self, keyspace: &str) -> Result<(), Error> {
+		let db = &self.get_initialized_inner().unwrap().db_instance;
+		if db.cf_handle(keyspace).is_some() {
+			return Ok(());
+		}
+		let RocksDBOptions {
+			opts,
+			..
+		} = get_options(&self.options, &self.inner.path);
+		db.create_cf(keyspace, &opts)?;
+		Ok(())
+	}
+
+	fn options(&self) -> Vec<(String, String)> {
+		self.effective.clone().into_iter().collect()
+	}
+
+	fn snapshot(&self) -> Result<RocksDBSnapshot, Error> {
+		let db = &self.get_initialized_inner().unwrap().db_instance;
+		Ok(RocksDBSnapshot::new(db.clone(), self.options.ttl))
 	}
 
 	async fn transaction(&self, rw: bool) -> Result<RocksDBTransaction, Error> {
@@ -59,7 +223,10 @@ impl DatastoreAdapter for RocksDBAdapter {
 		let db = &inner.db_instance;
 		let tx = db.transaction();
 
-		let tx = unsafe { extend_tx_lifetime(tx) };
+		let tx = TxType {
+			inner: unsafe { extend_tx_lifetime(tx) },
+			ttl: self.options.ttl,
+		};
 
 		Ok(DBTransaction::<DBType, TxType>::new(tx, db.clone(), rw).unwrap())
 	}
@@ -77,28 +244,73 @@ unsafe fn extend_tx_lifetime(
 	>(tx)
 }
 
-pub fn get_options(max_open_files: Option<i32>) -> Options {
-	// Current tuning based off of the total ordered example, flash
-	// storage example on
-	// https://github.com/facebook/rocksdb/wiki/RocksDB-Tuning-Guide
+// Size of the files in a database directory, including WAL and blob files
+fn dir_size(path: &Path) -> u64 {
+	let Ok(entries) = fs::read_dir(path) else {
+		return 0;
+	};
+	entries
+		.filter_map(|entry| entry.ok())
+		.map(|entry| match entry.metadata() {
+			Ok(meta) if meta.is_dir() => dir_size(&entry.path()),
+			Ok(meta) => meta.len(),
+			Err(_) => 0,
+		})
+		.sum()
+}
+
+pub fn get_options(options: &DatastoreOptions, path: &str) -> RocksDBOptions {
 	let mut opts = Options::default();
 	opts.create_if_missing(true);
 
 	// If there is no column missing, create a new column family
 	opts.create_missing_column_families(true);
-	opts.set_compaction_style(DBCompactionStyle::Level);
-	opts.set_write_buffer_size(67_108_864); // 64mb
-	opts.set_max_write_buffer_number(3);
-	opts.set_target_file_size_base(67_108_864); // 64mb
-	opts.set_level_zero_file_num_compaction_trigger(8);
-	opts.set_level_zero_slowdown_writes_trigger(17);
-	opts.set_level_zero_stop_writes_trigger(24);
-	opts.set_num_levels(4);
-	opts.set_max_bytes_for_level_base(536_870_912); // 512mb
-	opts.set_max_bytes_for_level_multiplier(8.0);
-
-	if let Some(max_open_files) = max_open_files {
-		opts.set_max_open_files(max_open_files);
+	let mut opts = RocksDBOptions::new(opts);
+
+	// Current tuning based off of the total ordered example, flash
+	// storage example on
+	// https://github.com/facebook/rocksdb/wiki/RocksDB-Tuning-Guide
+	opts.set("compaction_style", "kCompactionStyleLevel");
+	opts.set("write_buffer_size", "67108864"); // 64mb
+	opts.set("max_write_buffer_number", "3");
+	opts.set("target_file_size_base", "67108864"); // 64mb
+	opts.set("level0_file_num_compaction_trigger", "8");
+	opts.set("level0_slowdown_writes_trigger", "17");
+	opts.set("level0_stop_writes_trigger", "24");
+	opts.set("num_levels", "4");
+	opts.set("max_bytes_for_level_base", "536870912"); // 512mb
+	opts.set("max_bytes_for_level_multiplier", "8");
+
+	// An existing database is opened with the settings it was last opened with
+	if let Some(file) = OptionsFile::latest(path) {
+		opts.set_from_file(&file);
+	}
+
+	// Settings from the config file take precedence
+	if let Some(max_open_files) = options.max_open_files {
+		opts.set("max_open_files", &max_open_files.to_string());
+	}
+
+	// Opening a database with another comparator than the one it was created with fails
+	if options.comparator == Comparator::ReverseBytewise {
+		opts.set("comparator", "rocksdb.ReverseBytewiseComparator");
+	}
+
+	if let Some(ttl) = options.ttl {
+		opts.opts.set_compaction_filter("edma.TtlCompactionFilter", ttl::compaction_filter(ttl));
+		opts.effective.insert("ttl".to_string(), ttl.to_string());
+	}
+
+	if let Some(blob) = &options.blob {
+		opts.set("enable_blob_files", "true");
+		opts.set("min_blob_size", &blob.min_blob_size.to_string());
+		if let Some(blob_file_size) = blob.blob_file_size {
+			opts.set("blob_file_size", &blob_file_size.to_string());
+		}
+		opts.set("enable_blob_garbage_collection", &blob.enable_blob_gc.to_string());
+		if let Some(cutoff) = blob.blob_gc_age_cutoff {
+			opts.set("blob_garbage_collection_age_cutoff", &cutoff.to_string());
+		}
 	}
 
 	opts
@@ -0,0 +1,244 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use rocksdb::{DBCompactionStyle, DBCompressionType, Options, SliceTransform};
+
+/// Options RocksDB persisted in the latest `OPTIONS-<number>` file of a database directory.
+/// The file is formatted as INI, e.g. `[CFOptions "default"]` followed by `name=value` lines.
+#[derive(Debug, Default)]
+pub struct OptionsFile {
+	sections: Vec<(String, Vec<(String, String)>)>,
+}
+
+impl OptionsFile {
+	pub fn latest(dir: &str) -> Option<OptionsFile> {
+		let entries = fs::read_dir(Path::new(dir)).ok()?;
+		let latest = entries
+			.filter_map(|entry| entry.ok())
+			.filter_map(|entry| {
+				let name = entry.file_name().to_string_lossy().to_string();
+				// Files still being written end with `.dbtmp` and are skipped
+				let number = name.strip_prefix("OPTIONS-")?.parse::<u64>().ok()?;
+				Some((number, entry.path()))
+			})
+			.max_by_key(|(number, _)| *number)?;
+		let content = fs::read_to_string(latest.1).ok()?;
+		Some(OptionsFile::parse(&content))
+	}
+
+	pub fn parse(content: &str) -> OptionsFile {
+		let mut file = OptionsFile::default();
+		for line in content.lines().map(|line| line.trim()) {
+			if line.is_empty() || line.starts_with('#') {
+				continue;
+			}
+			if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+				file.sections.push((section.to_string(), vec![]));
+			} else if let (Some((name, value)), Some((_, options))) =
+				(line.split_once('='), file.sections.last_mut())
+			{
+				options.push((name.trim().to_string(), value.trim().to_string()));
+			}
+		}
+		file
+	}
+
+	pub fn section(&self, name: &str) -> &[(String, String)] {
+		match self.sections.iter().find(|(section, _)| section == name) {
+			Some((_, options)) => options,
+			None => &[],
+		}
+	}
+}
+
+// Options which change how stored data is read, the database can't be browsed safely if they
+// are set to something EDMA can't reproduce
+const SENSITIVE_OPTIONS: [&str; 5] = [
+	"comparator",
+	"merge_operator",
+	"prefix_extractor",
+	"compaction_filter",
+	"compaction_filter_factory",
+];
+
+/// RocksDB options together with the effective value of each option that was set, named as in
+/// OPTIONS files
+pub struct RocksDBOptions {
+	pub opts: Options,
+	pub effective: BTreeMap<String, String>,
+}
+
+impl RocksDBOptions {
+	pub fn new(opts: Options) -> Self {
+		RocksDBOptions {
+			opts,
+			effective: BTreeMap::new(),
+		}
+	}
+
+	/// Apply an option by name, returns `false` if the option or its value is not supported
+	pub fn set(&mut self, name: &str, value: &str) -> bool {
+		let applied = apply_option(&mut self.opts, name, value);
+		if applied {
+			self.effective.insert(name.to_string(), value.to_string());
+		}
+		applied
+	}
+
+	/// Apply the options of the default column family and database stored in an OPTIONS file.
+	/// Options which are not supported are kept as they are, and listed as not applied when
+	/// they change how data is read.
+	pub fn set_from_file(&mut self, file: &OptionsFile) {
+		let db_options = file.section("DBOptions");
+		let cf_options = file.section("CFOptions \"default\"");
+		for (name, value) in db_options.iter().chain(cf_options.iter()) {
+			if !self.set(name, value)
+				&& SENSITIVE_OPTIONS.contains(&name.as_str())
+				&& value != "nullptr"
+			{
+				self.effective.insert(name.to_string(), format!("{} (not applied)", value));
+			}
+		}
+	}
+}
+
+fn compression_type(value: &str) -> Option<DBCompressionType> {
+	match value {
+		"kNoCompression" => Some(DBCompressionType::None),
+		"kSnappyCompression" => Some(DBCompressionType::Snappy),
+		"kZlibCompression" => Some(DBCompressionType::Zlib),
+		"kBZip2Compression" => Some(DBCompressionType::Bz2),
+		"kLZ4Compression" => Some(DBCompressionType::Lz4),
+		"kLZ4HCCompression" => Some(DBCompressionType::Lz4hc),
+		"kZSTD" => Some(DBCompressionType::Zstd),
+		_ => None,
+	}
+}
+
+fn compaction_style(value: &str) -> Option<DBCompactionStyle> {
+	match value {
+		"kCompactionStyleLevel" => Some(DBCompactionStyle::Level),
+		"kCompactionStyleUniversal" => Some(DBCompactionStyle::Universal),
+		"kCompactionStyleFIFO" => Some(DBCompactionStyle::Fifo),
+		_ => None,
+	}
+}
+
+fn apply_option(opts: &mut Options, name: &str, value: &str) -> bool {
+	macro_rules! parse_option {
+		($setter: ident, $parse: expr) => {
+			match $parse {
+				Some(v) => {
+					opts.$setter(v);
+					true
+				}
+				None => false,
+			}
+		};
+	}
+
+	match name {
+		"max_open_files" => parse_option!(set_max_open_files, value.parse().ok()),
+		"max_background_jobs" => parse_option!(set_max_background_jobs, value.parse().ok()),
+		"bytes_per_sync" => parse_option!(set_bytes_per_sync, value.parse().ok()),
+		"max_total_wal_size" => parse_option!(set_max_total_wal_size, value.parse().ok()),
+		"write_buffer_size" => parse_option!(set_write_buffer_size, value.parse().ok()),
+		"max_write_buffer_number" => {
+			parse_option!(set_max_write_buffer_number, value.parse().ok())
+		}
+		"target_file_size_base" => parse_option!(set_target_file_size_base, value.parse().ok()),
+		"level0_file_num_compaction_trigger" => {
+			parse_option!(set_level_zero_file_num_compaction_trigger, value.parse().ok())
+		}
+		"level0_slowdown_writes_trigger" => {
+			parse_option!(set_level_zero_slowdown_writes_trigger, value.parse().ok())
+		}
+		"level0_stop_writes_trigger" => {
+			parse_option!(set_level_zero_stop_writes_trigger, value.parse().ok())
+		}
+		"num_levels" => parse_option!(set_num_levels, value.parse().ok()),
+		"max_bytes_for_level_base" => {
+			parse_option!(set_max_bytes_for_level_base, value.parse().ok())
+		}
+		"max_bytes_for_level_multiplier" => {
+			parse_option!(set_max_bytes_for_level_multiplier, value.parse().ok())
+		}
+		"level_compaction_dynamic_level_bytes" => {
+			parse_option!(set_level_compaction_dynamic_level_bytes, value.parse().ok())
+		}
+		"compaction_style" => parse_option!(set_compaction_style, compaction_style(value)),
+		"compression" => parse_option!(set_compression_type, compression_type(value)),
+		"bottommost_compression" => {
+			parse_option!(set_bottommost_compression_type, compression_type(value))
+		}
+		"enable_blob_files" => parse_option!(set_enable_blob_files, value.parse().ok()),
+		"min_blob_size" => parse_option!(set_min_blob_size, value.parse().ok()),
+		"blob_file_size" => parse_option!(set_blob_file_size, value.parse().ok()),
+		"blob_compression_type" => {
+			parse_option!(set_blob_compression_type, compression_type(value))
+		}
+		"enable_blob_garbage_collection" => {
+			parse_option!(set_enable_blob_gc, value.parse().ok())
+		}
+		"blob_garbage_collection_age_cutoff" => {
+			parse_option!(set_blob_gc_age_cutoff, value.parse().ok())
+		}
+		"comparator" => match value {
+			"leveldb.BytewiseComparator" => true,
+			"rocksdb.ReverseBytewiseComparator" => {
+				opts.set_comparator(value, |a, b| b.cmp(a));
+				true
+			}
+			_ => false,
+		},
+		"prefix_extractor" => {
+			let len = value.strip_prefix("rocksdb.FixedPrefix.").and_then(|l| l.parse().ok());
+			match (value, len) {
+				("nullptr", _) => true,
+				(_, Some(len)) => {
+					opts.set_prefix_extractor(SliceTransform::create_fixed_prefix(len));
+					true
+				}
+				_ => false,
+			}
+		}
+		_ => false,
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use rocksdb::Options;
+
+	use super::{OptionsFile, RocksDBOptions};
+
+	#[test]
+	fn should_apply_options_file() {
+		let content = "
+# This is a RocksDB option file.
+[Version]
+  rocksdb_version=7.4.4
+
+[DBOptions]
+  max_open_files=-1
+  unknown_option=1
+
+[CFOptions \"default\"]
+  comparator=rocksdb.ReverseBytewiseComparator
+  merge_operator=StringAppendOperator
+  compression=kZSTD
+  prefix_extractor=rocksdb.FixedPrefix.4
+";
+		let file = OptionsFile::parse(content);
+		assert_eq!(file.section("DBOptions").len(), 2);
+
+		let mut options = RocksDBOptions::new(Options::default());
+		options.set_from_file(&file);
+		let effective = options.effective;
+		assert_eq!(effective.get("max_open_files"), Some(&"-1".to_string()));
+		assert_eq!(effective.get("compression"), Some(&"kZSTD".to_string()));
+		assert_eq!(effective.get("prefix_extractor"), Some(&"rocksdb.FixedPrefix.4".to_string()));
+		let merge_operator = effective.get("merge_operator").unwrap();
+		assert_eq!(merge_operator, "StringAppendOperator (not applied)");
+		assert!(!effective.contains_key("unknown_option"));
+	}
+}
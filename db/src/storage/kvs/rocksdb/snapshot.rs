@@ -0,0 +1,71 @@
+use std::{pin::Pin, sync::Arc};
+
+use rocksdb::{BoundColumnFamily, IteratorMode, SnapshotWithThreadMode};
+
+use super::{
+	ttl::{collect_pairs, strip_timestamp},
+	ty::DBType,
+};
+use crate::{
+	err::Error,
+	interface::{
+		kv::{Key, Val},
+		KeyValuePair,
+	},
+	model::SimpleSnapshot,
+	TagBucket,
+};
+
+/// Snapshot of a RocksDB database, taken with `GetSnapshot` so reads of every column family
+/// see the same sequence number
+pub struct RocksDBSnapshot {
+	// Declared before the database so the snapshot is released first
+	inner: SnapshotWithThreadMode<'static, DBType>,
+	ttl: Option<u64>,
+	db: Pin<Arc<DBType>>,
+}
+
+impl RocksDBSnapshot {
+	pub fn new(db: Pin<Arc<DBType>>, ttl: Option<u64>) -> Self {
+		let inner = unsafe { extend_snapshot_lifetime(db.snapshot()) };
+		RocksDBSnapshot {
+			inner,
+			ttl,
+			db,
+		}
+	}
+
+	fn get_column_family(&self, name: &str) -> Result<Arc<BoundColumnFamily<'_>>, Error> {
+		self.db.cf_handle(name).ok_or(Error::DsNoColumnFamilyFound)
+	}
+}
+
+impl SimpleSnapshot for RocksDBSnapshot {
+	fn get(&self, key: Key, tags: TagBucket) -> Result<Option<Val>, Error> {
+		let result = match tags.get("column_family") {
+			Some(name) => self.inner.get_cf(&self.get_column_family(&name)?, key)?,
+			None => self.inner.get(key)?,
+		};
+		Ok(result.and_then(|v| strip_timestamp(self.ttl, &v).map(|v| v.to_vec())))
+	}
+
+	fn iterate(&self, tags: TagBucket) -> Result<Vec<Result<KeyValuePair, Error>>, Error> {
+		let pairs = match tags.get("column_family") {
+			Some(name) => {
+				let cf = self.get_column_family(&name)?;
+				collect_pairs(self.inner.iterator_cf(&cf, IteratorMode::Start), self.ttl)
+			}
+			None => collect_pairs(self.inner.iterator(IteratorMode::Start), self.ttl),
+		};
+		Ok(pairs)
+	}
+}
+
+// The snapshot borrows the database, which is kept alive by the `Arc` stored next to it
+unsafe fn extend_snapshot_lifetime(
+	snapshot: SnapshotWithThreadMode<'_, DBType>,
+) -> SnapshotWithThreadMode<'static, DBType> {
+	std::mem::transmute::<SnapshotWithThreadMode<'_, DBType>, SnapshotWithThreadMode<'static, DBType>>(
+		snapshot,
+	)
+}
@@ -0,0 +1,62 @@
+use rocksdb::compaction_filter::Decision;
+
+use crate::{
+	err::Error,
+	interface::{KeyValuePair, Val},
+	util::now,
+};
+
+/// Databases created with `DBWithTTL` store the write time after each value, as a little
+/// endian `u32` of seconds since the epoch
+const TIMESTAMP_LEN: usize = 4;
+
+fn expired(ttl: u64, value: &[u8]) -> bool {
+	if value.len() < TIMESTAMP_LEN {
+		return false;
+	}
+	let mut timestamp = [0u8; TIMESTAMP_LEN];
+	timestamp.copy_from_slice(&value[value.len() - TIMESTAMP_LEN..]);
+	u32::from_le_bytes(timestamp) as i64 + (ttl as i64) < now()
+}
+
+/// Value without its timestamp, or `None` once it expired. Expired values are kept until they
+/// are compacted, so reads have to skip them.
+pub fn strip_timestamp(ttl: Option<u64>, value: &[u8]) -> Option<&[u8]> {
+	match ttl {
+		Some(ttl) if value.len() >= TIMESTAMP_LEN => match expired(ttl, value) {
+			true => None,
+			false => Some(&value[..value.len() - TIMESTAMP_LEN]),
+		},
+		_ => Some(value),
+	}
+}
+
+pub fn append_timestamp(ttl: Option<u64>, value: Val) -> Val {
+	match ttl {
+		Some(_) => [value, (now() as u32).to_le_bytes().to_vec()].concat(),
+		None => value,
+	}
+}
+
+// Pairs of an iterator, values of databases opened with a TTL are returned without their
+// timestamp and expired pairs are skipped
+pub fn collect_pairs(
+	iterator: impl Iterator<Item = Result<(Box<[u8]>, Box<[u8]>), rocksdb::Error>>,
+	ttl: Option<u64>,
+) -> Vec<Result<KeyValuePair, Error>> {
+	iterator
+		.filter_map(|pair| {
+			let (k, v) = pair.unwrap();
+			let v = strip_timestamp(ttl, &v)?;
+			Some(Ok((k.to_vec(), v.to_vec())))
+		})
+		.collect()
+}
+
+/// Remove expired values while compacting, like the filter `DBWithTTL` installs
+pub fn compaction_filter(ttl: u64) -> impl FnMut(u32, &[u8], &[u8]) -> Decision + Send + 'static {
+	move |_level, _key, value| match expired(ttl, value) {
+		true => Decision::Remove,
+		false => Decision::Keep,
+	}
+}
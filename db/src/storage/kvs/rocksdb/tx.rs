@@ -1,9 +1,12 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use rocksdb::{BoundColumnFamily, DBAccess, DBIteratorWithThreadMode, IteratorMode};
+use rocksdb::{BoundColumnFamily, DBAccess, DBIteratorWithThreadMode, Direction, IteratorMode};
 
-use super::ty::{DBType, TxType};
+use super::{
+	ttl::{append_timestamp, collect_pairs, strip_timestamp},
+	ty::{DBType, TxType},
+};
 use crate::{
 	err::Error,
 	interface::{
@@ -70,7 +73,8 @@ impl SimpleTransaction for DBTransaction<DBType, TxType> {
 		let tx = guarded_tx.as_ref().unwrap();
 		let cf = tags.get_bytes("column_family");
 		let cf = &self.get_column_family(cf).unwrap();
-		Ok(tx.iterator_cf(cf, IteratorMode::Start).count())
+		let iterator = tx.iterator_cf(cf, IteratorMode::Start);
+		Ok(collect_pairs(iterator, tx.ttl).len())
 	}
 
 	async fn cancel(&mut self) -> Result<(), Error> {
@@ -105,7 +109,7 @@ impl SimpleTransaction for DBTransaction<DBType, TxType> {
 
 		let mut tx = self.tx.lock().await;
 		match tx.take() {
-			Some(tx) => tx.commit()?,
+			Some(tx) => tx.inner.commit()?,
 			None => unreachable!(),
 		}
 
@@ -120,19 +124,17 @@ impl SimpleTransaction for DBTransaction<DBType, TxType> {
 			return Err(Error::TxFinished);
 		}
 
-		let tx = self.tx.lock().await;
+		let guarded_tx = self.tx.lock().await;
+		let tx = guarded_tx.as_ref().unwrap();
 		let cf = tags.get_bytes("column_family");
-		match cf {
+		let result = match cf {
 			Some(_) => {
 				let cf = &self.get_column_family(cf).unwrap();
-				let result = tx.as_ref().unwrap().get_cf(cf, key.into()).unwrap().is_some();
-				Ok(result)
-			}
-			None => {
-				let result = tx.as_ref().unwrap().get(key.into()).unwrap().is_some();
-				Ok(result)
+				tx.get_cf(cf, key.into()).unwrap()
 			}
-		}
+			None => tx.get(key.into()).unwrap(),
+		};
+		Ok(result.filter(|v| strip_timestamp(tx.ttl, v).is_some()).is_some())
 	}
 	// Fetch a key from the database [column family]
 	async fn get<K>(&self, key: K, tags: TagBucket) -> Result<Option<Val>, Error>
@@ -146,13 +148,14 @@ impl SimpleTransaction for DBTransaction<DBType, TxType> {
 		let guarded_tx = self.tx.lock().await;
 		let tx = guarded_tx.as_ref().unwrap();
 		let cf = tags.get_bytes("column_family");
-		Ok(match cf {
+		let result = match cf {
 			Some(_) => {
 				let cf = &self.get_column_family(cf).unwrap();
 				tx.get_cf(cf, key.into()).unwrap()
 			}
 			None => tx.get(key.into()).unwrap(),
-		})
+		};
+		Ok(result.and_then(|v| strip_timestamp(tx.ttl, &v).map(|v| v.to_vec())))
 	}
 
 	// Insert or update a key in the database
@@ -173,13 +176,14 @@ impl SimpleTransaction for DBTransaction<DBType, TxType> {
 		// Set the key
 		let guarded_tx = self.tx.lock().await;
 		let tx = guarded_tx.as_ref().unwrap();
+		let val = append_timestamp(tx.ttl, val.into());
 		let cf = tags.get_bytes("column_family");
 		match cf {
 			Some(_) => {
 				let cf = &self.get_column_family(cf).unwrap();
-				tx.put_cf(cf, key.into(), val.into())?;
+				tx.put_cf(cf, key.into(), val)?;
 			}
-			None => tx.put(key.into(), val.into())?,
+			None => tx.put(key.into(), val)?,
 		};
 		Ok(())
 	}
@@ -202,19 +206,68 @@ impl SimpleTransaction for DBTransaction<DBType, TxType> {
 		// Future tx
 		let guarded_tx = self.tx.lock().await;
 		let tx = guarded_tx.as_ref().unwrap();
-		let (key, val) = (key.into(), val.into());
+		let (key, val) = (key.into(), append_timestamp(tx.ttl, val.into()));
+		let ttl = tx.ttl;
 		let cf = tags.get_bytes("column_family");
+		// Expired values are replaced as if the key didn't exist
 		match cf {
 			Some(_) => {
 				let cf = &self.get_column_family(cf).unwrap();
-				match tx.get_cf(cf, &key)? {
+				match tx.get_cf(cf, &key)?.filter(|v| strip_timestamp(ttl, v).is_some()) {
 					None => tx.put_cf(cf, key, val)?,
-					_ => return Err(Error::TxConditionNotMet),
+					_ => return Err(Error::TxKeyAlreadyExists),
 				};
 			}
 			None => {
-				match tx.get(&key)? {
+				match tx.get(&key)?.filter(|v| strip_timestamp(ttl, v).is_some()) {
 					None => tx.put(key, val)?,
+					_ => return Err(Error::TxKeyAlreadyExists),
+				};
+			}
+		};
+
+		Ok(())
+	}
+
+	// Lock the key for the rest of the transaction, then compare and swap its value
+	async fn cas<K, V>(
+		&mut self,
+		key: K,
+		expected: Option<V>,
+		new: V,
+		tags: TagBucket,
+	) -> Result<(), Error>
+	where
+		K: Into<Key> + Send,
+		V: Into<Key> + Send,
+	{
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		// Check to see if transaction is writable
+		if !self.writable {
+			return Err(Error::TxReadonly);
+		}
+
+		let guarded_tx = self.tx.lock().await;
+		let tx = guarded_tx.as_ref().unwrap();
+		let (key, expected) = (key.into(), expected.map(|v| v.into()));
+		let (new, ttl) = (append_timestamp(tx.ttl, new.into()), tx.ttl);
+		// Values are compared without their timestamp
+		let decode = |v: Option<Val>| v.and_then(|v| strip_timestamp(ttl, &v).map(|v| v.to_vec()));
+		let cf = tags.get_bytes("column_family");
+		match cf {
+			Some(_) => {
+				let cf = &self.get_column_family(cf).unwrap();
+				match decode(tx.get_for_update_cf(cf, &key, true)?) {
+					current if current == expected => tx.put_cf(cf, key, new)?,
+					_ => return Err(Error::TxConditionNotMet),
+				};
+			}
+			None => {
+				match decode(tx.get_for_update(&key, true)?) {
+					current if current == expected => tx.put(key, new)?,
 					_ => return Err(Error::TxConditionNotMet),
 				};
 			}
@@ -272,12 +325,7 @@ impl SimpleTransaction for DBTransaction<DBType, TxType> {
 		};
 
 		match get_iterator {
-			Ok(iterator) => Ok(iterator
-				.map(|pair| {
-					let (k, v) = pair.unwrap();
-					Ok((k.to_vec(), v.to_vec()))
-				})
-				.collect()),
+			Ok(iterator) => Ok(collect_pairs(iterator, tx.ttl)),
 			Err(err) => Err(err),
 		}
 	}
@@ -307,12 +355,7 @@ impl SimpleTransaction for DBTransaction<DBType, TxType> {
 		};
 		let taken_iterator = take_with_suffix(iterator, suffix);
 
-		Ok(taken_iterator
-			.map(|pair| {
-				let (k, v) = pair.unwrap();
-				Ok((k.to_vec(), v.to_vec()))
-			})
-			.collect())
+		Ok(collect_pairs(taken_iterator, tx.ttl))
 	}
 
 	// Iterate key value elements with handler
@@ -341,11 +384,45 @@ impl SimpleTransaction for DBTransaction<DBType, TxType> {
 		};
 		let taken_iterator = take_with_prefix(iterator, prefix);
 
-		Ok(taken_iterator
-			.map(|v| {
-				let (k, v) = v.unwrap();
-				Ok((k.to_vec(), v.to_vec()))
-			})
-			.collect())
+		Ok(collect_pairs(taken_iterator, tx.ttl))
+	}
+
+	async fn range_iterate<S>(
+		&self,
+		start: S,
+		limit: usize,
+		tags: TagBucket,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error>
+	where
+		S: Into<Key> + Send,
+	{
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		let guarded_tx = self.tx.lock().await;
+		let tx = guarded_tx.as_ref().unwrap();
+		let start: Key = start.into();
+		// The empty key is last with a reverse comparator
+		let mode = match start.is_empty() {
+			true => IteratorMode::Start,
+			false => IteratorMode::From(&start, Direction::Forward),
+		};
+		let cf = tags.get_bytes("column_family");
+		let iterator = match cf {
+			Some(_) => {
+				let cf = &self.get_column_family(cf)?;
+				tx.iterator_cf(cf, mode)
+			}
+			None => tx.iterator(mode),
+		};
+
+		// Expired pairs are skipped before the page is filled
+		let pairs = iterator.filter_map(|pair| {
+			let (k, v) = pair.unwrap();
+			let v = strip_timestamp(tx.ttl, &v)?;
+			Some(Ok((k.to_vec(), v.to_vec())))
+		});
+		Ok(pairs.take(limit).collect())
 	}
 }
@@ -1,5 +1,7 @@
 extern crate rocksdb;
 
+use std::ops::Deref;
+
 use rocksdb::OptimisticTransactionDB;
 
 use crate::DBTransaction;
@@ -8,5 +10,19 @@ use crate::DBTransaction;
 /// Using OptimisticTransactionDB type instead of default DB type
 /// This is for multithreaded concurrency control used in distributed system
 pub type DBType = OptimisticTransactionDB;
-pub type TxType = rocksdb::Transaction<'static, DBType>;
+
+/// Transaction together with the time to live of the database it belongs to
+pub struct TxType {
+	pub inner: rocksdb::Transaction<'static, DBType>,
+	pub ttl: Option<u64>,
+}
+
+impl Deref for TxType {
+	type Target = rocksdb::Transaction<'static, DBType>;
+
+	fn deref(&self) -> &Self::Target {
+		&self.inner
+	}
+}
+
 pub type RocksDBTransaction = DBTransaction<DBType, TxType>;
@@ -1,15 +1,18 @@
-use std::marker::PhantomData;
+use std::{marker::PhantomData, path::Path};
 
 use async_trait::async_trait;
+mod snapshot;
 pub mod tx;
 pub mod ty;
 
+pub use snapshot::*;
 pub use tx::*;
 pub use ty::*;
 
 use crate::{
 	util::{generate_path, get_absolute_path},
-	DBTransaction, DatastoreAdapter, Error, StorageAdapter, StorageAdapterName, StorageVariant,
+	DBTransaction, DatastoreAdapter, DatastoreStats, Error, StorageAdapter, StorageAdapterName,
+	StorageVariant, TagBucket,
 };
 pub struct SledAdapter(StorageAdapter<DBType>);
 
@@ -31,11 +34,22 @@ impl SledAdapter {
 			StorageVariant::KeyValueStore,
 		)?))
 	}
+
+	// Subscribe to changes of keys starting with the prefix in the tree of the tags
+	pub fn watch_prefix(&self, prefix: &[u8], tags: &TagBucket) -> Result<sled::Subscriber, Error> {
+		let db = &self.get_initialized_inner().unwrap().db_instance;
+		let subscriber = match tags.get("tree") {
+			Some(tree) => db.open_tree(tree)?.watch_prefix(prefix),
+			None => db.watch_prefix(prefix),
+		};
+		Ok(subscriber)
+	}
 }
 
 #[async_trait]
 impl DatastoreAdapter for SledAdapter {
 	type Transaction = SledTransaction;
+	type Snapshot = SledSnapshot;
 
 	fn default() -> Self {
 		let path = &generate_path("redb", None);
@@ -50,6 +64,68 @@ impl DatastoreAdapter for SledAdapter {
 		&self.0.path
 	}
 
+	fn keyspaces(&self) -> Result<Vec<String>, Error> {
+		let inner = self.get_initialized_inner().unwrap();
+		let names = inner.db_instance.tree_names();
+		// Sled always lists its default tree, which is browsed without a tree tag
+		let names = names.into_iter().filter(|name| name.as_ref() != b"__sled__default");
+		Ok(names.map(|name| String::from_utf8_lossy(&name).to_string()).collect())
+	}
+
+	fn stats(&self) -> Result<DatastoreStats, Error> {
+		let db = &self.get_initialized_inner().unwrap().db_instance;
+		let mut keys = 0;
+		let trees = db.tree_names();
+		for name in trees.iter() {
+			keys += db.open_tree(name)?.len() as u64;
+		}
+
+		let properties = vec![
+			("trees".to_string(), trees.len().to_string()),
+			("was_recovered".to_string(), db.was_recovered().to_string()),
+		];
+		Ok(DatastoreStats {
+			size_on_disk: Some(db.size_on_disk()?),
+			estimated_keys: Some(keys),
+			properties,
+			..Default::default()
+		})
+	}
+
+	fn flush(&self) -> Result<(), Error> {
+		self.get_initialized_inner().unwrap().db_instance.flush()?;
+		Ok(())
+	}
+
+	fn checkpoint(&self, dest: &str) -> Result<(), Error> {
+		if Path::new(dest).exists() {
+			return Err(Error::DsCheckpointExists(dest.to_string()));
+		}
+		let db = &self.get_initialized_inner().unwrap().db_instance;
+		// Sled has no checkpoints, every tree is copied to a new database. Trees are copied one
+		// after the other, a write made while they are copied may only be found in some of them.
+		let copy = sled::open(dest)?;
+		for name in db.tree_names() {
+			let (tree, copied) = (db.open_tree(&name)?, copy.open_tree(&name)?);
+			for pair in tree.iter() {
+				let (k, v) = pair?;
+				copied.insert(k, v)?;
+			}
+		}
+		copy.flush()?;
+		Ok(())
+	}
+
+	fn create_keyspace(&self, keyspace: &str) -> Result<(), Error> {
+		self.get_initialized_inner().unwrap().db_instance.open_tree(keyspace)?;
+		Ok(())
+	}
+
+	fn snapshot(&self) -> Result<SledSnapshot, Error> {
+		let db = &self.get_initialized_inner().unwrap().db_instance;
+		SledSnapshot::new(db.clone())
+	}
+
 	async fn transaction(&self, w: bool) -> Result<Self::Transaction, Error> {
 		let inner = self.get_initialized_inner().unwrap();
 		let db = &inner.db_instance;
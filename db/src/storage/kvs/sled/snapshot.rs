@@ -0,0 +1,135 @@
+use std::{
+	collections::{BTreeMap, HashMap, HashSet},
+	pin::Pin,
+	sync::{Arc, Mutex, Weak},
+};
+
+use lazy_static::lazy_static;
+
+use crate::{
+	interface::{Key, KeyValuePair, Val},
+	Error, SimpleSnapshot, TagBucket,
+};
+
+use super::ty::DBType;
+
+// Name under which sled lists its default tree
+const DEFAULT_TREE: &[u8] = b"__sled__default";
+
+// Values of keys before their first write since a snapshot was taken, by tree. `None` is the
+// default tree, a key which didn't exist has no value.
+type Overwritten = HashMap<Option<String>, BTreeMap<Key, Option<Val>>>;
+
+struct SnapshotLog {
+	// Address of the database the snapshot was taken of
+	db: usize,
+	overwritten: Mutex<Overwritten>,
+}
+
+lazy_static! {
+	// Snapshots which aren't dropped yet, writes keep the values they replace for them
+	static ref SNAPSHOTS: Mutex<Vec<Weak<SnapshotLog>>> = Mutex::new(vec![]);
+}
+
+fn address(db: &Pin<Arc<DBType>>) -> usize {
+	&**db as *const DBType as usize
+}
+
+/// Keep the value of a key a write is about to replace for every snapshot of the database
+/// which hasn't seen the key written yet. Called by transactions before each write.
+pub(crate) fn keep_value(
+	db: &Pin<Arc<DBType>>,
+	tree: &sled::Tree,
+	name: Option<String>,
+	key: &[u8],
+) -> Result<(), Error> {
+	let mut snapshots = SNAPSHOTS.lock().unwrap();
+	snapshots.retain(|log| log.strong_count() > 0);
+	let logs = snapshots.iter().filter_map(Weak::upgrade).filter(|log| log.db == address(db));
+	for log in logs {
+		let mut overwritten = log.overwritten.lock().unwrap();
+		let values = overwritten.entry(name.clone()).or_default();
+		if !values.contains_key(key) {
+			values.insert(key.to_vec(), tree.get(key)?.map(|v| v.to_vec()));
+		}
+	}
+	Ok(())
+}
+
+/// Snapshot of a Sled database. Sled has no snapshots, so reads go to the live trees and the
+/// value a key had when the snapshot was taken is copied when the key is first written
+/// afterwards. Only writes made through transactions of the same datastore are seen, as Sled
+/// doesn't let another process open the database meanwhile.
+pub struct SledSnapshot {
+	db: Pin<Arc<DBType>>,
+	// Trees when the snapshot was taken, `None` is the default tree
+	trees: HashSet<Option<String>>,
+	log: Arc<SnapshotLog>,
+}
+
+impl SledSnapshot {
+	pub fn new(db: Pin<Arc<DBType>>) -> Result<Self, Error> {
+		let log = Arc::new(SnapshotLog {
+			db: address(&db),
+			overwritten: Mutex::new(HashMap::new()),
+		});
+		SNAPSHOTS.lock().unwrap().push(Arc::downgrade(&log));
+		let trees = db.tree_names().into_iter().map(|name| match name.as_ref() == DEFAULT_TREE {
+			true => None,
+			false => Some(String::from_utf8_lossy(&name).to_string()),
+		});
+		Ok(SledSnapshot {
+			trees: trees.collect(),
+			db,
+			log,
+		})
+	}
+
+	// Trees created after the snapshot was taken are empty, they aren't opened so they aren't
+	// created by a read either
+	fn tree(&self, name: &Option<String>) -> Result<Option<sled::Tree>, Error> {
+		match name {
+			_ if !self.trees.contains(name) => Ok(None),
+			Some(name) => Ok(Some(self.db.open_tree(name)?)),
+			None => Ok(Some((**self.db).clone())),
+		}
+	}
+}
+
+impl SimpleSnapshot for SledSnapshot {
+	fn get(&self, key: Key, tags: TagBucket) -> Result<Option<Val>, Error> {
+		let name = tags.get("tree");
+		let tree = match self.tree(&name)? {
+			Some(tree) => tree,
+			None => return Ok(None),
+		};
+		// The live value is read first, a write in between keeps the value it replaces
+		let value = tree.get(&key)?.map(|v| v.to_vec());
+		let overwritten = self.log.overwritten.lock().unwrap();
+		match overwritten.get(&name).and_then(|values| values.get(&key)) {
+			Some(old) => Ok(old.clone()),
+			None => Ok(value),
+		}
+	}
+
+	fn iterate(&self, tags: TagBucket) -> Result<Vec<Result<KeyValuePair, Error>>, Error> {
+		let name = tags.get("tree");
+		let tree = match self.tree(&name)? {
+			Some(tree) => tree,
+			None => return Ok(vec![]),
+		};
+		let mut pairs = BTreeMap::new();
+		for pair in tree.iter() {
+			let (k, v) = pair?;
+			pairs.insert(k.to_vec(), v.to_vec());
+		}
+		let overwritten = self.log.overwritten.lock().unwrap();
+		for (key, old) in overwritten.get(&name).into_iter().flatten() {
+			match old {
+				Some(value) => pairs.insert(key.to_vec(), value.to_vec()),
+				None => pairs.remove(key),
+			};
+		}
+		Ok(pairs.into_iter().map(Ok).collect())
+	}
+}
@@ -6,7 +6,10 @@ use crate::{
 	DBTransaction, Error, SimpleTransaction, TagBucket,
 };
 
-use super::ty::{DBType, TxType};
+use super::{
+	snapshot::keep_value,
+	ty::{DBType, TxType},
+};
 
 fn filter_with_prefix(iterator: Iter, prefix: Vec<u8>) -> impl Iterator<Item = (IVec, IVec)> {
 	iterator
@@ -138,10 +141,12 @@ impl SimpleTransaction for DBTransaction<DBType, TxType> {
 		let key = key.into();
 		let k = key.as_slice();
 		let v = val.into();
-		if let Some(t) = tree_name {
+		if let Some(t) = tree_name.clone() {
 			let tree = db.open_tree(t).unwrap();
+			keep_value(db, &tree, tree_name, k)?;
 			tree.insert(k, v).unwrap()
 		} else {
+			keep_value(db, db, None, k)?;
 			db.insert(k, v).unwrap()
 		};
 		Ok(())
@@ -176,19 +181,61 @@ impl SimpleTransaction for DBTransaction<DBType, TxType> {
 		.unwrap();
 
 		match is_exists {
-			false => if let Some(t) = tree_name {
+			false => if let Some(t) = tree_name.clone() {
 				let tree = db.open_tree(t).unwrap();
+				keep_value(db, &tree, tree_name, k)?;
 				tree.insert(k, v)
 			} else {
+				keep_value(db, db, None, k)?;
 				db.insert(k, v)
 			}
 			.unwrap(),
-			_ => return Err(Error::TxConditionNotMet),
+			_ => return Err(Error::TxKeyAlreadyExists),
 		};
 
 		Ok(())
 	}
 
+	// Compare and swap the value of a key atomically
+	async fn cas<K, V>(
+		&mut self,
+		key: K,
+		expected: Option<V>,
+		new: V,
+		tags: TagBucket,
+	) -> Result<(), Error>
+	where
+		K: Into<Key> + Send,
+		V: Into<Key> + Send,
+	{
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		if !self.writable {
+			return Err(Error::TxReadonly);
+		}
+
+		let db = &self._db;
+		let tree_name = tags.get("tree");
+		let key = key.into();
+		let expected = expected.map(|v| v.into());
+		let new = Some(new.into());
+		let result = if let Some(t) = tree_name.clone() {
+			let tree = db.open_tree(t)?;
+			keep_value(db, &tree, tree_name, &key)?;
+			tree.compare_and_swap(key, expected, new)?
+		} else {
+			keep_value(db, db, None, &key)?;
+			db.compare_and_swap(key, expected, new)?
+		};
+
+		match result {
+			Ok(_) => Ok(()),
+			Err(_) => Err(Error::TxConditionNotMet),
+		}
+	}
+
 	// Delete a key
 	async fn del<K>(&mut self, key: K, tags: TagBucket) -> Result<(), Error>
 	where
@@ -207,10 +254,12 @@ impl SimpleTransaction for DBTransaction<DBType, TxType> {
 		let tree_name = tags.get("tree");
 		let key = key.into();
 		let k = key.as_slice();
-		if let Some(t) = tree_name {
+		if let Some(t) = tree_name.clone() {
 			let tree = db.open_tree(t).unwrap();
+			keep_value(db, &tree, tree_name, k)?;
 			tree.remove(k)
 		} else {
+			keep_value(db, db, None, k)?;
 			db.remove(k)
 		}
 		.unwrap();
@@ -303,4 +352,33 @@ impl SimpleTransaction for DBTransaction<DBType, TxType> {
 			})
 			.collect())
 	}
+
+	async fn range_iterate<S>(
+		&self,
+		start: S,
+		limit: usize,
+		tags: TagBucket,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error>
+	where
+		S: Into<Key> + Send,
+	{
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		let db = &self._db;
+		let start: Key = start.into();
+		let iter = match tags.get("tree") {
+			Some(t) => db.open_tree(t)?.range(start..),
+			None => db.range(start..),
+		};
+
+		Ok(iter
+			.take(limit)
+			.map(|pair| {
+				let (k, v) = pair?;
+				Ok((k.to_vec(), v.to_vec()))
+			})
+			.collect())
+	}
 }
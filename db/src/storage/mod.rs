@@ -1,8 +1,15 @@
 /// Storage
 mod ds;
 pub mod kvs;
+pub mod plugin;
+mod registry;
+mod snapshot;
 mod tx;
+mod watch;
 
 pub use ds::*;
 pub use kvs::*;
+pub use registry::*;
+pub use snapshot::*;
 pub use tx::*;
+pub use watch::*;
@@ -0,0 +1,102 @@
+//! Stable C ABI shared between EDMA and storage plugins compiled as shared libraries.
+//!
+//! A plugin exports a single symbol, `edma_plugin_vtable`, returning a pointer to a static
+//! `EdmaPluginVTable`. Every byte buffer crossing the boundary is borrowed for the duration of
+//! the call only: values produced by the plugin are handed to an `EdmaVisitor` callback so
+//! memory never has to be released on the other side of the boundary.
+use std::{ffi::c_void, ptr, slice};
+
+/// Version of the ABI described in this module
+pub const EDMA_PLUGIN_ABI_VERSION: u32 = 1;
+
+/// Name of the symbol exported by plugins
+pub const EDMA_PLUGIN_SYMBOL: &[u8] = b"edma_plugin_vtable\0";
+
+/// Operation succeeded
+pub const EDMA_OK: i32 = 0;
+/// Key is not in the keyspace
+pub const EDMA_NOT_FOUND: i32 = 1;
+/// Operation failed
+pub const EDMA_ERROR: i32 = -1;
+
+/// Opaque database handle owned by the plugin
+pub type EdmaHandle = *mut c_void;
+
+/// Called for every visited item, returning `false` stops the iteration
+pub type EdmaVisitor = extern "C" fn(ctx: *mut c_void, key: EdmaSlice, value: EdmaSlice) -> bool;
+
+/// Exported by plugins under `EDMA_PLUGIN_SYMBOL`
+pub type EdmaPluginEntry = extern "C" fn() -> *const EdmaPluginVTable;
+
+/// Borrowed byte buffer. An empty keyspace slice refers to the default keyspace.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct EdmaSlice {
+	pub data: *const u8,
+	pub len: usize,
+}
+
+impl EdmaSlice {
+	pub fn empty() -> Self {
+		EdmaSlice {
+			data: ptr::null(),
+			len: 0,
+		}
+	}
+
+	/// # Safety
+	/// `data` must point to `len` readable bytes for the lifetime of the returned slice
+	pub unsafe fn as_bytes<'a>(&self) -> &'a [u8] {
+		if self.data.is_null() || self.len == 0 {
+			return &[];
+		}
+		slice::from_raw_parts(self.data, self.len)
+	}
+}
+
+impl From<&[u8]> for EdmaSlice {
+	fn from(bytes: &[u8]) -> Self {
+		EdmaSlice {
+			data: bytes.as_ptr(),
+			len: bytes.len(),
+		}
+	}
+}
+
+#[repr(C)]
+pub struct EdmaPluginVTable {
+	/// Must be equal to `EDMA_PLUGIN_ABI_VERSION`
+	pub abi_version: u32,
+	/// Open the store located at `path` and write its handle into `handle`
+	pub open: extern "C" fn(path: EdmaSlice, handle: *mut EdmaHandle) -> i32,
+	/// Visit keyspace names, passed as keys with an empty value
+	pub list_keyspaces:
+		extern "C" fn(handle: EdmaHandle, visitor: EdmaVisitor, ctx: *mut c_void) -> i32,
+	/// Visit key-value pairs of a keyspace starting with `prefix` in key order
+	pub scan: extern "C" fn(
+		handle: EdmaHandle,
+		keyspace: EdmaSlice,
+		prefix: EdmaSlice,
+		visitor: EdmaVisitor,
+		ctx: *mut c_void,
+	) -> i32,
+	/// Visit the pair stored under `key`, returns `EDMA_NOT_FOUND` if there is none
+	pub get: extern "C" fn(
+		handle: EdmaHandle,
+		keyspace: EdmaSlice,
+		key: EdmaSlice,
+		visitor: EdmaVisitor,
+		ctx: *mut c_void,
+	) -> i32,
+	/// Insert or update a key
+	pub put: extern "C" fn(
+		handle: EdmaHandle,
+		keyspace: EdmaSlice,
+		key: EdmaSlice,
+		value: EdmaSlice,
+	) -> i32,
+	/// Delete a key, returns `EDMA_NOT_FOUND` if there is none
+	pub delete: extern "C" fn(handle: EdmaHandle, keyspace: EdmaSlice, key: EdmaSlice) -> i32,
+	/// Release the handle returned by `open`
+	pub close: extern "C" fn(handle: EdmaHandle),
+}
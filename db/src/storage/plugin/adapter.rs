@@ -0,0 +1,330 @@
+use std::{any::Any, ffi::c_void, sync::Arc};
+
+use async_trait::async_trait;
+
+use super::abi::{
+	EdmaHandle, EdmaPluginVTable, EdmaSlice, EDMA_NOT_FOUND, EDMA_OK, EDMA_PLUGIN_ABI_VERSION,
+};
+use crate::{
+	interface::{Key, KeyValuePair, Val},
+	Error, PluginAdapter, PluginTransaction, TagBucket,
+};
+
+/// Plugin vtable together with the shared library it was loaded from
+pub struct PluginLibrary {
+	vtable: &'static EdmaPluginVTable,
+	// Keeps the shared library mapped for as long as the vtable is in use
+	_library: Option<Box<dyn Any + Send + Sync>>,
+}
+
+impl PluginLibrary {
+	pub fn from_vtable(vtable: &'static EdmaPluginVTable) -> Result<Self, Error> {
+		PluginLibrary::with_library(vtable, None)
+	}
+
+	pub(crate) fn with_library(
+		vtable: &'static EdmaPluginVTable,
+		library: Option<Box<dyn Any + Send + Sync>>,
+	) -> Result<Self, Error> {
+		if vtable.abi_version != EDMA_PLUGIN_ABI_VERSION {
+			return Err(Error::Plugin(format!(
+				"ABI version {} is not supported, expected {}",
+				vtable.abi_version, EDMA_PLUGIN_ABI_VERSION
+			)));
+		}
+
+		Ok(PluginLibrary {
+			vtable,
+			_library: library,
+		})
+	}
+
+	/// Open the store located at `path` with this plugin
+	pub fn open(self: &Arc<Self>, path: &str) -> Result<CAbiAdapter, Error> {
+		let mut handle: EdmaHandle = std::ptr::null_mut();
+		let code = (self.vtable.open)(EdmaSlice::from(path.as_bytes()), &mut handle);
+		check_code(code, "open the database")?;
+
+		Ok(CAbiAdapter {
+			connection: Arc::new(Connection {
+				library: self.clone(),
+				handle,
+			}),
+			path: path.to_string(),
+		})
+	}
+}
+
+/// Open plugin handle, closed once the adapter and all of its transactions are dropped
+struct Connection {
+	library: Arc<PluginLibrary>,
+	handle: EdmaHandle,
+}
+
+// Plugins are required to accept calls on a handle from any thread
+unsafe impl Send for Connection {}
+unsafe impl Sync for Connection {}
+
+impl Drop for Connection {
+	fn drop(&mut self) {
+		(self.library.vtable.close)(self.handle);
+	}
+}
+
+fn check_code(code: i32, operation: &str) -> Result<(), Error> {
+	match code {
+		EDMA_OK => Ok(()),
+		EDMA_NOT_FOUND => Err(Error::TxnKeyNotFound),
+		code => Err(Error::Plugin(format!("Failed to {} (code {})", operation, code))),
+	}
+}
+
+extern "C" fn collect_pairs(ctx: *mut c_void, key: EdmaSlice, value: EdmaSlice) -> bool {
+	let pairs = unsafe { &mut *(ctx as *mut Vec<KeyValuePair>) };
+	unsafe { pairs.push((key.as_bytes().to_vec(), value.as_bytes().to_vec())) };
+	true
+}
+
+fn get_keyspace(tags: &TagBucket) -> Vec<u8> {
+	let keyspace = tags.get_bytes("column_family").or_else(|| tags.get_bytes("tree"));
+	keyspace.unwrap_or_default()
+}
+
+impl Connection {
+	fn vtable(&self) -> &EdmaPluginVTable {
+		self.library.vtable
+	}
+
+	fn keyspaces(&self) -> Result<Vec<String>, Error> {
+		let mut pairs: Vec<KeyValuePair> = vec![];
+		let ctx = &mut pairs as *mut Vec<KeyValuePair> as *mut c_void;
+		let code = (self.vtable().list_keyspaces)(self.handle, collect_pairs, ctx);
+		check_code(code, "list keyspaces")?;
+		Ok(pairs.into_iter().map(|(name, _)| String::from_utf8_lossy(&name).to_string()).collect())
+	}
+
+	fn scan(&self, keyspace: &[u8], prefix: &[u8]) -> Result<Vec<KeyValuePair>, Error> {
+		let mut pairs: Vec<KeyValuePair> = vec![];
+		let ctx = &mut pairs as *mut Vec<KeyValuePair> as *mut c_void;
+		let code = (self.vtable().scan)(
+			self.handle,
+			EdmaSlice::from(keyspace),
+			EdmaSlice::from(prefix),
+			collect_pairs,
+			ctx,
+		);
+		check_code(code, "scan the keyspace")?;
+		Ok(pairs)
+	}
+
+	fn get(&self, keyspace: &[u8], key: &[u8]) -> Result<Option<Val>, Error> {
+		let mut pairs: Vec<KeyValuePair> = vec![];
+		let ctx = &mut pairs as *mut Vec<KeyValuePair> as *mut c_void;
+		let code = (self.vtable().get)(
+			self.handle,
+			EdmaSlice::from(keyspace),
+			EdmaSlice::from(key),
+			collect_pairs,
+			ctx,
+		);
+		match check_code(code, "get the key") {
+			Ok(_) => Ok(pairs.pop().map(|(_, v)| v)),
+			Err(Error::TxnKeyNotFound) => Ok(None),
+			Err(err) => Err(err),
+		}
+	}
+
+	fn put(&self, keyspace: &[u8], key: &[u8], val: &[u8]) -> Result<(), Error> {
+		let code = (self.vtable().put)(
+			self.handle,
+			EdmaSlice::from(keyspace),
+			EdmaSlice::from(key),
+			EdmaSlice::from(val),
+		);
+		check_code(code, "put the key")
+	}
+
+	fn delete(&self, keyspace: &[u8], key: &[u8]) -> Result<(), Error> {
+		let code =
+			(self.vtable().delete)(self.handle, EdmaSlice::from(keyspace), EdmaSlice::from(key));
+		match check_code(code, "delete the key") {
+			Err(Error::TxnKeyNotFound) => Ok(()),
+			result => result,
+		}
+	}
+}
+
+/// # C ABI adapter
+/// Bridges a store opened by a shared library plugin to the `PluginAdapter` interface.
+pub struct CAbiAdapter {
+	connection: Arc<Connection>,
+	path: String,
+}
+
+/// Plugins have no transaction support, writes are applied as soon as they are issued.
+pub struct CAbiTransaction {
+	connection: Arc<Connection>,
+	ok: bool,
+	writable: bool,
+}
+
+#[async_trait]
+impl PluginAdapter for CAbiAdapter {
+	async fn transaction(&self, rw: bool) -> Result<Box<dyn PluginTransaction>, Error> {
+		Ok(Box::new(CAbiTransaction {
+			connection: self.connection.clone(),
+			ok: false,
+			writable: rw,
+		}))
+	}
+
+	fn keyspaces(&self) -> Result<Vec<String>, Error> {
+		self.connection.keyspaces()
+	}
+
+	fn path(&self) -> &str {
+		&self.path
+	}
+}
+
+impl CAbiTransaction {
+	fn check_writable(&self) -> Result<(), Error> {
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		// Check to see if transaction is writable
+		if !self.writable {
+			return Err(Error::TxReadonly);
+		}
+
+		Ok(())
+	}
+
+	fn filtered_scan<F>(
+		&self,
+		tags: TagBucket,
+		prefix: &[u8],
+		filter: F,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error>
+	where
+		F: Fn(&Key) -> bool,
+	{
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		let keyspace = get_keyspace(&tags);
+		let pairs = self.connection.scan(&keyspace, prefix)?;
+		Ok(pairs.into_iter().filter(|(k, _)| filter(k)).map(Ok).collect())
+	}
+}
+
+#[async_trait(?Send)]
+impl PluginTransaction for CAbiTransaction {
+	fn closed(&self) -> bool {
+		self.ok
+	}
+
+	async fn cancel(&mut self) -> Result<(), Error> {
+		if self.ok {
+			return Err(Error::TxFinished);
+		}
+
+		self.ok = true;
+
+		Ok(())
+	}
+
+	async fn count(&mut self, tags: TagBucket) -> Result<usize, Error> {
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		let keyspace = get_keyspace(&tags);
+		Ok(self.connection.scan(&keyspace, &[])?.len())
+	}
+
+	async fn commit(&mut self) -> Result<(), Error> {
+		self.check_writable()?;
+
+		// Mark this transaction as done
+		self.ok = true;
+
+		Ok(())
+	}
+
+	async fn exi(&self, key: Key, tags: TagBucket) -> Result<bool, Error> {
+		Ok(self.get(key, tags).await?.is_some())
+	}
+
+	async fn get(&self, key: Key, tags: TagBucket) -> Result<Option<Val>, Error> {
+		if self.closed() {
+			return Err(Error::TxFinished);
+		}
+
+		let keyspace = get_keyspace(&tags);
+		self.connection.get(&keyspace, &key)
+	}
+
+	async fn set(&mut self, key: Key, val: Val, tags: TagBucket) -> Result<(), Error> {
+		self.check_writable()?;
+
+		let keyspace = get_keyspace(&tags);
+		self.connection.put(&keyspace, &key, &val)
+	}
+
+	async fn put(&mut self, key: Key, val: Val, tags: TagBucket) -> Result<(), Error> {
+		self.check_writable()?;
+
+		let keyspace = get_keyspace(&tags);
+		match self.connection.get(&keyspace, &key)? {
+			None => self.connection.put(&keyspace, &key, &val),
+			_ => Err(Error::TxKeyAlreadyExists),
+		}
+	}
+
+	// Plugins don't lock keys, the check only guards against changes made before this call
+	async fn cas(
+		&mut self,
+		key: Key,
+		expected: Option<Val>,
+		new: Val,
+		tags: TagBucket,
+	) -> Result<(), Error> {
+		self.check_writable()?;
+
+		let keyspace = get_keyspace(&tags);
+		match self.connection.get(&keyspace, &key)? {
+			current if current == expected => self.connection.put(&keyspace, &key, &new),
+			_ => Err(Error::TxConditionNotMet),
+		}
+	}
+
+	async fn del(&mut self, key: Key, tags: TagBucket) -> Result<(), Error> {
+		self.check_writable()?;
+
+		let keyspace = get_keyspace(&tags);
+		self.connection.delete(&keyspace, &key)
+	}
+
+	async fn iterate(&self, tags: TagBucket) -> Result<Vec<Result<KeyValuePair, Error>>, Error> {
+		self.filtered_scan(tags, &[], |_| true)
+	}
+
+	async fn prefix_iterate(
+		&self,
+		prefix: Key,
+		tags: TagBucket,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error> {
+		self.filtered_scan(tags, &prefix, |_| true)
+	}
+
+	async fn suffix_iterate(
+		&self,
+		suffix: Key,
+		tags: TagBucket,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error> {
+		self.filtered_scan(tags, &[], |k| k.ends_with(&suffix))
+	}
+}
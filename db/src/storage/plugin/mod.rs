@@ -0,0 +1,34 @@
+pub mod abi;
+mod adapter;
+
+pub use adapter::*;
+
+#[cfg(feature = "plugin")]
+use std::sync::Arc;
+
+#[cfg(feature = "plugin")]
+use crate::{register_adapter, util::get_absolute_path, Error, PluginAdapter};
+
+/// Load a storage plugin from a shared library and register it for the given URI scheme
+#[cfg(feature = "plugin")]
+pub fn load_plugin(scheme: &str, path: &str) -> Result<(), Error> {
+	let abs_path = get_absolute_path(path);
+	let library = unsafe { libloading::Library::new(&abs_path) }
+		.map_err(|err| Error::Plugin(err.to_string()))?;
+
+	let vtable = unsafe {
+		let entry = library
+			.get::<abi::EdmaPluginEntry>(abi::EDMA_PLUGIN_SYMBOL)
+			.map_err(|err| Error::Plugin(err.to_string()))?;
+		match entry().as_ref() {
+			Some(vtable) => vtable,
+			None => return Err(Error::Plugin(format!("No vtable exported by {}", abs_path))),
+		}
+	};
+
+	let library = Arc::new(PluginLibrary::with_library(vtable, Some(Box::new(library)))?);
+	register_adapter(
+		scheme,
+		move |path| Ok(Box::new(library.open(path)?) as Box<dyn PluginAdapter>),
+	)
+}
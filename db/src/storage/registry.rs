@@ -0,0 +1,63 @@
+use std::{
+	collections::HashMap,
+	sync::{Arc, RwLock},
+};
+
+use lazy_static::lazy_static;
+
+use crate::{Error, PluginAdapter};
+
+/// Creates a plugin adapter from the path following the `<scheme>:` prefix
+pub type AdapterFactory = Arc<dyn Fn(&str) -> Result<Box<dyn PluginAdapter>, Error> + Send + Sync>;
+
+/// Schemes handled by the built-in adapters, these can't be overridden by plugins
+const RESERVED_SCHEMES: [&str; 3] = ["rocksdb", "redb", "sled"];
+
+lazy_static! {
+	static ref ADAPTERS: RwLock<HashMap<String, AdapterFactory>> = RwLock::new(HashMap::new());
+}
+
+/// Register an adapter factory for the given URI scheme. Registering a scheme twice replaces
+/// the previous factory.
+pub fn register_adapter<F>(scheme: &str, factory: F) -> Result<(), Error>
+where
+	F: Fn(&str) -> Result<Box<dyn PluginAdapter>, Error> + Send + Sync + 'static,
+{
+	if RESERVED_SCHEMES.contains(&scheme) {
+		return Err(Error::DsSchemeReserved(scheme.to_string()));
+	}
+
+	let mut adapters = ADAPTERS.write().unwrap();
+	adapters.insert(scheme.to_string(), Arc::new(factory));
+	Ok(())
+}
+
+/// Remove the adapter factory registered for the given URI scheme
+pub fn unregister_adapter(scheme: &str) -> bool {
+	let mut adapters = ADAPTERS.write().unwrap();
+	adapters.remove(scheme).is_some()
+}
+
+/// List URI schemes of registered plugin adapters
+pub fn registered_schemes() -> Vec<String> {
+	let adapters = ADAPTERS.read().unwrap();
+	adapters.keys().cloned().collect()
+}
+
+/// Split `<scheme>:<path>` and create the adapter registered for the scheme
+pub(crate) fn create_adapter(path: &str) -> Result<Box<dyn PluginAdapter>, Error> {
+	let (scheme, path) = match path.split_once(':') {
+		Some(v) => v,
+		None => return Err(Error::DsAdapterNotFound(path.to_string())),
+	};
+
+	let factory = {
+		let adapters = ADAPTERS.read().unwrap();
+		match adapters.get(scheme) {
+			Some(f) => f.clone(),
+			None => return Err(Error::DsAdapterNotFound(scheme.to_string())),
+		}
+	};
+
+	factory(path)
+}
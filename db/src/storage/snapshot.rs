@@ -0,0 +1,82 @@
+use crate::{
+	diff_scans,
+	interface::{Key, KeyValuePair, Val},
+	Error, KeyDiff, SimpleSnapshot, TagBucket,
+};
+
+#[cfg(feature = "kv-redb")]
+use super::ReDBSnapshot;
+
+#[cfg(feature = "kv-rocksdb")]
+use super::RocksDBSnapshot;
+
+#[cfg(feature = "kv-sled")]
+use super::SledSnapshot;
+
+pub(super) enum Inner {
+	#[cfg(feature = "kv-rocksdb")]
+	RocksDB(RocksDBSnapshot),
+	#[cfg(feature = "kv-redb")]
+	ReDB(ReDBSnapshot),
+	#[cfg(feature = "kv-sled")]
+	Sled(SledSnapshot),
+}
+
+/// Snapshot taken with `Datastore::snapshot`, reads of every keyspace see the same state of
+/// the datastore until the snapshot is dropped
+pub struct Snapshot {
+	pub(super) inner: Inner,
+}
+
+macro_rules! impl_snapshot_method {
+	($self: ident, $method: ident($($arg: expr),*)) => {
+		match &$self.inner {
+			#[cfg(feature = "kv-rocksdb")]
+			Inner::RocksDB(v) => v.$method($($arg),*),
+			#[cfg(feature = "kv-redb")]
+			Inner::ReDB(v) => v.$method($($arg),*),
+			#[cfg(feature = "kv-sled")]
+			Inner::Sled(v) => v.$method($($arg),*),
+			// Plugin only builds have no snapshots, `Datastore::snapshot` refuses them
+			#[allow(unreachable_patterns)]
+			_ => Err(Error::DsOperationNotSupported("snapshot")),
+		}
+	};
+}
+
+impl Snapshot {
+	pub fn get<K: Into<Key>>(&self, key: K, tags: TagBucket) -> Result<Option<Val>, Error> {
+		impl_snapshot_method!(self, get(key.into(), tags))
+	}
+
+	pub fn iterate(&self, tags: TagBucket) -> Result<Vec<Result<KeyValuePair, Error>>, Error> {
+		impl_snapshot_method!(self, iterate(tags))
+	}
+
+	pub fn prefix_iterate<P: Into<Key>>(
+		&self,
+		prefix: P,
+		tags: TagBucket,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error> {
+		impl_snapshot_method!(self, prefix_iterate(prefix.into(), tags))
+	}
+
+	pub fn suffix_iterate<S: Into<Key>>(
+		&self,
+		suffix: S,
+		tags: TagBucket,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error> {
+		impl_snapshot_method!(self, suffix_iterate(suffix.into(), tags))
+	}
+
+	/// Compare a keyspace of this snapshot with a keyspace of another snapshot, e.g. a later
+	/// snapshot of the same datastore
+	pub fn diff(
+		&self,
+		tags: TagBucket,
+		other: &Snapshot,
+		other_tags: TagBucket,
+	) -> Result<Vec<KeyDiff>, Error> {
+		diff_scans(self.iterate(tags)?, other.iterate(other_tags)?)
+	}
+}
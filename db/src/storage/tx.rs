@@ -3,7 +3,7 @@ use async_trait::async_trait;
 
 use crate::{
 	interface::{Key, Val},
-	Error, SimpleTransaction,
+	Error, PluginTransaction, SimpleTransaction,
 };
 
 #[cfg(feature = "kv-redb")]
@@ -23,6 +23,7 @@ pub(super) enum Inner {
 	ReDB(ReDBTransaction),
 	#[cfg(feature = "kv-sled")]
 	Sled(SledTransaction),
+	Plugin(Box<dyn PluginTransaction>),
 }
 
 pub struct Transaction {
@@ -0,0 +1,155 @@
+use std::collections::BTreeMap;
+
+use crate::{
+	diff_pairs,
+	interface::{Key, Val},
+	Datastore, Error, KeyDiff, SimpleTransaction, TagBucket, WatchEvent,
+};
+
+pub(super) enum Source {
+	// Keys are scanned again on every poll and compared with the previous scan
+	Poll,
+	#[cfg(feature = "kv-sled")]
+	Sled(sled::Subscriber),
+}
+
+/// Subscription to the changes of keys with a prefix, created with `Datastore::watch`.
+/// Sled pushes its changes to the subscription, other engines are scanned on every poll.
+/// Keys of multimap tables are reported with their last value.
+pub struct Subscription {
+	prefix: Key,
+	tags: TagBucket,
+	rows: BTreeMap<Key, Val>,
+	source: Source,
+}
+
+impl Subscription {
+	// Sled subscribes before the first scan so no change is lost in between
+	pub(super) async fn new(
+		ds: &Datastore,
+		prefix: Key,
+		tags: TagBucket,
+		source: Source,
+	) -> Result<Self, Error> {
+		let rows = Subscription::scan(ds, &prefix, &tags).await?;
+		Ok(Subscription {
+			prefix,
+			tags,
+			rows,
+			source,
+		})
+	}
+
+	async fn scan(
+		ds: &Datastore,
+		prefix: &Key,
+		tags: &TagBucket,
+	) -> Result<BTreeMap<Key, Val>, Error> {
+		let tx = ds.transaction(false).await?;
+		let mut rows = BTreeMap::new();
+		for pair in tx.prefix_iterate(prefix.to_vec(), tags.clone()).await? {
+			let (k, v) = pair?;
+			rows.insert(k, v);
+		}
+		Ok(rows)
+	}
+
+	/// Keys and values as of the last poll
+	pub fn rows(&self) -> impl Iterator<Item = (&Key, &Val)> {
+		self.rows.iter()
+	}
+
+	/// Changes since the last poll, the datastore must be the one the subscription was
+	/// created with, or the same database opened again for subscriptions of `poll_watch`
+	pub async fn poll(&mut self, ds: &Datastore) -> Result<Vec<WatchEvent>, Error> {
+		match &mut self.source {
+			Source::Poll => {
+				let rows = Subscription::scan(ds, &self.prefix, &self.tags).await?;
+				let events = diff_rows(&self.rows, &rows);
+				self.rows = rows;
+				Ok(events)
+			}
+			#[cfg(feature = "kv-sled")]
+			Source::Sled(subscriber) => {
+				let mut events = vec![];
+				while let Ok(event) = subscriber.next_timeout(std::time::Duration::ZERO) {
+					let event = match event {
+						sled::Event::Insert {
+							key,
+							value,
+						} => apply(&mut self.rows, key.to_vec(), Some(value.to_vec())),
+						sled::Event::Remove {
+							key,
+						} => apply(&mut self.rows, key.to_vec(), None),
+					};
+					events.extend(event);
+				}
+				Ok(events)
+			}
+		}
+	}
+}
+
+// Apply a change to the rows, a value written again unchanged is not an event
+#[cfg_attr(not(feature = "kv-sled"), allow(dead_code))]
+fn apply(rows: &mut BTreeMap<Key, Val>, key: Key, value: Option<Val>) -> Option<WatchEvent> {
+	match (rows.get(&key).cloned(), value) {
+		(None, Some(value)) => {
+			rows.insert(key.to_vec(), value.to_vec());
+			Some(WatchEvent::Insert {
+				key,
+				value,
+			})
+		}
+		(Some(old), Some(new)) if old != new => {
+			rows.insert(key.to_vec(), new.to_vec());
+			Some(WatchEvent::Update {
+				key,
+				old,
+				new,
+			})
+		}
+		(Some(old), None) => {
+			rows.remove(&key);
+			Some(WatchEvent::Delete {
+				key,
+				old,
+			})
+		}
+		_ => None,
+	}
+}
+
+fn diff_rows(old: &BTreeMap<Key, Val>, new: &BTreeMap<Key, Val>) -> Vec<WatchEvent> {
+	let pairs = |rows: &BTreeMap<Key, Val>| {
+		rows.iter().map(|(k, v)| Ok((k.to_vec(), v.to_vec()))).collect::<Vec<_>>()
+	};
+	let diffs = diff_pairs(pairs(old), pairs(new)).filter_map(Result::ok);
+	diffs
+		.map(|diff| match diff {
+			KeyDiff::Added {
+				key,
+				value,
+			} => WatchEvent::Insert {
+				key,
+				value,
+			},
+			KeyDiff::Removed {
+				key,
+				value,
+			} => WatchEvent::Delete {
+				key,
+				old: value,
+			},
+			KeyDiff::Changed {
+				key,
+				left,
+				right,
+			} => WatchEvent::Update {
+				key,
+				old: left,
+				new: right,
+			},
+		})
+		.collect()
+}
@@ -65,3 +65,23 @@ pub async fn should_put_key(adapter: impl DatastoreAdapter) {
 
 	assert!(tx.put(key, val, tags.clone()).await.is_err());
 }
+
+pub async fn should_cas_key(adapter: impl DatastoreAdapter) {
+	let adapter = adapter.spawn();
+	let tags = tag!("column_family" => "test_suite:v1".to_string());
+	let mut tx = adapter.transaction(true).await.unwrap();
+
+	let key = "mock cas key";
+	let val = "mock value";
+	let new_val = "mock value 2";
+
+	tx.cas(key, None, val, tags.clone()).await.unwrap();
+	assert!(tx.cas(key, None, val, tags.clone()).await.is_err());
+	assert!(tx.cas(key, Some(new_val), val, tags.clone()).await.is_err());
+	tx.cas(key, Some(val), new_val, tags.clone()).await.unwrap();
+	let res = tx.get(key, tags.clone()).await.unwrap();
+	match res {
+		Some(v) => assert_eq!(new_val, from_utf8(&v).unwrap()),
+		None => panic!("Wrong value"),
+	}
+}
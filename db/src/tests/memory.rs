@@ -0,0 +1,164 @@
+use std::{
+	collections::BTreeMap,
+	sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+
+use crate::{
+	interface::{Key, KeyValuePair, Val},
+	Error, PluginAdapter, PluginTransaction, TagBucket,
+};
+
+type Store = Arc<Mutex<BTreeMap<Key, Val>>>;
+
+/// In-memory adapter used to test the plugin registry
+pub struct MemoryAdapter {
+	path: String,
+	store: Store,
+}
+
+impl MemoryAdapter {
+	pub fn new(path: &str) -> Self {
+		MemoryAdapter {
+			path: path.to_string(),
+			store: Default::default(),
+		}
+	}
+}
+
+pub struct MemoryTransaction {
+	ok: bool,
+	writable: bool,
+	store: Store,
+}
+
+#[async_trait]
+impl PluginAdapter for MemoryAdapter {
+	async fn transaction(&self, rw: bool) -> Result<Box<dyn PluginTransaction>, Error> {
+		Ok(Box::new(MemoryTransaction {
+			ok: false,
+			writable: rw,
+			store: self.store.clone(),
+		}))
+	}
+
+	fn keyspaces(&self) -> Result<Vec<String>, Error> {
+		Ok(vec![])
+	}
+
+	fn path(&self) -> &str {
+		&self.path
+	}
+}
+
+impl MemoryTransaction {
+	fn collect<F: Fn(&Key) -> bool>(&self, filter: F) -> Vec<Result<KeyValuePair, Error>> {
+		let store = self.store.lock().unwrap();
+		store.iter().filter(|(k, _)| filter(k)).map(|(k, v)| Ok((k.clone(), v.clone()))).collect()
+	}
+}
+
+#[async_trait(?Send)]
+impl PluginTransaction for MemoryTransaction {
+	fn closed(&self) -> bool {
+		self.ok
+	}
+
+	async fn cancel(&mut self) -> Result<(), Error> {
+		if self.ok {
+			return Err(Error::TxFinished);
+		}
+		self.ok = true;
+		Ok(())
+	}
+
+	async fn count(&mut self, _tags: TagBucket) -> Result<usize, Error> {
+		Ok(self.store.lock().unwrap().len())
+	}
+
+	async fn commit(&mut self) -> Result<(), Error> {
+		if self.ok {
+			return Err(Error::TxFinished);
+		}
+		if !self.writable {
+			return Err(Error::TxReadonly);
+		}
+		self.ok = true;
+		Ok(())
+	}
+
+	async fn exi(&self, key: Key, _tags: TagBucket) -> Result<bool, Error> {
+		Ok(self.store.lock().unwrap().contains_key(&key))
+	}
+
+	async fn get(&self, key: Key, _tags: TagBucket) -> Result<Option<Val>, Error> {
+		Ok(self.store.lock().unwrap().get(&key).cloned())
+	}
+
+	async fn set(&mut self, key: Key, val: Val, _tags: TagBucket) -> Result<(), Error> {
+		if !self.writable {
+			return Err(Error::TxReadonly);
+		}
+		self.store.lock().unwrap().insert(key, val);
+		Ok(())
+	}
+
+	async fn put(&mut self, key: Key, val: Val, _tags: TagBucket) -> Result<(), Error> {
+		if !self.writable {
+			return Err(Error::TxReadonly);
+		}
+		let mut store = self.store.lock().unwrap();
+		if store.contains_key(&key) {
+			return Err(Error::TxKeyAlreadyExists);
+		}
+		store.insert(key, val);
+		Ok(())
+	}
+
+	async fn cas(
+		&mut self,
+		key: Key,
+		expected: Option<Val>,
+		new: Val,
+		_tags: TagBucket,
+	) -> Result<(), Error> {
+		if !self.writable {
+			return Err(Error::TxReadonly);
+		}
+		let mut store = self.store.lock().unwrap();
+		if store.get(&key) != expected.as_ref() {
+			return Err(Error::TxConditionNotMet);
+		}
+		store.insert(key, new);
+		Ok(())
+	}
+
+	async fn del(&mut self, key: Key, _tags: TagBucket) -> Result<(), Error> {
+		if !self.writable {
+			return Err(Error::TxReadonly);
+		}
+		self.store.lock().unwrap().remove(&key);
+		Ok(())
+	}
+
+	async fn iterate(&self, _tags: TagBucket) -> Result<Vec<Result<KeyValuePair, Error>>, Error> {
+		Ok(self.collect(|_| true))
+	}
+
+	async fn prefix_iterate(
+		&self,
+		prefix: Key,
+		_tags: TagBucket,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error> {
+		Ok(self.collect(|k| k.starts_with(&prefix)))
+	}
+
+	async fn suffix_iterate(
+		&self,
+		suffix: Key,
+		_tags: TagBucket,
+	) -> Result<Vec<Result<KeyValuePair, Error>>, Error> {
+		Ok(self.collect(|k| k.ends_with(&suffix)))
+	}
+}
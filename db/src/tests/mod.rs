@@ -1,3 +1,5 @@
 mod adapter_test;
+mod memory;
 
 pub use adapter_test::*;
+pub use memory::*;
@@ -0,0 +1,234 @@
+//! Sample EDMA storage plugin.
+//!
+//! Keeps every keyspace in memory and rewrites a single file on each write. Records are stored
+//! as `keyspace`, `key` and `value`, each prefixed with its length as a little endian `u32`.
+//! Build it with `cargo build -p edma_plugin_sample` and add the resulting shared library to
+//! the `plugins` list of `edma.json`.
+use std::{
+	collections::BTreeMap,
+	ffi::c_void,
+	fs,
+	io::{self, Read},
+	path::PathBuf,
+	sync::Mutex,
+};
+
+use db::abi::{
+	EdmaHandle, EdmaPluginVTable, EdmaSlice, EdmaVisitor, EDMA_ERROR, EDMA_NOT_FOUND, EDMA_OK,
+	EDMA_PLUGIN_ABI_VERSION,
+};
+
+type Keyspace = BTreeMap<Vec<u8>, Vec<u8>>;
+
+struct Store {
+	path: PathBuf,
+	keyspaces: Mutex<BTreeMap<Vec<u8>, Keyspace>>,
+}
+
+impl Store {
+	fn open(path: PathBuf) -> io::Result<Store> {
+		let mut keyspaces: BTreeMap<Vec<u8>, Keyspace> = BTreeMap::new();
+		if path.exists() {
+			let bytes = fs::read(&path)?;
+			let mut reader = bytes.as_slice();
+			while !reader.is_empty() {
+				let keyspace = read_chunk(&mut reader)?;
+				let key = read_chunk(&mut reader)?;
+				let value = read_chunk(&mut reader)?;
+				keyspaces.entry(keyspace).or_default().insert(key, value);
+			}
+		}
+
+		Ok(Store {
+			path,
+			keyspaces: Mutex::new(keyspaces),
+		})
+	}
+
+	fn persist(&self, keyspaces: &BTreeMap<Vec<u8>, Keyspace>) -> io::Result<()> {
+		let mut bytes = vec![];
+		for (keyspace, pairs) in keyspaces.iter() {
+			for (key, value) in pairs.iter() {
+				write_chunk(&mut bytes, keyspace);
+				write_chunk(&mut bytes, key);
+				write_chunk(&mut bytes, value);
+			}
+		}
+		fs::write(&self.path, bytes)
+	}
+}
+
+fn read_chunk(reader: &mut &[u8]) -> io::Result<Vec<u8>> {
+	let mut len = [0u8; 4];
+	reader.read_exact(&mut len)?;
+	let mut chunk = vec![0u8; u32::from_le_bytes(len) as usize];
+	reader.read_exact(&mut chunk)?;
+	Ok(chunk)
+}
+
+fn write_chunk(bytes: &mut Vec<u8>, chunk: &[u8]) {
+	bytes.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+	bytes.extend_from_slice(chunk);
+}
+
+fn store<'a>(handle: EdmaHandle) -> &'a Store {
+	unsafe { &*(handle as *const Store) }
+}
+
+extern "C" fn open(path: EdmaSlice, handle: *mut EdmaHandle) -> i32 {
+	let path = String::from_utf8_lossy(unsafe { path.as_bytes() }).to_string();
+	match Store::open(PathBuf::from(path)) {
+		Ok(store) => {
+			unsafe { *handle = Box::into_raw(Box::new(store)) as EdmaHandle };
+			EDMA_OK
+		}
+		Err(_) => EDMA_ERROR,
+	}
+}
+
+extern "C" fn list_keyspaces(handle: EdmaHandle, visitor: EdmaVisitor, ctx: *mut c_void) -> i32 {
+	let keyspaces = store(handle).keyspaces.lock().unwrap();
+	for keyspace in keyspaces.keys().filter(|keyspace| !keyspace.is_empty()) {
+		if !visitor(ctx, EdmaSlice::from(keyspace.as_slice()), EdmaSlice::empty()) {
+			break;
+		}
+	}
+	EDMA_OK
+}
+
+extern "C" fn scan(
+	handle: EdmaHandle,
+	keyspace: EdmaSlice,
+	prefix: EdmaSlice,
+	visitor: EdmaVisitor,
+	ctx: *mut c_void,
+) -> i32 {
+	let keyspaces = store(handle).keyspaces.lock().unwrap();
+	let prefix = unsafe { prefix.as_bytes() };
+	if let Some(pairs) = keyspaces.get(unsafe { keyspace.as_bytes() }) {
+		for (key, value) in pairs.range(prefix.to_vec()..) {
+			if !key.starts_with(prefix) {
+				break;
+			}
+			if !visitor(ctx, EdmaSlice::from(key.as_slice()), EdmaSlice::from(value.as_slice())) {
+				break;
+			}
+		}
+	}
+	EDMA_OK
+}
+
+extern "C" fn get(
+	handle: EdmaHandle,
+	keyspace: EdmaSlice,
+	key: EdmaSlice,
+	visitor: EdmaVisitor,
+	ctx: *mut c_void,
+) -> i32 {
+	let keyspaces = store(handle).keyspaces.lock().unwrap();
+	let key = unsafe { key.as_bytes() };
+	let value = keyspaces.get(unsafe { keyspace.as_bytes() }).and_then(|pairs| pairs.get(key));
+	match value {
+		Some(value) => {
+			visitor(ctx, EdmaSlice::from(key), EdmaSlice::from(value.as_slice()));
+			EDMA_OK
+		}
+		None => EDMA_NOT_FOUND,
+	}
+}
+
+extern "C" fn put(
+	handle: EdmaHandle,
+	keyspace: EdmaSlice,
+	key: EdmaSlice,
+	value: EdmaSlice,
+) -> i32 {
+	let store = store(handle);
+	let mut keyspaces = store.keyspaces.lock().unwrap();
+	let (keyspace, key, value) = unsafe { (keyspace.as_bytes(), key.as_bytes(), value.as_bytes()) };
+	keyspaces.entry(keyspace.to_vec()).or_default().insert(key.to_vec(), value.to_vec());
+	match store.persist(&keyspaces) {
+		Ok(_) => EDMA_OK,
+		Err(_) => EDMA_ERROR,
+	}
+}
+
+extern "C" fn delete(handle: EdmaHandle, keyspace: EdmaSlice, key: EdmaSlice) -> i32 {
+	let store = store(handle);
+	let mut keyspaces = store.keyspaces.lock().unwrap();
+	let (keyspace, key) = unsafe { (keyspace.as_bytes(), key.as_bytes()) };
+	let removed = keyspaces.get_mut(keyspace).and_then(|pairs| pairs.remove(key));
+	if removed.is_none() {
+		return EDMA_NOT_FOUND;
+	}
+	match store.persist(&keyspaces) {
+		Ok(_) => EDMA_OK,
+		Err(_) => EDMA_ERROR,
+	}
+}
+
+extern "C" fn close(handle: EdmaHandle) {
+	if !handle.is_null() {
+		drop(unsafe { Box::from_raw(handle as *mut Store) });
+	}
+}
+
+pub static VTABLE: EdmaPluginVTable = EdmaPluginVTable {
+	abi_version: EDMA_PLUGIN_ABI_VERSION,
+	open,
+	list_keyspaces,
+	scan,
+	get,
+	put,
+	delete,
+	close,
+};
+
+#[no_mangle]
+pub extern "C" fn edma_plugin_vtable() -> *const EdmaPluginVTable {
+	&VTABLE
+}
+
+#[cfg(test)]
+mod test {
+	use std::{env::temp_dir, sync::Arc};
+
+	use db::{register_adapter, tag, Datastore, PluginAdapter, PluginLibrary, SimpleTransaction};
+
+	use super::VTABLE;
+
+	#[tokio::test]
+	async fn should_persist_keyspaces() {
+		let library = Arc::new(PluginLibrary::from_vtable(&VTABLE).unwrap());
+		register_adapter("sample", move |path| {
+			Ok(Box::new(library.open(path)?) as Box<dyn PluginAdapter>)
+		})
+		.unwrap();
+
+		let path = temp_dir().join("edma_plugin_sample.db");
+		let _ = std::fs::remove_file(&path);
+		let path = format!("sample:{}", path.display());
+		let users = tag!("column_family" => "users".to_string());
+
+		let db = Datastore::new(&path);
+		let mut tx = db.transaction(true).await.unwrap();
+		tx.set("hello", "world", tag!()).await.unwrap();
+		tx.set("key", "value", users.clone()).await.unwrap();
+		tx.set("key 2", "value 2", users.clone()).await.unwrap();
+		assert!(tx.put("hello", "world", tag!()).await.is_err());
+		tx.commit().await.unwrap();
+		drop(tx);
+		drop(db);
+
+		let db = Datastore::new(&path);
+		assert_eq!(db.keyspaces().unwrap(), vec!["users".to_string()]);
+		let mut tx = db.transaction(true).await.unwrap();
+		let val = tx.get("hello", tag!()).await.unwrap();
+		assert_eq!(val, Some(b"world".to_vec()));
+		let iter = tx.prefix_iterate("key", users.clone()).await.unwrap();
+		assert!(iter.len() == 2);
+		tx.del("key", users.clone()).await.unwrap();
+		assert_eq!(tx.count(users.clone()).await.unwrap(), 1);
+		tx.commit().await.unwrap();
+	}
+}
@@ -113,6 +113,11 @@ impl<'a> AppComponent<'a> {
 		Ok(EventState::NotConsumed)
 	}
 
+	// Let components refresh data that changes without user input
+	pub async fn tick(&mut self) {
+		self.database.tick().await;
+	}
+
 	async fn components_event(&mut self, key: Key) -> Result<EventState> {
 		match self.focus {
 			Focus::MenuContainer => {
@@ -1,4 +1,15 @@
-use crate::config::CliConfig;
+use std::{fs, fs::File, io::BufWriter, path::Path};
+
+use anyhow::{anyhow, Result};
+use db::{
+	tag, ConflictPolicy, ImportOptions, ImportProgress, MigrateCheckpoint, MigrateOptions,
+	MigrateProgress, SimpleTransaction,
+};
+
+use crate::{
+	config::{CliConfig, Config},
+	utils::{decode_hex, encode_hex, export_pairs, import_file, ExportEncoding, ExportFormat},
+};
 use structopt::StructOpt;
 
 /// A cross-platform TUI database management tool written in Rust
@@ -7,8 +18,253 @@ use structopt::StructOpt;
 pub struct Cli {
 	#[structopt(flatten)]
 	pub config: CliConfig,
+	#[structopt(subcommand)]
+	pub command: Option<Command>,
+}
+
+#[derive(StructOpt, Debug)]
+pub enum Command {
+	/// Export a keyspace of a database to JSON Lines, CSV or a binary dump
+	Export(ExportCommand),
+	/// Import JSON Lines, CSV or a binary dump into a keyspace of a database
+	Import(ImportCommand),
+	/// Copy every keyspace of a database to a database of another engine
+	Migrate(MigrateCommand),
+}
+
+#[derive(StructOpt, Debug)]
+pub struct ExportCommand {
+	/// Database to export, as `<name>:<path>` of the config file, e.g. `rocksdb:./db`
+	#[structopt(long, short)]
+	database: String,
+	/// Column family, table or tree to export, the default keyspace when not set
+	#[structopt(long, short)]
+	keyspace: Option<String>,
+	/// Only export keys starting with the prefix
+	#[structopt(long)]
+	prefix: Option<String>,
+	/// `jsonl`, `csv` or `dump`, guessed from the extension of the output when not set
+	#[structopt(long, short)]
+	format: Option<String>,
+	/// Encoding of keys: `hex`, `base64` or the name of a template
+	#[structopt(long, default_value = "hex")]
+	key_encoding: String,
+	/// Encoding of values: `hex`, `base64` or the name of a template
+	#[structopt(long, default_value = "hex")]
+	value_encoding: String,
+	/// File to write
+	#[structopt(long, short)]
+	output: String,
+}
+
+#[derive(StructOpt, Debug)]
+pub struct ImportCommand {
+	/// Database to import into, as `<name>:<path>` of the config file, e.g. `rocksdb:./db`
+	#[structopt(long, short)]
+	database: String,
+	/// Column family, table or tree to import into, the default keyspace when not set
+	#[structopt(long, short)]
+	keyspace: Option<String>,
+	/// `jsonl`, `csv` or `dump`, guessed from the extension of the input when not set
+	#[structopt(long, short)]
+	format: Option<String>,
+	/// Encoding of keys: `hex` or `base64`
+	#[structopt(long, default_value = "hex")]
+	key_encoding: String,
+	/// Encoding of values: `hex` or `base64`
+	#[structopt(long, default_value = "hex")]
+	value_encoding: String,
+	/// What to do with keys that already exist: `overwrite`, `skip` or `fail`
+	#[structopt(long, default_value = "overwrite")]
+	conflict: String,
+	/// Number of pairs written in one transaction
+	#[structopt(long, default_value = "1000")]
+	batch_size: usize,
+	/// File to read
+	#[structopt(long, short)]
+	input: String,
+}
+
+#[derive(StructOpt, Debug)]
+pub struct MigrateCommand {
+	/// Database to copy, as `<name>:<path>` of the config file, e.g. `rocksdb:./db`
+	#[structopt(long, short)]
+	source: String,
+	/// Database to copy to, e.g. `redb:./db.redb`
+	#[structopt(long, short)]
+	target: String,
+	/// Number of pairs read and written in one transaction
+	#[structopt(long, default_value = "1000")]
+	batch_size: usize,
+	/// File keeping the position of the migration, an interrupted migration resumes from it
+	#[structopt(long)]
+	checkpoint: Option<String>,
 }
 
 pub fn parse() -> Cli {
 	Cli::from_args()
 }
+
+/// Run a subcommand instead of the terminal UI
+pub async fn run(config: &Config, command: Command) -> Result<()> {
+	match command {
+		Command::Export(export) => run_export(config, export).await,
+		Command::Import(import) => run_import(config, import).await,
+		Command::Migrate(migrate) => run_migrate(config, migrate).await,
+	}
+}
+
+async fn run_export(config: &Config, export: ExportCommand) -> Result<()> {
+	let format = match &export.format {
+		Some(format) => ExportFormat::from_string(format),
+		None => ExportFormat::from_path(&export.output),
+	};
+	let format = format.ok_or_else(|| anyhow!("Unknown export format, use jsonl, csv or dump"))?;
+	let encoding = |name: &str| {
+		ExportEncoding::from_string(name, &config.templates)
+			.ok_or_else(|| anyhow!("Unknown encoding {}, use hex, base64 or a template", name))
+	};
+	let (key, value) = (encoding(&export.key_encoding)?, encoding(&export.value_encoding)?);
+
+	let ds = config.open_datastore(&export.database);
+	let tags = match &export.keyspace {
+		Some(keyspace) => ds.keyspace_tags(keyspace),
+		None => tag!(),
+	};
+	let tx = ds.transaction(false).await?;
+	let scanned = match export.prefix {
+		Some(prefix) => tx.prefix_iterate(prefix, tags).await?,
+		None => tx.iterate(tags).await?,
+	};
+	let pairs = scanned.into_iter().collect::<Result<Vec<_>, _>>()?;
+
+	let mut writer = BufWriter::new(File::create(&export.output)?);
+	export_pairs(&mut writer, &pairs, &format, &key, &value)?;
+	println!("Exported {} key-value pairs to {}", pairs.len(), export.output);
+	Ok(())
+}
+
+async fn run_import(config: &Config, import: ImportCommand) -> Result<()> {
+	let format = match &import.format {
+		Some(format) => ExportFormat::from_string(format),
+		None => ExportFormat::from_path(&import.input),
+	};
+	let format = format.ok_or_else(|| anyhow!("Unknown import format, use jsonl, csv or dump"))?;
+	let encoding = |name: &str| {
+		ExportEncoding::from_string(name, &config.templates)
+			.ok_or_else(|| anyhow!("Unknown encoding {}, use hex or base64", name))
+	};
+	let (key, value) = (encoding(&import.key_encoding)?, encoding(&import.value_encoding)?);
+	let conflict = match import.conflict.as_str() {
+		"overwrite" => ConflictPolicy::Overwrite,
+		"skip" => ConflictPolicy::Skip,
+		"fail" => ConflictPolicy::Fail,
+		other => {
+			return Err(anyhow!("Unknown conflict policy {}, use overwrite, skip or fail", other))
+		}
+	};
+	let options = ImportOptions {
+		batch_size: import.batch_size,
+		conflict,
+	};
+
+	let pairs = import_file(&import.input, &format, &key, &value)?;
+	let total = pairs.len();
+	let ds = config.open_datastore(&import.database);
+	let tags = match &import.keyspace {
+		Some(keyspace) => ds.keyspace_tags(keyspace),
+		None => tag!(),
+	};
+	let report = |progress: &ImportProgress| {
+		let done = progress.imported + progress.skipped;
+		println!("{}/{} key-value pairs processed", done, total);
+	};
+	let progress = ds.import(pairs, tags, &options, report).await?;
+	println!(
+		"Imported {} key-value pairs from {}, skipped {}",
+		progress.imported, import.input, progress.skipped
+	);
+	Ok(())
+}
+
+// The checkpoint file holds the keyspace, the hex encoded key and the number of its values
+// copied on a line each
+fn read_checkpoint(path: &str) -> Result<Option<MigrateCheckpoint>> {
+	if !Path::new(path).exists() {
+		return Ok(None);
+	}
+	let content = fs::read_to_string(path)?;
+	let mut lines = content.lines();
+	let invalid = || anyhow!("Invalid checkpoint file {}", path);
+	match (lines.next(), lines.next(), lines.next()) {
+		(Some(keyspace), Some(key), Some(values)) => Ok(Some(MigrateCheckpoint {
+			keyspace: keyspace.to_string(),
+			key: decode_hex(key)?,
+			values: values.parse().map_err(|_| invalid())?,
+		})),
+		_ => Err(invalid()),
+	}
+}
+
+async fn run_migrate(config: &Config, migrate: MigrateCommand) -> Result<()> {
+	if migrate.source == migrate.target {
+		return Err(anyhow!("Can't migrate a database to itself"));
+	}
+	let resume = match &migrate.checkpoint {
+		Some(path) => read_checkpoint(path)?,
+		None => None,
+	};
+	if let Some(checkpoint) = &resume {
+		println!("Resuming from key {} of {}", encode_hex(&checkpoint.key), checkpoint.keyspace);
+	}
+	let options = MigrateOptions {
+		batch_size: migrate.batch_size,
+		resume,
+	};
+
+	let source = config.open_datastore(&migrate.source);
+	let target = config.open_datastore(&migrate.target);
+	let mut saved = Ok(());
+	let track = |progress: &MigrateProgress| {
+		let MigrateCheckpoint {
+			keyspace,
+			key,
+			values,
+		} = &progress.checkpoint;
+		println!("{} key-value pairs migrated, now in {}", progress.migrated, keyspace);
+		if let (Some(path), Ok(_)) = (&migrate.checkpoint, &saved) {
+			let checkpoint = format!("{}\n{}\n{}\n", keyspace, encode_hex(key), values);
+			saved = fs::write(path, checkpoint);
+		}
+	};
+	let report = source.migrate(&target, &options, track).await?;
+	saved?;
+	// Every keyspace is copied, running the migration again starts over
+	if let Some(path) = &migrate.checkpoint {
+		if Path::new(path).exists() {
+			fs::remove_file(path)?;
+		}
+	}
+
+	for keyspace in report.keyspaces.iter() {
+		let status = if keyspace.verified() {
+			"ok"
+		} else {
+			"MISMATCH"
+		};
+		println!(
+			"{}: {} pairs, checksum {:016x} / target {} pairs, checksum {:016x} {}",
+			keyspace.keyspace,
+			keyspace.source.count,
+			keyspace.source.checksum,
+			keyspace.target.count,
+			keyspace.target.checksum,
+			status
+		);
+	}
+	if !report.verified() {
+		return Err(anyhow!("The target doesn't match the source"));
+	}
+	println!("Migrated {} key-value pairs to {}", report.migrated, migrate.target);
+	Ok(())
+}
@@ -59,7 +59,7 @@ impl CommandComponent {
 		let splitted = complete.split(' ');
 		for token in splitted {
 			match token {
-				t if token.starts_with("COLUMN") | token.starts_with("TABLE") => {
+				t if token.starts_with("COLUMN") => {
 					let value = t.split('=').nth(1);
 					match value {
 						Some(v) => self.add_command(Command {
@@ -71,6 +71,18 @@ impl CommandComponent {
 						}
 					}
 				}
+				t if token.starts_with("TABLE") => {
+					let value = t.split('=').nth(1);
+					match value {
+						Some(v) => self.add_command(Command {
+							token: "TABLE".to_string(),
+							value: v.to_string(),
+						}),
+						None => {
+							return self.set_invalid(true, "No TABLE value found");
+						}
+					}
+				}
 				t if token.starts_with("TREE") => {
 					let value = t.split('=').nth(1);
 					match value {
@@ -95,6 +107,19 @@ impl CommandComponent {
 						}
 					}
 				}
+				// Bounds are dates or text, either can be left out
+				t if token.starts_with("RANGE") => {
+					let value = t.split('=').nth(1).filter(|value| value.contains(".."));
+					match value {
+						Some(v) => self.add_command(Command {
+							token: "RANGE".to_string(),
+							value: v.replace('"', ""),
+						}),
+						None => {
+							return self.set_invalid(true, "No RANGE value found, use start..end");
+						}
+					}
+				}
 				t if token.starts_with("SUFFIX") => {
 					let value = t.split('=').nth(1);
 					match value {
@@ -107,6 +132,82 @@ impl CommandComponent {
 						}
 					}
 				}
+				// Maintenance commands run on the selected database
+				t if token.starts_with("COMPACT") => {
+					let value = t.split('=').nth(1).unwrap_or("");
+					self.add_command(Command {
+						token: "COMPACT".to_string(),
+						value: value.to_string(),
+					})
+				}
+				"FLUSH" => self.add_command(Command {
+					token: "FLUSH".to_string(),
+					value: "".to_string(),
+				}),
+				t if token.starts_with("CHECKPOINT") => {
+					let value = t.split('=').nth(1);
+					match value {
+						Some(v) => self.add_command(Command {
+							token: "CHECKPOINT".to_string(),
+							value: v.replace('"', "").to_string(),
+						}),
+						None => {
+							return self.set_invalid(true, "No CHECKPOINT value found");
+						}
+					}
+				}
+				t if token.starts_with("EXPORT") => {
+					let value = t.split('=').nth(1);
+					match value {
+						Some(v) => self.add_command(Command {
+							token: "EXPORT".to_string(),
+							value: v.replace('"', "").to_string(),
+						}),
+						None => {
+							return self.set_invalid(true, "No EXPORT value found");
+						}
+					}
+				}
+				t if token.starts_with("IMPORT") => {
+					let value = t.split('=').nth(1);
+					match value {
+						Some(v) => self.add_command(Command {
+							token: "IMPORT".to_string(),
+							value: v.replace('"', "").to_string(),
+						}),
+						None => {
+							return self.set_invalid(true, "No IMPORT value found");
+						}
+					}
+				}
+				t if token.starts_with("MIGRATE") => {
+					let value = t.split('=').nth(1);
+					match value {
+						Some(v) => self.add_command(Command {
+							token: "MIGRATE".to_string(),
+							value: v.replace('"', "").to_string(),
+						}),
+						None => {
+							return self.set_invalid(true, "No MIGRATE value found");
+						}
+					}
+				}
+				// Without a name the suggested templates are only used by the preview
+				t if token.starts_with("SUGGEST") => {
+					let value = t.split('=').nth(1).unwrap_or("");
+					self.add_command(Command {
+						token: "SUGGEST".to_string(),
+						value: value.replace('"', ""),
+					})
+				}
+				// Without a database the pinned snapshot is compared with the latest changes
+				t if token.starts_with("DIFF") => {
+					let value = t.split('=').nth(1).unwrap_or("");
+					self.add_command(Command {
+						token: "DIFF".to_string(),
+						value: value.replace('"', ""),
+					})
+				}
 				_ => return self.set_invalid(true, "Mismatch command"),
 			}
 		}
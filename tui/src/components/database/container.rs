@@ -2,10 +2,15 @@ use crate::{
 	components::RenderAbleComponent,
 	config::Config,
 	events::{EventState, Key},
-	utils::get_absolute_path,
+	utils::{
+		describe_template, export_pairs, get_absolute_path, get_key_char, import_file,
+		sample_pairs, suggest_template, table_templates, ExportEncoding, ExportFormat,
+		LayoutTemplate,
+	},
 };
-use anyhow::Result;
-use db::tag;
+use anyhow::{anyhow, Result};
+use db::{tag, ImportOptions, ImportProgress, MigrateOptions, MigrateProgress};
+use std::{fs::File, io::BufWriter};
 use tui::{
 	backend::Backend,
 	layout::{Constraint, Direction, Layout, Rect},
@@ -14,8 +19,9 @@ use tui::{
 };
 
 use super::{
-	database_explorer::DatabaseExplorerComponent, CommandComponent, DatabaseEditorComponent,
-	DatabaseSelectionComponent, StatusComponent,
+	database_explorer::DatabaseExplorerComponent, CommandComponent, DatabaseDiffComponent,
+	DatabaseEditorComponent, DatabaseInfoComponent, DatabaseSelectionComponent,
+	DatabaseStatsComponent, StatusComponent,
 };
 
 enum Focus {
@@ -31,7 +37,10 @@ pub struct DatabaseTabComponent<'a> {
 	databases: DatabaseSelectionComponent<'a>,
 	explorer: DatabaseExplorerComponent<'a>,
 	editor: DatabaseEditorComponent<'a>,
+	diff: DatabaseDiffComponent<'a>,
 	status: StatusComponent<'a>,
+	info: DatabaseInfoComponent,
+	stats: DatabaseStatsComponent,
 }
 
 impl<'a> DatabaseTabComponent<'a> {
@@ -39,7 +48,10 @@ impl<'a> DatabaseTabComponent<'a> {
 		DatabaseTabComponent {
 			explorer: DatabaseExplorerComponent::new(config.clone()),
 			editor: DatabaseEditorComponent::new(config.clone()),
+			diff: DatabaseDiffComponent::new(config.clone()),
 			status: StatusComponent::new(config.clone()),
+			info: DatabaseInfoComponent::default(),
+			stats: DatabaseStatsComponent::new(config.clone()),
 			databases: DatabaseSelectionComponent::new(config.clone()),
 			command: CommandComponent::new(config.clone()),
 			focus: Focus::Explorer,
@@ -65,9 +77,16 @@ impl<'a> DatabaseTabComponent<'a> {
 	}
 
 	async fn handle_command_event(&mut self) {
+		// The database is locked by the running operation until it is done
+		if self.status.is_busy() {
+			return self.command.reset_command();
+		}
+		self.diff.close();
 		let commands = self.command.commands.to_vec();
 		let mut tags = tag!();
-		let (name, path, _) = self.get_database_info();
+		let (name, path, abs_p) = self.get_database_info();
+		// A range is scanned within the prefix of the same command
+		let mut prefix = vec![];
 		for command in commands {
 			match command.token.as_str() {
 				// COLUMN is specified for RocksDB, Redb should be TABLE
@@ -75,37 +94,310 @@ impl<'a> DatabaseTabComponent<'a> {
 					tags.insert("column_family", command.value);
 					self.editor.scan_database(tags.clone(), &name, &path).await;
 				}
+				// Redb tables are typed, the table type is shown in the status bar
+				"TABLE" => {
+					let db_path = format!("{}:{}", name, path);
+					let table_type = match self.editor.open_datastore(&db_path) {
+						Some(ds) => ds.keyspace_type(&command.value),
+						None => self.config.open_datastore(&db_path).keyspace_type(&command.value),
+					};
+					match table_type {
+						Ok(Some(ty)) => {
+							let text = format!("{} {} {}", abs_p, command.value, ty);
+							self.status.set_text(Span::raw(text));
+							if let Some((key, value)) = table_templates(&ty) {
+								self.editor.use_templates(key, value);
+							}
+						}
+						_ => self.status.set_text(Span::raw(abs_p.clone())),
+					}
+					tags.insert("column_family", command.value);
+					self.editor.scan_database(tags.clone(), &name, &path).await;
+				}
 				"TREE" => {
 					tags.insert("tree", command.value);
 					self.editor.scan_database(tags.clone(), &name, &path).await;
 				}
 				// PREFIX and SUFFIX scan only support key traversal not value traversal
 				"PREFIX" => {
-					let prefix = &command.value;
-					let bytes = prefix.as_bytes().to_vec();
+					prefix = command.value.as_bytes().to_vec();
+					let bytes = prefix.to_vec();
 					self.editor.prefix_scan_database(tags.clone(), &name, &path, bytes).await;
 				}
+				// Bounds are encoded by the key template selected in the preview when it reads
+				// timestamps or dates, and follow the prefix
+				"RANGE" => {
+					let (start, end) = match command.value.split_once("..") {
+						Some(bounds) => bounds,
+						None => {
+							let text = "Invalid range: use RANGE=start..end, e.g. RANGE=a..m";
+							self.status.set_text(Span::raw(text));
+							continue;
+						}
+					};
+					let template = self.editor.scan_results().1 .0;
+					let encode = |bound: &str| -> Result<Option<Vec<u8>>, String> {
+						if bound.is_empty() {
+							return Ok(None);
+						}
+						let bytes = match template.as_ref().and_then(|t| t.encode_time(bound)) {
+							Some(bytes) => bytes?,
+							None => bound.as_bytes().to_vec(),
+						};
+						Ok(Some([prefix.as_slice(), &bytes].concat()))
+					};
+					match (encode(start), encode(end)) {
+						(Ok(start), Ok(end)) => {
+							let start = start.unwrap_or_else(|| prefix.to_vec());
+							let (tags, bytes) = (tags.clone(), prefix.to_vec());
+							self.editor
+								.range_scan_database(tags, &name, &path, bytes, start, end)
+								.await;
+						}
+						(Err(err), _) | (_, Err(err)) => {
+							self.status.set_text(Span::raw(format!("Invalid range: {}", err)))
+						}
+					}
+				}
 				"SUFFIX" => {
 					let suffix = &command.value;
 					let bytes = suffix.as_bytes().to_vec();
 					self.editor.suffix_scan_database(tags.clone(), &name, &path, bytes).await;
 				}
+				// The editor releases the database so the operation can open it
+				"COMPACT" => {
+					self.editor.release_datastore();
+					let (config, db_path) = (self.config.clone(), format!("{}:{}", name, path));
+					let keyspace = Some(command.value).filter(|keyspace| !keyspace.is_empty());
+					self.status.run_task(&format!("Compacting {}", abs_p), move || {
+						config.try_open_datastore(&db_path)?.compact_range(
+							keyspace.as_deref(),
+							None,
+							None,
+						)
+					});
+				}
+				"FLUSH" => {
+					self.editor.release_datastore();
+					let (config, db_path) = (self.config.clone(), format!("{}:{}", name, path));
+					self.status.run_task(&format!("Flushing {}", abs_p), move || {
+						config.try_open_datastore(&db_path)?.flush()
+					});
+				}
+				"CHECKPOINT" => {
+					self.editor.release_datastore();
+					let (config, db_path) = (self.config.clone(), format!("{}:{}", name, path));
+					let dest = get_absolute_path(&command.value);
+					self.status.run_task(&format!("Checkpoint to {}", dest), move || {
+						config.try_open_datastore(&db_path)?.checkpoint(&dest)
+					});
+				}
+				// Scan results are exported with the templates selected in the preview
+				"EXPORT" => {
+					let dest = get_absolute_path(&command.value);
+					let text = match self.export_scan(&dest) {
+						Ok(count) => format!("Exported {} key-value pairs to {}", count, dest),
+						Err(err) => format!("Export to {} failed: {}", dest, err),
+					};
+					self.status.set_text(Span::raw(text));
+				}
+				// Templates are suggested from samples of the pairs listed in the editor and
+				// selected in the preview, a name saves them to the config file
+				"SUGGEST" => {
+					let text = self.suggest_templates(&command.value);
+					self.status.set_text(Span::raw(text));
+				}
+				// Hex encoded files are imported into the keyspace of the command, existing keys
+				// are overwritten
+				"IMPORT" => {
+					self.editor.release_datastore();
+					let (config, db_path) = (self.config.clone(), format!("{}:{}", name, path));
+					let (source, tags) = (get_absolute_path(&command.value), tags.clone());
+					let label = format!("Importing {}", source);
+					self.status.run_task_with_progress(&label, move |progress| {
+						let format = ExportFormat::from_path(&source).ok_or_else(|| {
+							anyhow!("unknown format, use a .jsonl, .csv or .dump file")
+						})?;
+						let encoding = ExportEncoding::Hex;
+						let pairs = import_file(&source, &format, &encoding, &encoding)?;
+						let report = |done: &ImportProgress| {
+							let text =
+								format!("{} imported, {} skipped", done.imported, done.skipped);
+							*progress.lock().unwrap() = Some(text);
+						};
+						let runtime = tokio::runtime::Builder::new_current_thread().build()?;
+						let ds = config.try_open_datastore(&db_path)?;
+						runtime.block_on(ds.import(
+							pairs,
+							tags,
+							&ImportOptions::default(),
+							report,
+						))?;
+						Ok::<(), anyhow::Error>(())
+					});
+				}
+				// Every keyspace is copied to a database of any engine, e.g. `redb:./db.redb`
+				"MIGRATE" => {
+					if command.value == format!("{}:{}", name, path) {
+						self.status.set_text(Span::raw("Can't migrate a database to itself"));
+						continue;
+					}
+					self.editor.release_datastore();
+					let (config, db_path) = (self.config.clone(), format!("{}:{}", name, path));
+					let target = command.value;
+					let label = format!("Migrating {} to {}", abs_p, target);
+					self.status.run_task_with_progress(&label, move |progress| {
+						let track = |done: &MigrateProgress| {
+							let keyspace = &done.checkpoint.keyspace;
+							let text = format!("{} migrated, now in {}", done.migrated, keyspace);
+							*progress.lock().unwrap() = Some(text);
+						};
+						let runtime = tokio::runtime::Builder::new_current_thread().build()?;
+						let source = config.try_open_datastore(&db_path)?;
+						let target = config.try_open_datastore(&target)?;
+						let report = runtime.block_on(source.migrate(
+							&target,
+							&MigrateOptions::default(),
+							track,
+						))?;
+						match report.keyspaces.iter().find(|keyspace| !keyspace.verified()) {
+							Some(keyspace) => {
+								Err(anyhow!("checksums of {} differ", keyspace.keyspace))
+							}
+							None => Ok(()),
+						}
+					});
+				}
+				// The keyspace of the command is compared with another database, or with its
+				// latest changes when the editor is pinned
+				"DIFF" => {
+					let db_path = format!("{}:{}", name, path);
+					match command.value.as_str() {
+						"" => match self.editor.diff_pinned(&db_path, tags.clone()) {
+							Some(diffs) => self.diff.set_diff("pinned", "latest", diffs),
+							None => {
+								let pin = get_key_char(self.config.key_config.pin);
+								let text = format!("Pin the editor with {} to diff changes", pin);
+								self.status.set_text(Span::raw(text));
+							}
+						},
+						other if other == db_path => {
+							self.status.set_text(Span::raw("Can't diff a database with itself"))
+						}
+						other => {
+							// A database which can't be opened, e.g. a mistyped path, isn't diffed
+							let right = match self.config.try_open_datastore(other) {
+								Ok(right) => right,
+								Err(err) => {
+									self.status.set_text(Span::raw(err.to_string()));
+									continue;
+								}
+							};
+							let keyspace = tags.get("column_family").or_else(|| tags.get("tree"));
+							let other_tags = match keyspace {
+								Some(keyspace) => right.keyspace_tags(&keyspace),
+								None => tag!(),
+							};
+							let diffs = match self.editor.open_datastore(&db_path) {
+								Some(left) => left.diff(tags.clone(), &right, other_tags).await,
+								None => match self.config.try_open_datastore(&db_path) {
+									Ok(left) => left.diff(tags.clone(), &right, other_tags).await,
+									Err(err) => {
+										self.status.set_text(Span::raw(err.to_string()));
+										continue;
+									}
+								},
+							};
+							self.diff.set_diff(&abs_p, other, diffs);
+						}
+					}
+					if self.diff.is_open() {
+						self.focus = Focus::Editor;
+					}
+				}
 				_ => {}
 			}
 		}
 		self.command.reset_command();
 	}
 
+	fn suggest_templates(&mut self, name: &str) -> String {
+		let (pairs, _) = self.editor.scan_results();
+		if pairs.is_empty() {
+			return "Scan a keyspace to suggest templates".to_string();
+		}
+		let samples = sample_pairs(pairs);
+		let keys: Vec<_> = samples.iter().map(|(key, _)| key.as_slice()).collect();
+		let values: Vec<_> = samples.iter().map(|(_, value)| value.as_slice()).collect();
+		let (key_name, value_name) = match name {
+			"" => ("SUGGESTED:Key".to_string(), "SUGGESTED:Value".to_string()),
+			name => (format!("{}:Key", name), format!("{}:Value", name)),
+		};
+		let key = suggest_template(&key_name, &keys);
+		let value = suggest_template(&value_name, &values);
+		let mut text = format!(
+			"{} keys: {} | values: {}",
+			samples.len(),
+			describe_template(&key),
+			describe_template(&value)
+		);
+		if !name.is_empty() {
+			text = match self.config.save_templates(&[key.clone(), value.clone()]) {
+				Ok(()) => format!("Saved {} and {}, {}", key_name, value_name, text),
+				Err(err) => format!("Unable to save templates: {}", err),
+			};
+		}
+		self.editor.use_templates(key, value);
+		text
+	}
+
+	fn export_scan(&self, dest: &str) -> Result<usize> {
+		let format = ExportFormat::from_path(dest)
+			.ok_or_else(|| anyhow!("unknown format, use a .jsonl, .csv or .dump file"))?;
+		let (pairs, (key, value)) = self.editor.scan_results();
+		let encoding = |template: Option<LayoutTemplate>| match template {
+			Some(template) => ExportEncoding::Template(template),
+			None => ExportEncoding::Hex,
+		};
+		let mut writer = BufWriter::new(File::create(dest)?);
+		export_pairs(&mut writer, pairs, &format, &encoding(key), &encoding(value))?;
+		Ok(pairs.len())
+	}
+
 	async fn handle_explorer_event(&mut self) {
+		if self.status.is_busy() {
+			return;
+		}
+		// Selecting a database refreshes the editor, a pinned snapshot or tail is released
+		self.editor.release_datastore();
+		self.diff.close();
 		if self.explorer.state().selected().is_some() {
 			let (name, path, abs_p) = self.get_database_info();
-			self.status.set_text(Span::raw(abs_p));
+			// The datastore is released right away, the editor opens its own instance
+			let ds = self.config.open_datastore(&format!("{}:{}", name, path));
+			let keyspaces = ds.keyspaces();
+			self.info.set_options(ds.options());
+			self.stats.set_stats(ds.stats().ok());
+			drop(ds);
+			match keyspaces {
+				Ok(keyspaces) if !keyspaces.is_empty() => {
+					let text = format!("{} [{}]", abs_p, keyspaces.join(", "));
+					self.status.set_text(Span::raw(text));
+				}
+				_ => self.status.set_text(Span::raw(abs_p)),
+			}
 			self.editor.scan_database(tag!(), &name, &path).await;
 		} else {
 			self.status.reset();
+			self.info.reset();
+			self.stats.reset();
 		}
 	}
 
+	pub async fn tick(&mut self) {
+		self.editor.tick().await;
+	}
+
 	pub async fn event(&mut self, key: Key) -> Result<EventState> {
 		match self.focus {
 			Focus::Explorer => {
@@ -124,6 +416,10 @@ impl<'a> DatabaseTabComponent<'a> {
 					self.handle_explorer_event().await;
 					return Ok(EventState::Consumed);
 				}
+				if key == self.config.key_config.stats {
+					self.stats.toggle();
+					return Ok(EventState::Consumed);
+				}
 				Ok(EventState::NotConsumed)
 			}
 			Focus::Command => {
@@ -142,7 +438,22 @@ impl<'a> DatabaseTabComponent<'a> {
 				Ok(EventState::NotConsumed)
 			}
 			Focus::Editor => {
-				if self.editor.event(key).await?.is_consumed() {
+				// The database is locked by the running operation, the editor can't open it
+				let key_config = &self.config.key_config;
+				let opens = [key_config.pin, key_config.tail, key_config.edit];
+				if self.status.is_busy() && !self.editor.is_editing() && opens.contains(&key) {
+					return Ok(EventState::Consumed);
+				}
+				let state = match (self.stats.is_expanded(), self.diff.is_open()) {
+					(true, _) => self.stats.event(key).await?,
+					(false, true) => self.diff.event(key).await?,
+					(false, false) => self.editor.event(key).await?,
+				};
+				if state.is_consumed() {
+					return Ok(EventState::Consumed);
+				}
+				if key == self.config.key_config.stats {
+					self.stats.toggle();
 					return Ok(EventState::Consumed);
 				}
 				if key == Key::Left {
@@ -173,14 +484,21 @@ impl<'a> RenderAbleComponent for DatabaseTabComponent<'a> {
 
 		let left_stack_chunks = Layout::default()
 			.direction(Direction::Vertical)
-			.constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
+			.constraints([
+				Constraint::Percentage(25),
+				Constraint::Percentage(45),
+				Constraint::Percentage(30),
+			])
 			.split(main_chunks[0]);
 
+		// The editor takes the rows left by statistics sized from their content
+		let stats_height = self.stats.height(main_chunks[1].width);
 		let right_stack_chunks = Layout::default()
 			.direction(Direction::Vertical)
 			.constraints([
 				Constraint::Length(3),
-				Constraint::Length(main_chunks[0].height.saturating_sub(6)),
+				Constraint::Length(main_chunks[0].height.saturating_sub(6 + stats_height)),
+				Constraint::Length(stats_height),
 				Constraint::Length(2),
 			])
 			.split(main_chunks[1]);
@@ -195,17 +513,22 @@ impl<'a> RenderAbleComponent for DatabaseTabComponent<'a> {
 			left_stack_chunks[1],
 			focused && matches!(self.focus, Focus::Explorer),
 		)?;
+		self.info.render(f, left_stack_chunks[2], false)?;
 		self.command.render(
 			f,
 			right_stack_chunks[0],
 			focused && matches!(self.focus, Focus::Command),
 		)?;
-		self.editor.render(
-			f,
-			right_stack_chunks[1],
-			focused && matches!(self.focus, Focus::Editor),
-		)?;
-		self.status.render(f, right_stack_chunks[2], false)?;
+		let editor_focused = focused && matches!(self.focus, Focus::Editor);
+		match (self.stats.is_expanded(), self.diff.is_open()) {
+			(true, _) => self.stats.render(f, right_stack_chunks[1], editor_focused)?,
+			(false, true) => self.diff.render(f, right_stack_chunks[1], editor_focused)?,
+			(false, false) => self.editor.render(f, right_stack_chunks[1], editor_focused)?,
+		}
+		if stats_height > 0 {
+			self.stats.render(f, right_stack_chunks[2], false)?;
+		}
+		self.status.render(f, right_stack_chunks[3], false)?;
 
 		Ok(())
 	}
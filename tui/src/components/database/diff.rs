@@ -0,0 +1,265 @@
+use anyhow::Result;
+use db::{Error, KeyDiff};
+use tui::{
+	backend::Backend,
+	layout::{Alignment, Constraint, Direction, Layout, Rect},
+	style::{Color, Modifier, Style},
+	text::{Span, Spans},
+	widgets::{Cell, Paragraph, Row, Table, TableState},
+	Frame,
+};
+
+use crate::{
+	components::{render_container, RenderAbleComponent},
+	config::Config,
+	constants::HIGHLIGHT_COLOR,
+	events::{EventState, Key},
+	ui::StatefulTable,
+};
+
+use super::PreviewComponent;
+
+enum Focus {
+	Table,
+	Container,
+}
+
+/// Differences between two databases or snapshots, listed in place of the editor. The value
+/// of the selected key on each side is decoded with the selected templates.
+pub struct DatabaseDiffComponent<'a> {
+	config: Config,
+	// Names of the compared sides, none while the view is closed
+	sides: Option<(String, String)>,
+	diffs: Vec<KeyDiff>,
+	err: Option<String>,
+	table: StatefulTable,
+	left: PreviewComponent<'a>,
+	right: PreviewComponent<'a>,
+	focus: Focus,
+}
+
+fn status(diff: &KeyDiff) -> (&'static str, Color) {
+	match diff {
+		KeyDiff::Added {
+			..
+		} => ("added", Color::Green),
+		KeyDiff::Removed {
+			..
+		} => ("removed", Color::Red),
+		KeyDiff::Changed {
+			..
+		} => ("changed", Color::Yellow),
+	}
+}
+
+// Values of the key on the left and on the right side
+fn sides(diff: &KeyDiff) -> (Option<&Vec<u8>>, Option<&Vec<u8>>) {
+	match diff {
+		KeyDiff::Added {
+			value,
+			..
+		} => (None, Some(value)),
+		KeyDiff::Removed {
+			value,
+			..
+		} => (Some(value), None),
+		KeyDiff::Changed {
+			left,
+			right,
+			..
+		} => (Some(left), Some(right)),
+	}
+}
+
+fn build_table(diffs: &[KeyDiff]) -> StatefulTable {
+	let format = |value: Option<&Vec<u8>>| value.map(|v| format!("{:?}", v)).unwrap_or_default();
+	let items = diffs.iter().enumerate().map(|(index, diff)| {
+		let (left, right) = sides(diff);
+		vec![
+			index.to_string(),
+			status(diff).0.to_string(),
+			format!("{:?}", diff.key()),
+			format(left),
+			format(right),
+		]
+	});
+	StatefulTable::default()
+		.with_items(items.collect())
+		.with_headers(vec!["#", "Status", "Key", "Left", "Right"])
+		.build()
+}
+
+impl DatabaseDiffComponent<'_> {
+	pub fn new(config: Config) -> Self {
+		DatabaseDiffComponent {
+			left: PreviewComponent::new(config.clone()).with_side("Left"),
+			right: PreviewComponent::new(config.clone()).with_side("Right"),
+			sides: None,
+			diffs: vec![],
+			err: None,
+			table: StatefulTable::default(),
+			focus: Focus::Container,
+			config,
+		}
+	}
+
+	pub fn is_open(&self) -> bool {
+		self.sides.is_some()
+	}
+
+	pub fn set_diff(&mut self, left: &str, right: &str, diffs: Result<Vec<KeyDiff>, Error>) {
+		self.sides = Some((left.to_string(), right.to_string()));
+		match diffs {
+			Ok(diffs) => {
+				self.err = None;
+				self.diffs = diffs;
+			}
+			Err(err) => {
+				self.err = Some(err.to_string());
+				self.diffs = vec![];
+			}
+		}
+		self.table = build_table(&self.diffs);
+		self.focus = Focus::Container;
+		self.update_preview();
+	}
+
+	pub fn close(&mut self) {
+		self.sides = None;
+		self.diffs = vec![];
+		self.table = StatefulTable::default();
+		self.update_preview();
+	}
+
+	fn update_preview(&mut self) {
+		let selected = self.table.state.selected().and_then(|index| self.diffs.get(index));
+		let (left, right) = match selected {
+			Some(diff) => {
+				let (left, right) = sides(diff);
+				let pair =
+					|value: Option<&Vec<u8>>| value.map(|v| (diff.key().to_vec(), v.to_vec()));
+				(pair(left), pair(right))
+			}
+			None => (None, None),
+		};
+		self.left.set_pair(left);
+		self.right.set_pair(right);
+	}
+
+	pub async fn event(&mut self, key: Key) -> Result<EventState> {
+		if self.left.event(key).await?.is_consumed() {
+			self.right.event(key).await?;
+			return Ok(EventState::Consumed);
+		}
+
+		match self.focus {
+			Focus::Container => match key {
+				k if k == self.config.key_config.enter && !self.diffs.is_empty() => {
+					self.focus = Focus::Table;
+					self.table.next();
+				}
+				k if k == self.config.key_config.escape => self.close(),
+				_ => return Ok(EventState::NotConsumed),
+			},
+			Focus::Table => match key {
+				k if k == self.config.key_config.escape => {
+					self.table.state = TableState::default();
+					self.focus = Focus::Container;
+				}
+				k if k == self.config.key_config.up => self.table.previous(),
+				k if k == self.config.key_config.down => self.table.next(),
+				_ => return Ok(EventState::NotConsumed),
+			},
+		}
+		self.update_preview();
+		Ok(EventState::Consumed)
+	}
+
+	fn render_preview<B: Backend>(
+		&self,
+		f: &mut Frame<B>,
+		rect: Rect,
+		preview: &PreviewComponent,
+		side: &str,
+	) -> Result<()> {
+		if preview.pair().is_some() {
+			return preview.render(f, rect, false);
+		}
+		let text = format!("Key not found on the {} side", side.to_lowercase());
+		let missing = Paragraph::new(Span::raw(text))
+			.alignment(Alignment::Center)
+			.block(render_container(side, false));
+		f.render_widget(missing, rect);
+		Ok(())
+	}
+}
+
+impl RenderAbleComponent for DatabaseDiffComponent<'_> {
+	fn render<B: Backend>(
+		&self,
+		f: &mut Frame<B>,
+		rect: Rect,
+		focused: bool,
+	) -> Result<(), anyhow::Error> {
+		let (left, right) = self.sides.clone().unwrap_or_default();
+		if self.diffs.is_empty() {
+			let title = format!("Diff {} / {} [ESC]", left, right);
+			let text = self.err.clone().unwrap_or_else(|| "No differences found".to_string());
+			let empty = Paragraph::new(vec![
+				Spans::from(vec![Span::raw("")]),
+				Spans::from(vec![Span::raw(text)]),
+			])
+			.alignment(Alignment::Center)
+			.block(render_container(&title, focused));
+			f.render_widget(empty, rect);
+			return Ok(());
+		}
+
+		let mut chunks = Layout::default()
+			.direction(Direction::Vertical)
+			.constraints([Constraint::Percentage(100)])
+			.split(rect);
+		if self.table.state.selected().is_some() {
+			chunks = Layout::default()
+				.direction(Direction::Vertical)
+				.constraints([
+					Constraint::Percentage(50),
+					Constraint::Percentage(25),
+					Constraint::Percentage(25),
+				])
+				.split(rect);
+			self.render_preview(f, chunks[1], &self.left, "Left")?;
+			self.render_preview(f, chunks[2], &self.right, "Right")?;
+		}
+
+		let header_cells = self
+			.table
+			.headers
+			.iter()
+			.map(|h| Cell::from(h.as_str()).style(Style::default().fg(Color::Black)));
+		let header =
+			Row::new(header_cells).style(Style::default().bg(Color::DarkGray)).bottom_margin(1);
+		let rows = self.table.items.iter().zip(self.diffs.iter()).map(|(item, diff)| {
+			let cells = item.iter().map(|c| Cell::from(c.clone()));
+			Row::new(cells).style(Style::default().fg(status(diff).1))
+		});
+
+		let label = format!("Diff {} / {} ({} differences)", left, right, self.diffs.len());
+		let table = Table::new(rows)
+			.header(header)
+			.block(render_container(&label, focused))
+			.highlight_style(
+				Style::default().bg(HIGHLIGHT_COLOR).fg(Color::Black).add_modifier(Modifier::BOLD),
+			)
+			.widths(&[
+				Constraint::Percentage(5),
+				Constraint::Percentage(10),
+				Constraint::Percentage(25),
+				Constraint::Percentage(30),
+				Constraint::Percentage(30),
+			]);
+		f.render_stateful_widget(table, chunks[0], &mut self.table.state.clone());
+
+		Ok(())
+	}
+}
@@ -1,20 +1,30 @@
+use std::{
+	collections::HashMap,
+	time::{Duration, Instant},
+};
+
 use anyhow::Result;
-use db::{Datastore, KeyValuePair, SimpleTransaction, TagBucket};
+use db::{
+	Datastore, Error, KeyDiff, KeyValuePair, SimpleTransaction, Snapshot, Subscription, TagBucket,
+	WatchEvent,
+};
 use tui::{
 	backend::Backend,
 	layout::{Alignment, Constraint, Direction, Layout, Rect},
 	style::{Color, Modifier, Style},
 	text::{Span, Spans},
-	widgets::{Cell, Paragraph, Row, Table, TableState},
+	widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState},
 	Frame,
 };
+use tui_textarea::{Input, Key as InputKey, TextArea};
 
 use crate::{
 	components::{render_container, RenderAbleComponent},
 	config::Config,
-	constants::HIGHLIGHT_COLOR,
+	constants::{BORDER_TYPE, HIGHLIGHT_COLOR},
 	events::{EventState, Key},
 	ui::StatefulTable,
+	utils::{decode_hex, encode_hex, LayoutTemplate},
 };
 
 use super::PreviewComponent;
@@ -22,6 +32,7 @@ use super::PreviewComponent;
 enum Focus {
 	Table,
 	Container,
+	Edit,
 }
 
 pub struct DatabaseEditorComponent<'a> {
@@ -30,21 +41,160 @@ pub struct DatabaseEditorComponent<'a> {
 	table: StatefulTable,
 	err: Option<String>,
 	pairs: Vec<KeyValuePair>,
+	groups: Vec<KeyValueGroup>,
+	// Tags and datastore path of the last scan, used to write edited values back
+	source: Option<(TagBucket, String)>,
+	edit: Vec<char>,
+	// Values which aren't UTF-8 text are edited as hex
+	edit_hex: bool,
+	edit_err: Option<String>,
+	pinned: Option<PinnedSnapshot>,
+	// Keys of the last scan, a tail follows them
+	keys: ScanKeys,
+	// Columns of the table showing the fields of keys, the others show the fields of values
+	key_columns: usize,
+	tail: Option<Tail>,
 	focus: Focus,
 }
 
-fn build_table(pairs: Vec<KeyValuePair>) -> StatefulTable {
+/// Snapshot every scan of a datastore reads from until it is unpinned. The datastore stays
+/// open meanwhile, values are edited through it.
+struct PinnedSnapshot {
+	path: String,
+	snapshot: Snapshot,
+	ds: Datastore,
+}
+
+// Changed rows stay highlighted for a while, datastores without push notifications are
+// scanned again at most once per interval
+const TAIL_HIGHLIGHT: Duration = Duration::from_secs(5);
+const TAIL_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+enum RowChange {
+	Inserted,
+	Updated,
+}
+
+/// Subscription the rows of the editor follow while tailing. The datastore is opened again
+/// for each poll, so other writers can open it in between.
+struct Tail {
+	path: String,
+	subscription: Subscription,
+	polled: Instant,
+	changes: HashMap<Vec<u8>, (RowChange, Instant)>,
+	// Error opening the datastore for the last poll, e.g. locked by another writer
+	err: Option<String>,
+}
+
+/// Keys read by a scan: keys with a prefix and a suffix, from `start` up to `end` which is
+/// excluded
+#[derive(Clone, Default)]
+struct ScanKeys {
+	prefix: Vec<u8>,
+	suffix: Vec<u8>,
+	start: Vec<u8>,
+	end: Option<Vec<u8>>,
+}
+
+impl ScanKeys {
+	fn contains(&self, key: &[u8]) -> bool {
+		let before_end = match &self.end {
+			Some(end) => key < end.as_slice(),
+			None => true,
+		};
+		let after_start = key >= self.start.as_slice();
+		key.starts_with(&self.prefix) && key.ends_with(&self.suffix) && after_start && before_end
+	}
+}
+
+type KeyValueGroup = (Vec<u8>, Vec<Vec<u8>>);
+
+// Rows of a tail which are keys of the scan it follows
+fn tailed_rows(keys: &ScanKeys, subscription: &Subscription) -> Vec<KeyValuePair> {
+	let rows = subscription.rows().filter(|(k, _)| keys.contains(k));
+	rows.map(|(k, v)| (k.to_vec(), v.to_vec())).collect()
+}
+
+/// Group values of consecutive pairs sharing the same key, multimap tables return a pair
+/// for each value of a key
+fn group_pairs(pairs: &[KeyValuePair]) -> Vec<KeyValueGroup> {
+	let mut groups: Vec<KeyValueGroup> = vec![];
+	for (key, value) in pairs.iter() {
+		match groups.last_mut() {
+			Some((last, values)) if last == key => values.push(value.to_vec()),
+			_ => groups.push((key.to_vec(), vec![value.to_vec()])),
+		}
+	}
+	groups
+}
+
+// Templates bound to the keyspace decoding the key and the values of a row
+type BoundTemplates<'a> = (Option<&'a LayoutTemplate>, Option<&'a LayoutTemplate>);
+
+// Fields decoded by the template, or the bytes as a single field
+fn decode_fields(
+	template: Option<&LayoutTemplate>,
+	raw: &[u8],
+	name: &str,
+) -> Vec<(String, String)> {
+	match template {
+		Some(template) => template.decode(raw),
+		None => vec![(name.to_string(), format!("{:?}", raw))],
+	}
+}
+
+// Cells of the columns, columns missing from the fields are left empty
+fn field_cells(columns: &[String], fields: &[Vec<(String, String)>]) -> Vec<String> {
+	let cell = |column: &String| {
+		let values = fields.iter().map(|fields| {
+			let field = fields.iter().find(|(name, _)| name == column);
+			field.map(|(_, value)| value.as_str()).unwrap_or_default()
+		});
+		values.collect::<Vec<_>>().join("\n")
+	};
+	columns.iter().map(cell).collect()
+}
+
+/// Rows of the groups with a column for each field of the bound templates, keys and values
+/// without templates are shown as bytes in the `Key` and `Value` columns. Returns the table
+/// and the number of key columns.
+fn build_table(groups: &[KeyValueGroup], templates: &[BoundTemplates]) -> (StatefulTable, usize) {
+	let (mut key_columns, mut value_columns) = (vec![], vec![]);
+	let mut rows = vec![];
+	for ((key, values), (key_template, value_template)) in groups.iter().zip(templates) {
+		let key_fields = decode_fields(*key_template, key, "Key");
+		let value_fields: Vec<_> =
+			values.iter().map(|value| decode_fields(*value_template, value, "Value")).collect();
+		for (name, _) in key_fields.iter() {
+			if !key_columns.contains(name) {
+				key_columns.push(name.clone());
+			}
+		}
+		for (name, _) in value_fields.iter().flatten() {
+			if !value_columns.contains(name) {
+				value_columns.push(name.clone());
+			}
+		}
+		rows.push((key_fields, value_fields));
+	}
+	if key_columns.is_empty() {
+		key_columns.push("Key".to_string());
+	}
+	if value_columns.is_empty() {
+		value_columns.push("Value".to_string());
+	}
 	let mut items = vec![];
-	for (index, (key, value)) in pairs.iter().enumerate() {
+	for (index, (key_fields, value_fields)) in rows.iter().enumerate() {
 		let index = format!("{:?}", index);
-		let key = format!("{:?}", key.to_vec());
-		let value = format!("{:?}", value.to_vec());
-		items.push(vec![index, key, value])
-	}
-	StatefulTable::default()
-		.with_items(items.to_vec())
-		.with_headers(vec!["#", "Key", "Value"])
-		.build()
+		let count = value_fields.len().to_string();
+		let keys = field_cells(&key_columns, &[key_fields.to_vec()]);
+		let values = field_cells(&value_columns, value_fields);
+		items.push([vec![index], keys, vec![count], values].concat());
+	}
+	let headers =
+		[vec!["#".to_string()], key_columns.to_vec(), vec!["Count".to_string()], value_columns];
+	let table = StatefulTable::default().with_items(items).with_headers(headers.concat()).build();
+	(table, key_columns.len())
 }
 
 impl DatabaseEditorComponent<'_> {
@@ -55,9 +205,14 @@ impl DatabaseEditorComponent<'_> {
 		prefix: Vec<u8>,
 	) -> Vec<KeyValuePair> {
 		let mut result = vec![];
-		let ds = Datastore::new(path);
-		let tx = ds.transaction(false).await.unwrap();
-		let data = tx.suffix_iterate(prefix, tags).await;
+		let data = match self.pinned_snapshot(path) {
+			Some(snapshot) => snapshot.suffix_iterate(prefix, tags),
+			None => {
+				let ds = self.config.open_datastore(path);
+				let tx = ds.transaction(false).await.unwrap();
+				tx.suffix_iterate(prefix, tags).await
+			}
+		};
 		self.clear_err();
 		match data {
 			Ok(pairs) => {
@@ -79,9 +234,52 @@ impl DatabaseEditorComponent<'_> {
 		prefix: Vec<u8>,
 	) -> Vec<KeyValuePair> {
 		let mut result = vec![];
-		let ds = Datastore::new(path);
-		let tx = ds.transaction(false).await.unwrap();
-		let data = tx.prefix_iterate(prefix, tags).await;
+		let data = match self.pinned_snapshot(path) {
+			Some(snapshot) => snapshot.prefix_iterate(prefix, tags),
+			None => {
+				let ds = self.config.open_datastore(path);
+				let tx = ds.transaction(false).await.unwrap();
+				tx.prefix_iterate(prefix, tags).await
+			}
+		};
+		self.clear_err();
+		match data {
+			Ok(pairs) => {
+				for pair in pairs {
+					result.push(pair.unwrap());
+				}
+			}
+			Err(err) => {
+				self.set_err(err.to_string());
+			}
+		}
+		result
+	}
+
+	// Keys from `start` up to `end`, which is excluded, as long as they keep the prefix
+	async fn range_scan_from_path(
+		&mut self,
+		tags: TagBucket,
+		path: &str,
+		keys: &ScanKeys,
+	) -> Vec<KeyValuePair> {
+		let mut result = vec![];
+		let in_range = |key: &[u8]| keys.contains(key);
+		let data = match self.pinned_snapshot(path) {
+			Some(snapshot) => snapshot.iterate(tags).map(|pairs| {
+				let matches = |pair: &Result<KeyValuePair, Error>| match pair {
+					Ok((k, _)) => in_range(k),
+					Err(_) => true,
+				};
+				pairs.into_iter().filter(matches).collect()
+			}),
+			None => {
+				let ds = self.config.open_datastore(path);
+				// Keys are in order, the range is read a page at a time until a key leaves it
+				let pairs = ds.range_while(&keys.start, tags, in_range).await;
+				pairs.map(|pairs| pairs.into_iter().map(Ok).collect::<Vec<_>>())
+			}
+		};
 		self.clear_err();
 		match data {
 			Ok(pairs) => {
@@ -98,9 +296,14 @@ impl DatabaseEditorComponent<'_> {
 
 	async fn scan_from_path(&mut self, tags: TagBucket, path: &str) -> Vec<KeyValuePair> {
 		let mut result = vec![];
-		let ds = Datastore::new(path);
-		let tx = ds.transaction(false).await.unwrap();
-		let data = tx.iterate(tags).await;
+		let data = match self.pinned_snapshot(path) {
+			Some(snapshot) => snapshot.iterate(tags),
+			None => {
+				let ds = self.config.open_datastore(path);
+				let tx = ds.transaction(false).await.unwrap();
+				tx.iterate(tags).await
+			}
+		};
 		self.clear_err();
 		match data {
 			Ok(pairs) => {
@@ -124,9 +327,38 @@ impl DatabaseEditorComponent<'_> {
 		prefix: Vec<u8>,
 	) {
 		let db_path = format!("{}:{}", name, path);
+		self.source = Some((tags.clone(), db_path.clone()));
+		self.tail = None;
+		self.keys = ScanKeys {
+			prefix: prefix.to_vec(),
+			..Default::default()
+		};
 		let pairs = self.prefix_scan_from_path(tags, &db_path, prefix).await;
-		self.table = build_table(pairs.to_vec());
-		self.pairs = pairs;
+		self.set_pairs(pairs);
+	}
+
+	/// Scan keys from `start` up to `end` which keep the prefix
+	pub async fn range_scan_database(
+		&mut self,
+		tags: TagBucket,
+		name: &str,
+		path: &str,
+		prefix: Vec<u8>,
+		start: Vec<u8>,
+		end: Option<Vec<u8>>,
+	) {
+		let db_path = format!("{}:{}", name, path);
+		self.source = Some((tags.clone(), db_path.clone()));
+		self.tail = None;
+		let keys = ScanKeys {
+			prefix,
+			start,
+			end,
+			..Default::default()
+		};
+		let pairs = self.range_scan_from_path(tags, &db_path, &keys).await;
+		self.keys = keys;
+		self.set_pairs(pairs);
 	}
 
 	pub async fn suffix_scan_database(
@@ -137,30 +369,147 @@ impl DatabaseEditorComponent<'_> {
 		suffix: Vec<u8>,
 	) {
 		let db_path = format!("{}:{}", name, path);
+		self.source = Some((tags.clone(), db_path.clone()));
+		self.tail = None;
+		self.keys = ScanKeys {
+			suffix: suffix.to_vec(),
+			..Default::default()
+		};
 		let pairs = self.suffix_scan_from_path(tags, &db_path, suffix).await;
-		self.table = build_table(pairs.to_vec());
-		self.pairs = pairs;
+		self.set_pairs(pairs);
 	}
 
 	pub async fn scan_database(&mut self, tags: TagBucket, name: &str, path: &str) {
 		let db_path = format!("{}:{}", name, path);
+		self.source = Some((tags.clone(), db_path.clone()));
+		self.tail = None;
+		self.keys = ScanKeys::default();
 		let pairs = self.scan_from_path(tags, &db_path).await;
-		self.table = build_table(pairs.to_vec());
+		self.set_pairs(pairs);
+	}
+
+	// Snapshot pinned for the datastore, a snapshot of another datastore is released
+	fn pinned_snapshot(&mut self, path: &str) -> Option<&Snapshot> {
+		if self.pinned.as_ref().filter(|pinned| pinned.path == path).is_none() {
+			self.pinned = None;
+		}
+		self.pinned.as_ref().map(|pinned| &pinned.snapshot)
+	}
+
+	/// Datastore kept open by the pinned snapshot, a datastore can't be opened again while it
+	/// is open
+	pub fn open_datastore(&self, path: &str) -> Option<&Datastore> {
+		let pinned = self.pinned.as_ref().filter(|pinned| pinned.path == path);
+		pinned.map(|pinned| &pinned.ds)
+	}
+
+	/// Compare the pinned snapshot of the datastore with its latest changes
+	pub fn diff_pinned(&self, path: &str, tags: TagBucket) -> Option<Result<Vec<KeyDiff>, Error>> {
+		let pinned = self.pinned.as_ref().filter(|pinned| pinned.path == path)?;
+		let latest = match pinned.ds.snapshot() {
+			Ok(latest) => latest,
+			Err(err) => return Some(Err(err)),
+		};
+		Some(pinned.snapshot.diff(tags.clone(), &latest, tags))
+	}
+
+	/// Unpin the snapshot and stop tailing so the datastore is closed
+	pub fn release_datastore(&mut self) {
+		self.pinned = None;
+		self.tail = None;
+	}
+
+	fn set_pairs(&mut self, pairs: Vec<KeyValuePair>) {
+		let groups = group_pairs(&pairs);
+		let templates: Vec<_> = groups.iter().map(|(key, _)| self.bound_templates(key)).collect();
+		(self.table, self.key_columns) = build_table(&groups, &templates);
+		self.groups = groups;
 		self.pairs = pairs;
 	}
 
+	// Templates bound to the keyspace of the last scan decoding a key and its values
+	fn bound_templates(&self, key: &[u8]) -> BoundTemplates<'_> {
+		let Some((tags, db_path)) = &self.source else {
+			return (None, None);
+		};
+		let keyspace = tags.get("column_family").or_else(|| tags.get("tree"));
+		let binding = self.config.binding(db_path, keyspace.as_deref(), key);
+		let template = |name: &Option<String>| name.as_ref().and_then(|n| self.config.template(n));
+		match binding {
+			Some(binding) => (template(&binding.key_template), template(&binding.value_template)),
+			None => (None, None),
+		}
+	}
+
+	// Update the value of a key without losing the selected row
+	fn replace_value(&mut self, key: &[u8], value: Vec<u8>) {
+		let state = self.table.state.clone();
+		let mut pairs = self.pairs.to_vec();
+		for (_, v) in pairs.iter_mut().filter(|(k, _)| k == key) {
+			*v = value.to_vec();
+		}
+		self.set_pairs(pairs);
+		self.table.state = state;
+	}
+
+	/// Pairs of the last scan and the templates selected to decode them
+	pub fn scan_results(
+		&self,
+	) -> (&[KeyValuePair], (Option<LayoutTemplate>, Option<LayoutTemplate>)) {
+		(&self.pairs, self.preview.selected_templates())
+	}
+
+	/// Decode keys and values with new templates, replacing templates of the same name
+	pub fn use_templates(&mut self, key: LayoutTemplate, value: LayoutTemplate) {
+		self.preview.use_templates(key, value);
+	}
+
+	/// A value is being edited, every key goes to it
+	pub fn is_editing(&self) -> bool {
+		matches!(self.focus, Focus::Edit)
+	}
+
 	fn pairs_empty(&self) -> bool {
 		self.pairs.is_empty()
 	}
 
 	fn generate_label(&self) -> String {
-		format!("Editor ({} key-value pairs)", self.pairs.len())
+		let pinned = match (&self.pinned, &self.tail) {
+			(Some(_), _) => " [pinned]".to_string(),
+			(
+				_,
+				Some(Tail {
+					err: Some(err),
+					..
+				}),
+			) => format!(" [tailing, {}]", err),
+			(_, Some(_)) => " [tailing]".to_string(),
+			_ => "".to_string(),
+		};
+		if self.groups.len() == self.pairs.len() {
+			return format!("Editor ({} key-value pairs){}", self.pairs.len(), pinned);
+		}
+		format!(
+			"Editor ({} keys, {} key-value pairs){}",
+			self.groups.len(),
+			self.pairs.len(),
+			pinned
+		)
 	}
 
 	pub fn new(config: Config) -> Self {
 		DatabaseEditorComponent {
 			preview: PreviewComponent::new(config.clone()),
 			pairs: vec![],
+			groups: vec![],
+			source: None,
+			edit: vec![],
+			edit_hex: false,
+			edit_err: None,
+			pinned: None,
+			keys: ScanKeys::default(),
+			key_columns: 1,
+			tail: None,
 			table: StatefulTable::default(),
 			focus: Focus::Container,
 			err: None,
@@ -179,7 +528,13 @@ impl DatabaseEditorComponent<'_> {
 	fn update_preview(&mut self) {
 		match self.table.state.selected() {
 			Some(selected) if !self.pairs_empty() => {
-				let pair = Some(self.pairs[selected].clone());
+				// Grouped keys are previewed with their first value
+				let (key, values) = &self.groups[selected];
+				let pair = Some((key.to_vec(), values[0].to_vec()));
+				let (key_template, value_template) = self.bound_templates(key);
+				let names =
+					(key_template.map(|t| t.name.clone()), value_template.map(|t| t.name.clone()));
+				self.preview.select_bound_templates(names);
 				self.preview.set_pair(pair)
 			}
 			_ => self.preview.set_pair(None),
@@ -213,7 +568,226 @@ impl DatabaseEditorComponent<'_> {
 		Ok(EventState::Consumed)
 	}
 
+	fn handle_edit(&mut self) -> Result<EventState> {
+		match self.table.state.selected() {
+			// Keys holding many values can't be swapped as a whole
+			Some(selected) if self.groups[selected].1.len() == 1 => {
+				let value = self.groups[selected].1[0].to_vec();
+				self.set_edit(&value);
+				self.edit_err = None;
+				self.focus = Focus::Edit;
+			}
+			_ => {}
+		}
+		Ok(EventState::Consumed)
+	}
+
+	/// Pin scans to a snapshot of the datastore of the last scan, or unpin them so the next
+	/// scan reads the latest changes
+	fn handle_pin(&mut self) -> Result<EventState> {
+		self.tail = None;
+		if let (None, Some((_, path))) = (self.pinned.take(), &self.source) {
+			let pinned = self.config.try_open_datastore(path).and_then(|ds| {
+				Ok(PinnedSnapshot {
+					path: path.clone(),
+					snapshot: ds.snapshot()?,
+					ds,
+				})
+			});
+			match pinned {
+				Ok(pinned) => self.pinned = Some(pinned),
+				Err(err) => self.set_err(err.to_string()),
+			}
+		}
+		Ok(EventState::Consumed)
+	}
+
+	/// Follow changes of the keys of the last scan, or stop following them
+	async fn handle_tail(&mut self) -> Result<EventState> {
+		self.pinned = None;
+		if let (None, Some((tags, path))) = (self.tail.take(), self.source.clone()) {
+			let subscription = match self.config.try_open_datastore(&path) {
+				Ok(ds) => ds.poll_watch(self.keys.prefix.to_vec(), tags).await,
+				Err(err) => Err(err),
+			};
+			match subscription {
+				Ok(subscription) => {
+					self.set_pairs(tailed_rows(&self.keys, &subscription));
+					self.tail = Some(Tail {
+						path,
+						subscription,
+						polled: Instant::now(),
+						changes: HashMap::new(),
+						err: None,
+					});
+				}
+				Err(err) => self.set_err(err.to_string()),
+			}
+		}
+		self.update_preview();
+		Ok(EventState::Consumed)
+	}
+
+	/// Apply the changes of the tailed keys, called on every tick of the event loop
+	pub async fn tick(&mut self) {
+		let Some(tail) = self.tail.as_mut() else {
+			return;
+		};
+		tail.changes.retain(|_, (_, at)| at.elapsed() < TAIL_HIGHLIGHT);
+		if tail.polled.elapsed() < TAIL_POLL_INTERVAL {
+			return;
+		}
+		tail.polled = Instant::now();
+
+		// A poll is skipped while the datastore can't be opened, e.g. locked by another writer
+		let ds = match self.config.try_open_datastore(&tail.path) {
+			Ok(ds) => ds,
+			Err(err) => {
+				tail.err = Some(err.to_string());
+				return;
+			}
+		};
+		tail.err = None;
+		let events = match tail.subscription.poll(&ds).await {
+			Ok(events) => events,
+			Err(err) => {
+				self.tail = None;
+				return self.set_err(err.to_string());
+			}
+		};
+		drop(ds);
+		let events: Vec<_> =
+			events.iter().filter(|event| self.keys.contains(event.key())).collect();
+		if events.is_empty() {
+			return;
+		}
+		for event in events {
+			let change = match event {
+				WatchEvent::Insert {
+					..
+				} => RowChange::Inserted,
+				WatchEvent::Update {
+					..
+				} => RowChange::Updated,
+				WatchEvent::Delete {
+					key,
+					..
+				} => {
+					tail.changes.remove(key);
+					continue;
+				}
+			};
+			tail.changes.insert(event.key().to_vec(), (change, Instant::now()));
+		}
+
+		// The selected row stays selected while rows are added and removed
+		let pairs = tailed_rows(&self.keys, &tail.subscription);
+		let selected = self.table.state.selected().map(|index| self.groups[index].0.to_vec());
+		self.set_pairs(pairs);
+		if let Some(key) = selected {
+			let index = self.groups.iter().position(|(k, _)| *k == key);
+			self.table.state.select(index.or_else(|| self.groups.len().checked_sub(1)));
+		}
+		self.update_preview();
+	}
+
+	// Edit a value as text, or as hex when it isn't UTF-8 text
+	fn set_edit(&mut self, value: &[u8]) {
+		(self.edit, self.edit_hex) = match std::str::from_utf8(value) {
+			Ok(text) => (text.chars().collect(), false),
+			Err(_) => (encode_hex(value).chars().collect(), true),
+		};
+	}
+
+	/// Write the edited value with a compare and swap, so changes made by other writers since
+	/// the last scan are never overwritten
+	async fn submit_edit(&mut self) -> Result<EventState> {
+		let (Some(selected), Some((tags, path))) =
+			(self.table.state.selected(), self.source.clone())
+		else {
+			return Ok(EventState::Consumed);
+		};
+		let (key, values) = self.groups[selected].clone();
+		let edit = self.edit.iter().collect::<String>();
+		let new = match self.edit_hex {
+			true => match decode_hex(&edit) {
+				Ok(new) => new,
+				Err(err) => {
+					self.edit_err = Some(err.to_string());
+					return Ok(EventState::Consumed);
+				}
+			},
+			false => edit.into_bytes(),
+		};
+
+		match self.swap_value(&path, tags, &key, &values[0], &new).await {
+			Ok(None) => {
+				self.replace_value(&key, new);
+				self.focus = Focus::Table;
+			}
+			Ok(Some(current)) => {
+				self.set_edit(&current);
+				self.replace_value(&key, current);
+				self.edit_err = Some("Value was changed by another writer, reloaded".to_string());
+			}
+			Err(err) => self.edit_err = Some(err.to_string()),
+		}
+		self.update_preview();
+		Ok(EventState::Consumed)
+	}
+
+	// Swap the value of a key, the value written by another writer since the last scan is
+	// returned instead. A pinned or tailed datastore is already open, the value is written
+	// through it.
+	async fn swap_value(
+		&self,
+		path: &str,
+		tags: TagBucket,
+		key: &[u8],
+		old: &[u8],
+		new: &[u8],
+	) -> Result<Option<Vec<u8>>, Error> {
+		let opened;
+		let ds = match self.open_datastore(path) {
+			Some(ds) => ds,
+			None => {
+				opened = self.config.try_open_datastore(path)?;
+				&opened
+			}
+		};
+		let mut tx = ds.transaction(true).await?;
+		match tx.cas(key.to_vec(), Some(old.to_vec()), new.to_vec(), tags.clone()).await {
+			Ok(_) => {
+				tx.commit().await?;
+				Ok(None)
+			}
+			Err(Error::TxConditionNotMet) => {
+				tx.cancel().await?;
+				let tx = ds.transaction(false).await?;
+				Ok(Some(tx.get(key.to_vec(), tags).await?.unwrap_or_default()))
+			}
+			Err(err) => {
+				tx.cancel().await?;
+				Err(err)
+			}
+		}
+	}
+
 	pub async fn event(&mut self, key: Key) -> Result<EventState> {
+		// Every key goes to the edited value until it is saved or discarded
+		if let Focus::Edit = self.focus {
+			match key {
+				k if k == self.config.key_config.escape => self.focus = Focus::Table,
+				k if k == self.config.key_config.enter => return self.submit_edit().await,
+				k if k == self.config.key_config.backspace => {
+					self.edit.pop();
+				}
+				Key::Char(c) => self.edit.push(c),
+				_ => {}
+			}
+			return Ok(EventState::Consumed);
+		}
+
 		if self.preview.event(key).await?.is_consumed() {
 			return Ok(EventState::Consumed);
 		}
@@ -224,10 +798,20 @@ impl DatabaseEditorComponent<'_> {
 					self.focus = Focus::Table;
 					return self.handle_next();
 				}
+				if key == self.config.key_config.pin {
+					return self.handle_pin();
+				}
+				if key == self.config.key_config.tail {
+					return self.handle_tail().await;
+				}
 			}
+			Focus::Edit => {}
 			Focus::Table => match key {
 				k if k == self.config.key_config.enter => return self.handle_enter(),
 				k if k == self.config.key_config.escape => return self.handle_escape(),
+				k if k == self.config.key_config.edit => return self.handle_edit(),
+				k if k == self.config.key_config.pin => return self.handle_pin(),
+				k if k == self.config.key_config.tail => return self.handle_tail().await,
 				_ if key == self.config.key_config.up && matches!(self.focus, Focus::Table) => {
 					return self.handle_prev()
 				}
@@ -240,6 +824,50 @@ impl DatabaseEditorComponent<'_> {
 	}
 }
 
+impl DatabaseEditorComponent<'_> {
+	// Rows changed while tailing are highlighted until the change is old
+	fn row_style(&self, key: &[u8]) -> Style {
+		let change = self.tail.as_ref().and_then(|tail| tail.changes.get(key));
+		match change {
+			Some((RowChange::Inserted, _)) => Style::default().fg(Color::Green),
+			Some((RowChange::Updated, _)) => Style::default().fg(Color::Yellow),
+			None => Style::default(),
+		}
+	}
+
+	fn render_edit<B: Backend>(&self, f: &mut Frame<B>, rect: Rect) {
+		let style = Style::default().bg(HIGHLIGHT_COLOR).add_modifier(Modifier::BOLD);
+		let mut textarea = TextArea::default();
+		textarea.set_cursor_style(style);
+
+		let container = match &self.edit_err {
+			Some(err) => {
+				textarea.set_style(Style::default().fg(Color::Red));
+				Block::default()
+					.borders(Borders::ALL)
+					.style(Style::default().fg(Color::Red))
+					.title(format!("{} [ESC]", err))
+					.border_type(BORDER_TYPE)
+			}
+			None => match self.edit_hex {
+				true => render_container("Edit value as hex [ENTER to save, ESC to cancel]", true),
+				false => render_container("Edit value [ENTER to save, ESC to cancel]", true),
+			},
+		};
+		textarea.set_block(container);
+
+		for c in self.edit.iter() {
+			textarea.input(Input {
+				key: InputKey::Char(*c),
+				ctrl: false,
+				alt: false,
+			});
+		}
+
+		f.render_widget(textarea.widget(), rect);
+	}
+}
+
 impl RenderAbleComponent for DatabaseEditorComponent<'_> {
 	fn render<B: Backend>(
 		&self,
@@ -265,20 +893,34 @@ impl RenderAbleComponent for DatabaseEditorComponent<'_> {
 				.table
 				.headers
 				.iter()
-				.map(|h| Cell::from(*h).style(Style::default().fg(Color::Black)));
+				.map(|h| Cell::from(h.as_str()).style(Style::default().fg(Color::Black)));
 			let normal_style = Style::default().bg(Color::DarkGray);
 			let header = Row::new(header_cells).style(normal_style).height(1).bottom_margin(1);
 
-			let rows = self.table.items.iter().map(|item| {
+			let rows = self.table.items.iter().enumerate().map(|(index, item)| {
 				let height = item
 					.iter()
 					.map(|content| content.chars().filter(|c| *c == '\n').count())
 					.max()
 					.unwrap_or(0) + 1;
 				let cells = item.iter().map(|c| Cell::from(c.clone()));
-				Row::new(cells).height(height as u16).bottom_margin(1)
+				Row::new(cells)
+					.height(height as u16)
+					.bottom_margin(1)
+					.style(self.row_style(&self.groups[index].0))
 			});
 
+			// Fields of keys share the width of the key column, fields of values the rest
+			let value_columns = self.table.headers.len().saturating_sub(self.key_columns + 2);
+			let key_width = Constraint::Percentage(30 / self.key_columns.max(1) as u16);
+			let value_width = Constraint::Percentage(60 / value_columns.max(1) as u16);
+			let widths = [
+				vec![Constraint::Percentage(5)],
+				vec![key_width; self.key_columns],
+				vec![Constraint::Percentage(5)],
+				vec![value_width; value_columns],
+			]
+			.concat();
 			let label = self.generate_label();
 			let table = Table::new(rows)
 				.header(header)
@@ -289,12 +931,18 @@ impl RenderAbleComponent for DatabaseEditorComponent<'_> {
 						.fg(Color::Black)
 						.add_modifier(Modifier::BOLD),
 				)
-				.widths(&[
-					Constraint::Percentage(5),
-					Constraint::Percentage(35),
-					Constraint::Percentage(60),
-				]);
-			f.render_stateful_widget(table, chunks[0], &mut self.table.state.clone());
+				.widths(&widths);
+			let table_rect = if matches!(self.focus, Focus::Edit) {
+				let edit_chunks = Layout::default()
+					.direction(Direction::Vertical)
+					.constraints([Constraint::Min(0), Constraint::Length(3)])
+					.split(chunks[0]);
+				self.render_edit(f, edit_chunks[1]);
+				edit_chunks[0]
+			} else {
+				chunks[0]
+			};
+			f.render_stateful_widget(table, table_rect, &mut self.table.state.clone());
 		} else {
 			let text =
 				self.err.clone().unwrap_or_else(|| "No data found in this database".to_string());
@@ -0,0 +1,48 @@
+use crate::components::{render_container, RenderAbleComponent};
+use tui::{
+	backend::Backend,
+	layout::Rect,
+	text::{Span, Spans},
+	widgets::{Paragraph, Wrap},
+	Frame,
+};
+
+/// Effective options of the selected database, e.g. the settings read from a RocksDB
+/// OPTIONS file
+#[derive(Default)]
+pub struct DatabaseInfoComponent {
+	options: Vec<(String, String)>,
+}
+
+impl DatabaseInfoComponent {
+	pub fn set_options(&mut self, options: Vec<(String, String)>) {
+		self.options = options;
+	}
+
+	pub fn reset(&mut self) {
+		self.options = vec![];
+	}
+}
+
+impl RenderAbleComponent for DatabaseInfoComponent {
+	fn render<B: Backend>(
+		&self,
+		f: &mut Frame<B>,
+		rect: Rect,
+		focused: bool,
+	) -> Result<(), anyhow::Error> {
+		let lines: Vec<Spans> = match self.options.is_empty() {
+			true => vec![Spans::from(Span::raw("No options"))],
+			false => self
+				.options
+				.iter()
+				.map(|(name, value)| Spans::from(Span::raw(format!("{}: {}", name, value))))
+				.collect(),
+		};
+		let info = Paragraph::new(lines).block(render_container("Options", focused)).wrap(Wrap {
+			trim: true,
+		});
+		f.render_widget(info, rect);
+		Ok(())
+	}
+}
@@ -2,14 +2,20 @@ mod command;
 mod container;
 mod database_explorer;
 mod database_selection;
+mod diff;
 mod editor;
+mod info;
 mod preview;
+mod stats;
 mod status;
 
 pub use command::*;
 pub use container::*;
 pub use database_explorer::*;
 pub use database_selection::*;
+pub use diff::*;
 pub use editor::*;
+pub use info::*;
 pub use preview::*;
+pub use stats::*;
 pub use status::*;
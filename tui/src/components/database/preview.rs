@@ -1,3 +1,5 @@
+use std::{collections::HashSet, ops::Range};
+
 use anyhow::Result;
 use db::KeyValuePair;
 use tui::{
@@ -14,15 +16,61 @@ use crate::{
 	config::Config,
 	constants::HIGHLIGHT_COLOR,
 	events::{EventState, Key},
-	ui::StatefulList,
-	utils::{get_key_char, FromLayoutVariant},
+	ui::{HexDump, StatefulList},
+	utils::{get_key_char, FieldNode, LayoutTemplate},
 };
 
 pub struct PreviewComponent<'a> {
 	config: Config,
 	pair: Option<KeyValuePair>,
+	// Side of a diff the pair was read from, shown in the titles
+	side: Option<String>,
 	key_layout: StatefulList<'a>,
 	value_layout: StatefulList<'a>,
+	// Line of the value preview under the cursor and paths of the folded value fields
+	value_cursor: usize,
+	folded: HashSet<Vec<usize>>,
+	// The value is shown as a hex dump with the selected byte under the cursor
+	hex: bool,
+	hex_cursor: usize,
+	// Names of the templates bound to the keyspace of the pair, selected when they change
+	bound: (Option<String>, Option<String>),
+}
+
+// Line of a decoded field, folded is only set for fields with children
+struct PreviewLine {
+	depth: usize,
+	name: String,
+	value: String,
+	path: Vec<usize>,
+	folded: Option<bool>,
+}
+
+// Fields are listed depth first, folded fields show their children on a single line
+fn push_lines(
+	node: &FieldNode,
+	path: Vec<usize>,
+	folded: &HashSet<Vec<usize>>,
+	lines: &mut Vec<PreviewLine>,
+) {
+	let is_folded = folded.contains(&path);
+	let value = match is_folded {
+		true => node.compact_value(),
+		false => node.value.clone(),
+	};
+	lines.push(PreviewLine {
+		depth: path.len() - 1,
+		name: node.name.clone(),
+		value,
+		path: path.clone(),
+		folded: (!node.children.is_empty()).then_some(is_folded),
+	});
+	if is_folded {
+		return;
+	}
+	for (index, child) in node.children.iter().enumerate() {
+		push_lines(child, [path.as_slice(), &[index]].concat(), folded, lines);
+	}
 }
 
 fn build_list(config: Config) -> StatefulList<'static> {
@@ -45,39 +93,151 @@ impl PreviewComponent<'_> {
 			key_layout: build_list(config.clone()),
 			value_layout: build_list(config.clone()),
 			pair: None,
+			side: None,
+			value_cursor: 0,
+			folded: HashSet::new(),
+			hex: false,
+			hex_cursor: 0,
+			bound: (None, None),
 			config,
 		}
 	}
 
+	pub fn with_side(mut self, side: &str) -> Self {
+		self.side = Some(side.to_string());
+		self
+	}
+
+	fn title(&self, title: &str) -> String {
+		match &self.side {
+			Some(side) => format!("{} {}", side, title),
+			None => title.to_string(),
+		}
+	}
+
 	pub fn pair(&self) -> Option<KeyValuePair> {
 		self.pair.clone()
 	}
 
 	pub fn set_pair(&mut self, pair: Option<KeyValuePair>) {
+		if self.pair != pair {
+			self.reset_tree();
+		}
 		self.pair = pair;
 	}
 
-	fn deserialize_key(&self, layout: &StatefulList, raw: Vec<u8>) -> Vec<(String, String)> {
+	fn reset_tree(&mut self) {
+		self.value_cursor = 0;
+		self.folded.clear();
+		self.hex_cursor = 0;
+	}
+
+	// Decoded fields as lines, nested fields follow their parent unless it is folded
+	fn deserialize_key(
+		&self,
+		layout: &StatefulList,
+		raw: Vec<u8>,
+		folded: &HashSet<Vec<usize>>,
+	) -> Vec<PreviewLine> {
 		let selected_layout = layout.state.selected();
-		let default = ("*".to_string(), format!("{:?}", raw));
-		let mut data = vec![default];
+		let mut nodes = vec![FieldNode::new("*", &format!("{:?}", raw))];
 		if let Some(layout) = selected_layout {
-			let index = layout;
-			let l = &self.config.templates[index];
-			let mut items: Vec<(String, String)> = vec![];
-			for item in l.layout.iter() {
-				let (start, end) = (item.from, std::cmp::min(item.to, raw.len()));
-				if start > end {
-					items.push((item.name.clone(), "OVERFLOW".to_string()));
-				} else {
-					let slice = raw[start..end].to_vec();
-					let converted = slice.from_variant(item.variant.clone());
-					items.push((item.name.clone(), converted));
+			nodes = self.config.templates[layout].decode_tree(&raw);
+		}
+		let mut lines = vec![];
+		for (index, node) in nodes.iter().enumerate() {
+			push_lines(node, vec![index], folded, &mut lines);
+		}
+		lines
+	}
+
+	fn value_lines(&self) -> Vec<PreviewLine> {
+		match &self.pair {
+			Some((_, value)) => {
+				self.deserialize_key(&self.value_layout, value.clone(), &self.folded)
+			}
+			None => vec![],
+		}
+	}
+
+	// Value bytes read by the layouts of the selected template, decompressed when it sets a
+	// compression, and the fields reading them
+	fn value_bytes(&self) -> (Vec<u8>, Vec<(String, Range<usize>)>) {
+		let value = match &self.pair {
+			Some((_, value)) => value,
+			None => return (vec![], vec![]),
+		};
+		let template =
+			self.value_layout.state.selected().and_then(|i| self.config.templates.get(i));
+		match template.map(|template| (template, template.decompress(value))) {
+			Some((template, Ok(bytes))) => {
+				let fields = template.field_ranges(&bytes);
+				(bytes.to_vec(), fields)
+			}
+			_ => (value.clone(), vec![]),
+		}
+	}
+
+	// Start of the field before or after the selected byte
+	fn hex_field_start(&self, next: bool) -> Option<usize> {
+		let (_, fields) = self.value_bytes();
+		let starts = fields.into_iter().map(|(_, range)| range.start);
+		match next {
+			true => starts.filter(|start| *start > self.hex_cursor).min(),
+			false => starts.filter(|start| *start < self.hex_cursor).max(),
+		}
+	}
+
+	/// Add templates, replacing templates of the same name, and select them to decode the key
+	/// and the value
+	pub fn use_templates(&mut self, key: LayoutTemplate, value: LayoutTemplate) {
+		let mut select = |template: LayoutTemplate| {
+			let templates = &mut self.config.templates;
+			match templates.iter().position(|t| t.name == template.name) {
+				Some(index) => {
+					templates[index] = template;
+					index
+				}
+				None => {
+					templates.push(template);
+					templates.len() - 1
 				}
 			}
-			data = items;
+		};
+		let (key, value) = (select(key), select(value));
+		self.key_layout = build_list(self.config.clone());
+		self.value_layout = build_list(self.config.clone());
+		self.key_layout.state.select(Some(key));
+		self.value_layout.state.select(Some(value));
+		self.reset_tree();
+	}
+
+	/// Select the templates bound to the keyspace of the pair. Templates selected by hand are
+	/// kept while the pairs use the same binding.
+	pub fn select_bound_templates(&mut self, bound: (Option<String>, Option<String>)) {
+		if self.bound == bound {
+			return;
+		}
+		let position = |name: &Option<String>| {
+			let name = name.as_ref()?;
+			self.config.templates.iter().position(|template| template.name == *name)
+		};
+		if let Some(index) = position(&bound.0) {
+			self.key_layout.state.select(Some(index));
+		}
+		if let Some(index) = position(&bound.1) {
+			self.value_layout.state.select(Some(index));
+			self.reset_tree();
 		}
-		data
+		self.bound = bound;
+	}
+
+	/// Templates selected to decode the key and the value
+	pub fn selected_templates(&self) -> (Option<LayoutTemplate>, Option<LayoutTemplate>) {
+		let template = |layout: &StatefulList| {
+			layout.state.selected().and_then(|index| self.config.templates.get(index)).cloned()
+		};
+		(template(&self.key_layout), template(&self.value_layout))
 	}
 
 	fn render_layout<B: Backend>(
@@ -95,28 +255,43 @@ impl PreviewComponent<'_> {
 		f.render_stateful_widget(list, rect, &mut layout.state.clone());
 	}
 
+	// The cursor is shown once a field can be folded
 	fn render_preview<B: Backend>(
 		&self,
 		f: &mut Frame<B>,
 		rect: Rect,
 		focused: bool,
 		title: &str,
-		layout: &StatefulList,
-		bytes: Vec<u8>,
+		lines: Vec<PreviewLine>,
+		cursor: Option<usize>,
 	) {
-		let values = self.deserialize_key(layout, bytes);
+		let cursor = cursor.filter(|_| lines.iter().any(|line| line.folded.is_some()));
 		let mut spans = vec![];
-		for (name, item) in values.iter() {
+		for (index, line) in lines.iter().enumerate() {
+			let marker = match line.folded {
+				Some(true) => "▸ ",
+				Some(false) => "▾ ",
+				None => "",
+			};
+			let style = match cursor == Some(index) {
+				true => Style::default().add_modifier(Modifier::REVERSED),
+				false => Style::default(),
+			};
 			spans.push(Spans::from(vec![
-				Span::styled(name, Style::default().fg(HIGHLIGHT_COLOR)),
-				Span::raw(":"),
-				Span::raw(item),
+				Span::raw("  ".repeat(line.depth)),
+				Span::raw(marker),
+				Span::styled(line.name.clone(), style.fg(HIGHLIGHT_COLOR)),
+				Span::styled(":", style),
+				Span::styled(line.value.clone(), style),
 			]));
 		}
+		let height = rect.height.saturating_sub(2) as usize;
+		let offset = cursor.map_or(0, |cursor| (cursor + 1).saturating_sub(height));
 		let content = Paragraph::new(spans)
 			.wrap(Wrap {
-				trim: true,
+				trim: false,
 			})
+			.scroll((offset as u16, 0))
 			.block(render_container(title, focused));
 
 		f.render_widget(content, rect);
@@ -135,14 +310,9 @@ impl PreviewComponent<'_> {
 	}
 
 	fn render_key_preview<B: Backend>(&self, f: &mut Frame<B>, rect: Rect, focused: bool) {
-		self.render_preview(
-			f,
-			rect,
-			focused,
-			"Key Preview",
-			&self.key_layout,
-			self.pair.clone().unwrap().0,
-		);
+		let key = self.pair.clone().unwrap().0;
+		let lines = self.deserialize_key(&self.key_layout, key, &HashSet::new());
+		self.render_preview(f, rect, focused, &self.title("Key Preview"), lines, None);
 	}
 
 	fn render_value_layout<B: Backend>(&self, f: &mut Frame<B>, rect: Rect, focused: bool) {
@@ -157,15 +327,34 @@ impl PreviewComponent<'_> {
 		);
 	}
 
+	// The title shows the offset and the fields of the selected byte
+	fn render_value_hex<B: Backend>(&self, f: &mut Frame<B>, rect: Rect, focused: bool) {
+		let (raw, fields) = self.value_bytes();
+		let hex_key = get_key_char(self.config.key_config.preview_hex);
+		let mut title = format!("{} [{}]", self.title("Value Hex"), hex_key);
+		if let Some(byte) = raw.get(self.hex_cursor) {
+			title = format!("{} {:#x}={:02x}", title, self.hex_cursor, byte);
+		}
+		let selected = fields.iter().filter(|(_, range)| range.contains(&self.hex_cursor));
+		for (name, _) in selected {
+			title = format!("{} {}", title, name);
+		}
+		let ranges = fields.into_iter().map(|(_, range)| range).collect();
+		let dump = HexDump::new(&raw)
+			.fields(ranges)
+			.select(Some(self.hex_cursor))
+			.block(render_container(&title, focused));
+
+		f.render_widget(dump, rect);
+	}
+
 	fn render_value_preview<B: Backend>(&self, f: &mut Frame<B>, rect: Rect, focused: bool) {
-		self.render_preview(
-			f,
-			rect,
-			focused,
-			"Value Preview",
-			&self.value_layout,
-			self.pair.clone().unwrap().1,
-		);
+		if self.hex {
+			return self.render_value_hex(f, rect, focused);
+		}
+		let title = self.title("Value Preview");
+		let lines = self.value_lines();
+		self.render_preview(f, rect, focused, &title, lines, Some(self.value_cursor));
 	}
 
 	pub async fn event(&mut self, key: Key) -> Result<EventState> {
@@ -180,10 +369,51 @@ impl PreviewComponent<'_> {
 			}
 			k if k == self.config.key_config.value_layout_up => {
 				self.value_layout.previous();
+				self.reset_tree();
 				return Ok(EventState::Consumed);
 			}
 			k if k == self.config.key_config.value_layout_down => {
 				self.value_layout.next();
+				self.reset_tree();
+				return Ok(EventState::Consumed);
+			}
+			k if k == self.config.key_config.preview_hex && self.pair.is_some() => {
+				self.hex = !self.hex;
+				return Ok(EventState::Consumed);
+			}
+			// The hex dump moves byte by byte, or to the start of a field
+			k if k == self.config.key_config.preview_up && self.hex && self.pair.is_some() => {
+				self.hex_cursor = self.hex_cursor.saturating_sub(1);
+				return Ok(EventState::Consumed);
+			}
+			k if k == self.config.key_config.preview_down && self.hex && self.pair.is_some() => {
+				let last = self.value_bytes().0.len().saturating_sub(1);
+				self.hex_cursor = std::cmp::min(self.hex_cursor + 1, last);
+				return Ok(EventState::Consumed);
+			}
+			k if k == self.config.key_config.preview_prev_field && self.hex => {
+				self.hex_cursor = self.hex_field_start(false).unwrap_or(self.hex_cursor);
+				return Ok(EventState::Consumed);
+			}
+			k if k == self.config.key_config.preview_next_field && self.hex => {
+				self.hex_cursor = self.hex_field_start(true).unwrap_or(self.hex_cursor);
+				return Ok(EventState::Consumed);
+			}
+			k if k == self.config.key_config.preview_up && self.pair.is_some() => {
+				self.value_cursor = self.value_cursor.saturating_sub(1);
+				return Ok(EventState::Consumed);
+			}
+			k if k == self.config.key_config.preview_down && self.pair.is_some() => {
+				let last = self.value_lines().len().saturating_sub(1);
+				self.value_cursor = std::cmp::min(self.value_cursor + 1, last);
+				return Ok(EventState::Consumed);
+			}
+			k if k == self.config.key_config.preview_fold && self.pair.is_some() => {
+				if let Some(line) = self.value_lines().get(self.value_cursor) {
+					if line.folded.is_some() && !self.folded.remove(&line.path) {
+						self.folded.insert(line.path.clone());
+					}
+				}
 				return Ok(EventState::Consumed);
 			}
 			_ => {}
@@ -0,0 +1,165 @@
+use anyhow::Result;
+use db::DatastoreStats;
+use tui::{
+	backend::Backend,
+	layout::Rect,
+	text::{Span, Spans},
+	widgets::{Paragraph, Wrap},
+	Frame,
+};
+
+use crate::{
+	components::{render_container, RenderAbleComponent},
+	config::Config,
+	events::{EventState, Key},
+};
+
+const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+
+fn format_size(size: u64) -> String {
+	let mut value = size as f64;
+	let mut unit = 0;
+	while value >= 1024.0 && unit < UNITS.len() - 1 {
+		value /= 1024.0;
+		unit += 1;
+	}
+	match unit {
+		0 => format!("{} {}", size, UNITS[0]),
+		_ => format!("{:.1} {}", value, UNITS[unit]),
+	}
+}
+
+// Rows of the panel below the editor at most, expanded statistics take the place of the editor
+const PANEL_MAX_HEIGHT: u16 = 8;
+
+/// Statistics of the selected database, e.g. the size on disk and the SST files of each
+/// RocksDB level. The panel below the editor is sized from the statistics and hidden without
+/// them, expanded statistics take the place of the editor and are scrolled.
+pub struct DatabaseStatsComponent {
+	config: Config,
+	stats: Option<DatastoreStats>,
+	expanded: bool,
+	scroll: u16,
+}
+
+impl DatabaseStatsComponent {
+	pub fn new(config: Config) -> Self {
+		DatabaseStatsComponent {
+			config,
+			stats: None,
+			expanded: false,
+			scroll: 0,
+		}
+	}
+
+	pub fn set_stats(&mut self, stats: Option<DatastoreStats>) {
+		self.stats = stats;
+		self.expanded = false;
+		self.scroll = 0;
+	}
+
+	pub fn reset(&mut self) {
+		self.set_stats(None);
+	}
+
+	pub fn is_expanded(&self) -> bool {
+		self.expanded
+	}
+
+	/// Expand the statistics in place of the editor, or collapse them below it
+	pub fn toggle(&mut self) {
+		self.expanded = !self.expanded && self.stats.is_some();
+		self.scroll = 0;
+	}
+
+	/// Height of the panel below the editor for the given width, borders included
+	pub fn height(&self, width: u16) -> u16 {
+		if self.expanded {
+			return 0;
+		}
+		let Some(stats) = &self.stats else {
+			return 0;
+		};
+		let width = width.saturating_sub(2).max(1) as usize;
+		let rows: usize = DatabaseStatsComponent::lines(stats)
+			.iter()
+			.map(|line| line.width().max(1).div_ceil(width))
+			.sum();
+		std::cmp::min(rows as u16 + 2, PANEL_MAX_HEIGHT)
+	}
+
+	pub async fn event(&mut self, key: Key) -> Result<EventState> {
+		if !self.expanded {
+			return Ok(EventState::NotConsumed);
+		}
+		match key {
+			k if k == self.config.key_config.up => self.scroll = self.scroll.saturating_sub(1),
+			k if k == self.config.key_config.down => self.scroll = self.scroll.saturating_add(1),
+			k if k == self.config.key_config.escape => self.toggle(),
+			_ => return Ok(EventState::NotConsumed),
+		}
+		Ok(EventState::Consumed)
+	}
+
+	fn lines(stats: &DatastoreStats) -> Vec<Spans<'_>> {
+		let mut summary = vec![];
+		if let Some(size) = stats.size_on_disk {
+			summary.push(format!("Size on disk: {}", format_size(size)));
+		}
+		if let Some(keys) = stats.estimated_keys {
+			summary.push(format!("Estimated keys: {}", keys));
+		}
+		if let Some(size) = stats.live_data_size {
+			summary.push(format!("Live data: {}", format_size(size)));
+		}
+
+		let mut lines = vec![Spans::from(Span::raw(summary.join(" | ")))];
+		for level in stats.levels.iter() {
+			lines.push(Spans::from(Span::raw(format!(
+				"L{}: {} files, {}, {} entries",
+				level.level,
+				level.files,
+				format_size(level.size),
+				level.entries
+			))));
+		}
+		if !stats.properties.is_empty() {
+			let properties: Vec<String> = stats
+				.properties
+				.iter()
+				.map(|(name, value)| format!("{}: {}", name, value))
+				.collect();
+			lines.push(Spans::from(Span::raw(properties.join(", "))));
+		}
+		if let Some(raw) = &stats.raw {
+			lines.extend(raw.lines().map(|line| Spans::from(Span::raw(line.to_string()))));
+		}
+		lines
+	}
+}
+
+impl RenderAbleComponent for DatabaseStatsComponent {
+	fn render<B: Backend>(
+		&self,
+		f: &mut Frame<B>,
+		rect: Rect,
+		focused: bool,
+	) -> Result<(), anyhow::Error> {
+		let lines = match &self.stats {
+			Some(stats) => DatabaseStatsComponent::lines(stats),
+			None => vec![Spans::from(Span::raw("No statistics"))],
+		};
+		let title = match self.expanded {
+			true => "Statistics [ESC to collapse]",
+			false => "Statistics",
+		};
+		let stats = Paragraph::new(lines)
+			.block(render_container(title, focused))
+			.wrap(Wrap {
+				trim: false,
+			})
+			.scroll((self.scroll, 0));
+		f.render_widget(stats, rect);
+		Ok(())
+	}
+}
@@ -3,6 +3,12 @@ use crate::{
 	config::Config,
 	constants::DEFAULT_STATUS_TEXT,
 };
+use std::{
+	fmt::Display,
+	sync::{Arc, Mutex},
+	thread::{self, JoinHandle},
+	time::{Duration, Instant},
+};
 use tui::{
 	backend::Backend,
 	layout::Rect,
@@ -11,9 +17,24 @@ use tui::{
 	Frame,
 };
 
+type TaskResult = Arc<Mutex<Option<(Result<(), String>, Duration)>>>;
+
+/// Progress a background operation reports while it runs, e.g. the pairs imported so far
+pub type TaskProgress = Arc<Mutex<Option<String>>>;
+
+/// Operation running in the background, its progress replaces the status text
+struct StatusTask {
+	label: String,
+	started: Instant,
+	result: TaskResult,
+	progress: TaskProgress,
+	thread: JoinHandle<()>,
+}
+
 pub struct StatusComponent<'a> {
 	config: Config,
 	text: Span<'a>,
+	task: Option<StatusTask>,
 }
 
 impl<'a> StatusComponent<'a> {
@@ -21,15 +42,77 @@ impl<'a> StatusComponent<'a> {
 		StatusComponent {
 			config,
 			text: Span::raw(DEFAULT_STATUS_TEXT),
+			task: None,
 		}
 	}
 
 	pub fn set_text(&mut self, text: Span<'a>) {
 		self.text = text;
+		self.task = None;
 	}
 
 	pub fn reset(&mut self) {
 		self.text = Span::raw(DEFAULT_STATUS_TEXT);
+		self.task = None;
+	}
+
+	/// Run a long operation such as a compaction on another thread, the status shows how long
+	/// it has been running and then its result
+	pub fn run_task<F>(&mut self, label: &str, task: F)
+	where
+		F: FnOnce() -> Result<(), db::Error> + Send + 'static,
+	{
+		self.run_task_with_progress(label, |_| task())
+	}
+
+	/// Run an operation on another thread, the text it sets in the progress is shown next to
+	/// the label
+	pub fn run_task_with_progress<F, E>(&mut self, label: &str, task: F)
+	where
+		F: FnOnce(TaskProgress) -> Result<(), E> + Send + 'static,
+		E: Display,
+	{
+		let result: TaskResult = Arc::new(Mutex::new(None));
+		let progress: TaskProgress = Arc::new(Mutex::new(None));
+		let (shared, reported) = (result.clone(), progress.clone());
+		let thread = thread::spawn(move || {
+			let started = Instant::now();
+			let outcome = task(reported).map_err(|err| err.to_string());
+			*shared.lock().unwrap() = Some((outcome, started.elapsed()));
+		});
+		self.task = Some(StatusTask {
+			label: label.to_string(),
+			started: Instant::now(),
+			result,
+			progress,
+			thread,
+		});
+	}
+
+	/// An operation is running, a thread which stopped without a result panicked
+	pub fn is_busy(&self) -> bool {
+		match &self.task {
+			Some(task) => task.result.lock().unwrap().is_none() && !task.thread.is_finished(),
+			None => false,
+		}
+	}
+
+	fn task_text(task: &StatusTask) -> String {
+		let progress = match &*task.progress.lock().unwrap() {
+			Some(progress) => format!(" ({})", progress),
+			None => "".to_string(),
+		};
+		match &*task.result.lock().unwrap() {
+			None if task.thread.is_finished() => format!("{} stopped unexpectedly", task.label),
+			None => {
+				let elapsed = task.started.elapsed().as_secs_f32();
+				format!("{}...{} {:.1}s", task.label, progress, elapsed)
+			}
+			Some((Ok(_), elapsed)) => {
+				format!("{} done in {:.1}s{}", task.label, elapsed.as_secs_f32(), progress)
+			}
+			Some((Err(err), _)) => format!("{} failed: {}", task.label, err),
+		}
 	}
 }
 
@@ -40,7 +123,11 @@ impl<'a> RenderAbleComponent for StatusComponent<'a> {
 		rect: Rect,
 		focused: bool,
 	) -> Result<(), anyhow::Error> {
-		let status = Paragraph::new(vec![Spans::from(vec![self.text.clone()])])
+		let text = match &self.task {
+			Some(task) => Span::raw(StatusComponent::task_text(task)),
+			None => self.text.clone(),
+		};
+		let status = Paragraph::new(vec![Spans::from(vec![text])])
 			.block(render_container("Status", focused));
 		f.render_widget(status, rect);
 		Ok(())
@@ -29,12 +29,14 @@ fn build_table(layouts: Vec<ByteLayout>) -> StatefulTable {
 			index.to_string(),
 			layout.name.clone(),
 			layout.variant.to_string(),
-			if layout.from == usize::MIN {
+			if layout.chained {
+				"Previous".to_string()
+			} else if layout.from == usize::MIN {
 				"Start".to_string()
 			} else {
 				layout.from.to_string()
 			},
-			if layout.to == usize::MAX {
+			if layout.chained || layout.to == usize::MAX {
 				"End".to_string()
 			} else {
 				layout.to.to_string()
@@ -63,6 +65,15 @@ impl TemplateLayoutComponent {
 		}
 	}
 
+	// Templates decompressing their values name the compression
+	fn title(&self) -> String {
+		let compression = self.layout.as_ref().and_then(|layout| layout.compression);
+		match compression {
+			Some(compression) => format!("Layout ({})", compression),
+			None => "Layout".to_string(),
+		}
+	}
+
 	pub async fn event(&mut self, _key: Key) -> Result<EventState> {
 		Ok(EventState::NotConsumed)
 	}
@@ -76,8 +87,10 @@ impl RenderAbleComponent for TemplateLayoutComponent {
 		focused: bool,
 	) -> Result<(), anyhow::Error> {
 		if let Some(t) = &self.table {
-			let header_cells =
-				t.headers.iter().map(|h| Cell::from(*h).style(Style::default().fg(Color::Black)));
+			let header_cells = t
+				.headers
+				.iter()
+				.map(|h| Cell::from(h.as_str()).style(Style::default().fg(Color::Black)));
 			let normal_style = Style::default().bg(Color::DarkGray);
 			let header = Row::new(header_cells).style(normal_style).height(1).bottom_margin(1);
 
@@ -91,9 +104,10 @@ impl RenderAbleComponent for TemplateLayoutComponent {
 				Row::new(cells).height(height as u16).bottom_margin(1)
 			});
 
+			let title = self.title();
 			let table = Table::new(rows)
 				.header(header)
-				.block(render_container("Layout", focused))
+				.block(render_container(&title, focused))
 				.highlight_style(Style::default().fg(HIGHLIGHT_COLOR).add_modifier(Modifier::BOLD))
 				.widths(&[
 					Constraint::Percentage(5),
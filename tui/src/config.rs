@@ -1,16 +1,23 @@
 use std::{collections::HashMap, fs, path::PathBuf};
 
-use serde_json::Value;
+use anyhow::{anyhow, Result};
+use db::{Datastore, DatastoreOptions};
+use serde::Serialize;
+use serde_json::{json, ser::PrettyFormatter, Value};
 use structopt::StructOpt;
 
 use crate::{
 	events::Key,
-	utils::{get_absolute_path_buf, sanitize, ByteLayout, LayoutTemplate, LayoutVariant},
+	utils::{
+		get_absolute_path, get_absolute_path_buf, load_schema_fields, sanitize, schema_fields_json,
+		ByteLayout, Compression, LayoutTemplate, LayoutVariant, ProtoLayout, TimeFormat,
+	},
 };
 
 #[derive(Clone, Debug)]
 pub struct DatabaseConfig {
 	pub path: String,
+	pub options: DatastoreOptions,
 }
 
 #[derive(StructOpt, Debug)]
@@ -36,18 +43,64 @@ pub struct KeyConfig {
 	pub key_layout_down: Key,
 	pub value_layout_up: Key,
 	pub value_layout_down: Key,
+	pub preview_up: Key,
+	pub preview_down: Key,
+	pub preview_fold: Key,
+	pub preview_hex: Key,
+	pub preview_prev_field: Key,
+	pub preview_next_field: Key,
 	pub database_select_up: Key,
 	pub database_select_down: Key,
 	pub home_tab: Key,
 	pub database_tab: Key,
 	pub layout_tab: Key,
+	pub edit: Key,
+	pub pin: Key,
+	pub tail: Key,
+	pub stats: Key,
 	pub quit: Key,
 }
 
+/// Templates decoding the keys and values of a keyspace which start with a prefix. Unset
+/// database and keyspace match any database and keyspace.
+#[derive(Clone, Debug, Default)]
+pub struct TemplateBinding {
+	// Database as `<name>:<absolute path>`
+	pub database: Option<String>,
+	pub keyspace: Option<String>,
+	pub prefix: Vec<u8>,
+	pub key_template: Option<String>,
+	pub value_template: Option<String>,
+}
+
+// Database formatted as `name:path` with an absolute path, paths of the config file are
+// relative to the working directory
+fn absolute_db_path(db_path: &str) -> String {
+	match db_path.split_once(':') {
+		Some((name, path)) => format!("{}:{}", name, get_absolute_path(path)),
+		None => db_path.to_string(),
+	}
+}
+
+impl TemplateBinding {
+	fn matches(&self, db_path: &str, keyspace: Option<&str>, key: &[u8]) -> bool {
+		let database = match &self.database {
+			Some(database) => *database == absolute_db_path(db_path),
+			None => true,
+		};
+		let keyspace = match &self.keyspace {
+			Some(bound) => keyspace == Some(bound.as_str()),
+			None => true,
+		};
+		database && keyspace && key.starts_with(&self.prefix)
+	}
+}
+
 #[derive(Clone, Debug)]
 pub struct Config {
 	pub databases: HashMap<String, Vec<DatabaseConfig>>,
 	pub templates: Vec<LayoutTemplate>,
+	pub bindings: Vec<TemplateBinding>,
 	pub path: String,
 	pub key_config: KeyConfig,
 }
@@ -65,6 +118,7 @@ impl Config {
 			databases: Default::default(),
 			path: get_absolute_path_buf(config.config_path.to_path_buf()),
 			templates: Default::default(),
+			bindings: Default::default(),
 			key_config: KeyConfig {
 				backspace: Key::Backspace,
 				enter: Key::Enter,
@@ -77,11 +131,21 @@ impl Config {
 				key_layout_down: Key::Char('j'),
 				value_layout_up: Key::Char('k'),
 				value_layout_down: Key::Char('l'),
+				preview_up: Key::Char('['),
+				preview_down: Key::Char(']'),
+				preview_fold: Key::Char('f'),
+				preview_hex: Key::Char('x'),
+				preview_prev_field: Key::Char('{'),
+				preview_next_field: Key::Char('}'),
 				database_select_up: Key::Char('9'),
 				database_select_down: Key::Char('0'),
 				home_tab: Key::Char('h'),
 				database_tab: Key::Char('d'),
 				layout_tab: Key::Char('l'),
+				edit: Key::Char('e'),
+				pin: Key::Char('p'),
+				tail: Key::Char('t'),
+				stats: Key::Char('s'),
 				quit: Key::Char('q'),
 			},
 		}
@@ -94,6 +158,72 @@ impl Config {
 	pub fn set_layouts(&mut self, layouts: Vec<LayoutTemplate>) {
 		self.templates = layouts;
 	}
+
+	pub fn set_bindings(&mut self, bindings: Vec<TemplateBinding>) {
+		self.bindings = bindings;
+	}
+
+	/// Binding of a key of a keyspace, the binding with the longest prefix applies and then the
+	/// one naming its database or keyspace
+	pub fn binding(
+		&self,
+		db_path: &str,
+		keyspace: Option<&str>,
+		key: &[u8],
+	) -> Option<&TemplateBinding> {
+		let bindings = self.bindings.iter().filter(|b| b.matches(db_path, keyspace, key));
+		bindings.max_by_key(|b| (b.prefix.len(), b.database.is_some(), b.keyspace.is_some()))
+	}
+
+	pub fn template(&self, name: &str) -> Option<&LayoutTemplate> {
+		self.templates.iter().find(|template| template.name == name)
+	}
+
+	/// Write templates to the config file, templates of the same name are replaced. Other
+	/// entries and templates keep their order, the file is written back indented like its
+	/// first entry
+	pub fn save_templates(&self, templates: &[LayoutTemplate]) -> Result<()> {
+		let text = fs::read_to_string(&self.path)?;
+		let mut res: Value = serde_json::from_str(&text)?;
+		let entries = res.as_object_mut().ok_or(anyhow!("config file isn't an object"))?;
+		let names: Vec<_> = templates.iter().map(|template| template.name.as_str()).collect();
+		let items = entries.entry("templates").or_insert_with(|| Value::Array(vec![]));
+		let items = items.as_array_mut().ok_or(anyhow!("templates aren't a list"))?;
+		items.retain(|saved| match saved.get("name").and_then(|name| name.as_str()) {
+			Some(name) => !names.contains(&name),
+			None => true,
+		});
+		for template in templates {
+			items.push(template_json(template)?);
+		}
+
+		// Entries are indented like the first one, or with two spaces like the example config
+		let line = text.trim_start().split_once('\n').map(|(_, rest)| rest).unwrap_or_default();
+		let indent = &line[..line.len() - line.trim_start_matches([' ', '\t']).len()];
+		let indent = Some(indent).filter(|indent| !indent.is_empty()).unwrap_or("  ");
+		let mut json = vec![];
+		let formatter = PrettyFormatter::with_indent(indent.as_bytes());
+		let mut serializer = serde_json::Serializer::with_formatter(&mut json, formatter);
+		res.serialize(&mut serializer)?;
+		json.push(b'\n');
+		fs::write(&self.path, json)?;
+		Ok(())
+	}
+
+	/// Open a database formatted as `name:path` with the options it is configured with
+	pub fn open_datastore(&self, db_path: &str) -> Datastore {
+		self.try_open_datastore(db_path).unwrap()
+	}
+
+	/// Open a database like `open_datastore`, returning the error of a database which can't be
+	/// opened, e.g. locked by another process
+	pub fn try_open_datastore(&self, db_path: &str) -> Result<Datastore, db::Error> {
+		let options = db_path.split_once(':').and_then(|(name, path)| {
+			let databases = self.databases.get(name)?;
+			databases.iter().find(|database| database.path == path).map(|d| d.options.clone())
+		});
+		Datastore::try_new_with_options(db_path, &options.unwrap_or_default())
+	}
 }
 
 fn create_config_example(path: &PathBuf) {
@@ -106,74 +236,413 @@ fn create_config_example(path: &PathBuf) {
 	}
 }
 
-pub fn load_config(cli: &CliConfig) -> Config {
+pub fn load_config(cli: &CliConfig) -> Result<Config> {
 	let mut config = Config::new(cli);
 	if cli.create_config {
 		create_config_example(&cli.config_path);
 	}
 	let Ok(data) = fs::read_to_string(&config.path) else {
-		panic!("Unable to read config file: {}. Run with `--create-config` to create an example config file", config.path)
+		let hint = "Run with `--create-config` to create an example config file";
+		return Err(anyhow!("Unable to read config file: {}. {}", config.path, hint));
 	};
-	let res: serde_json::Value = serde_json::from_str(&data).expect("Unable to parse");
+	let res: serde_json::Value =
+		serde_json::from_str(&data).map_err(|err| anyhow!("Unable to parse config: {}", err))?;
+
+	// Plugins are registered before databases so their schemes can be resolved
+	if let Some(p) = res.get("plugins") {
+		load_plugins(p).map_err(|err| anyhow!("Invalid config: {}", err))?;
+	}
 
 	if let Some(d) = res.get("databases") {
-		let databases = load_databases(d);
+		let databases = load_databases(d).map_err(|err| anyhow!("Invalid config: {}", err))?;
 		config.set_databases(databases);
 	}
 
-	if let Some(t) = res.get("templates") {
-		let system_templates = vec![
-			build_template("Bytes", LayoutVariant::Bytes),
-			build_template("String", LayoutVariant::String),
-			build_template("Int32", LayoutVariant::Int32),
-			build_template("Int64", LayoutVariant::Int64),
-			build_template("Float32", LayoutVariant::Float32),
-			build_template("Float64", LayoutVariant::Float64),
-			build_template("Boolean", LayoutVariant::Boolean),
-		];
-		let templates = load_templates(t);
-		config.set_layouts([system_templates, templates].concat());
+	// System templates are available to bindings and commands without user templates
+	let system_templates = vec![
+		build_template("Bytes", LayoutVariant::Bytes),
+		build_template("String", LayoutVariant::String),
+		build_template("Int8", LayoutVariant::Int8),
+		build_template("UInt8", LayoutVariant::UInt8),
+		build_template("Int16BE", LayoutVariant::Int16BE),
+		build_template("Int16LE", LayoutVariant::Int16LE),
+		build_template("UInt16BE", LayoutVariant::UInt16BE),
+		build_template("UInt16LE", LayoutVariant::UInt16LE),
+		build_template("Int32BE", LayoutVariant::Int32BE),
+		build_template("Int32LE", LayoutVariant::Int32LE),
+		build_template("UInt32BE", LayoutVariant::UInt32BE),
+		build_template("UInt32LE", LayoutVariant::UInt32LE),
+		build_template("Int64BE", LayoutVariant::Int64BE),
+		build_template("Int64LE", LayoutVariant::Int64LE),
+		build_template("UInt64BE", LayoutVariant::UInt64BE),
+		build_template("UInt64LE", LayoutVariant::UInt64LE),
+		build_template("Int128BE", LayoutVariant::Int128BE),
+		build_template("Int128LE", LayoutVariant::Int128LE),
+		build_template("UInt128BE", LayoutVariant::UInt128BE),
+		build_template("UInt128LE", LayoutVariant::UInt128LE),
+		build_template("Float32", LayoutVariant::Float32),
+		build_template("Float64", LayoutVariant::Float64),
+		build_template("Boolean", LayoutVariant::Boolean),
+		build_template("Json", LayoutVariant::Json),
+		build_template("Cbor", LayoutVariant::Cbor),
+		build_template("MessagePack", LayoutVariant::MessagePack),
+		build_template("Timestamp", LayoutVariant::Timestamp),
+		build_template("Date", LayoutVariant::Date),
+		// Names of big endian integers before their endianness was written
+		build_template("Int32", LayoutVariant::Int32BE),
+		build_template("Int64", LayoutVariant::Int64BE),
+	];
+	let templates = res.get("templates").map(load_templates).transpose();
+	let templates = templates.map_err(|err| anyhow!("Invalid config: {}", err))?;
+	config.set_layouts([system_templates, templates.unwrap_or_default()].concat());
+
+	// Bound templates must be loaded first
+	if let Some(b) = res.get("bindings") {
+		let bindings =
+			load_bindings(b, &config).map_err(|err| anyhow!("Invalid config: {}", err))?;
+		config.set_bindings(bindings);
 	}
 
-	config
+	Ok(config)
 }
 
 /// Load byte layout template from JSON config file
-fn load_templates(json_templates: &Value) -> Vec<LayoutTemplate> {
-	let templates = json_templates.as_array();
+fn load_templates(json_templates: &Value) -> Result<Vec<LayoutTemplate>, String> {
+	let templates = json_templates.as_array().ok_or("templates must be an array")?;
 	let mut layout_templates = Vec::<LayoutTemplate>::new();
-	for template in templates.unwrap().iter() {
+	for template in templates.iter() {
 		let mut t = LayoutTemplate::default();
-		let name = sanitize(&template.get("name").unwrap().to_string());
+		let name = template.get("name").ok_or("template without name")?;
+		let name = sanitize(&name.to_string());
 		t.set_name(&name);
-		let layouts = template.get("layouts").unwrap().as_array();
+		if let Some(compression) = template.get("compression").and_then(|c| c.as_str()) {
+			let Some(compression) = Compression::from_string(compression) else {
+				return Err(format!("unknown compression {} of template {}", compression, name));
+			};
+			t.set_compression(Some(compression));
+		}
+		let layouts = template.get("layouts").and_then(|layouts| layouts.as_array());
+		let layouts = layouts.ok_or(format!("template {} without layouts", name))?;
 		// Load layout from json template
-		for layout in layouts.unwrap().iter() {
+		for layout in layouts.iter() {
 			let mut l = ByteLayout::default();
-			let name = sanitize(&layout.get("name").unwrap().to_string());
-			let variant = sanitize(&layout.get("variant").unwrap().to_string());
-			let from = layout.get("from").unwrap().as_i64().unwrap() as usize;
-			let to = layout.get("to").unwrap().as_i64().unwrap() as usize;
-			let variant = LayoutVariant::from_string(&variant);
-			t.push_layout(l.with_name(name).with_variant(variant).with_range(from, to).build());
+			let (name, variant) = match (layout.get("name"), layout.get("variant")) {
+				(Some(name), Some(variant)) => (name.to_string(), variant.to_string()),
+				_ => return Err(format!("layout of template {} without name or variant", name)),
+			};
+			let variant = LayoutVariant::from_string(&sanitize(&variant))
+				.map_err(|err| format!("layout {}: {}", sanitize(&name), err))?;
+			l.with_name(sanitize(&name)).with_variant(variant);
+			// Protobuf layouts without a schema are decoded from their wire format
+			if let Some(proto) = layout.get("proto").and_then(|proto| proto.as_str()) {
+				let message = layout.get("message").and_then(|message| message.as_str());
+				let proto = get_absolute_path(proto);
+				l.with_proto(ProtoLayout::load(&proto, message.unwrap_or_default()));
+			}
+			if let LayoutVariant::Timestamp | LayoutVariant::Date = l.variant {
+				let time = load_time_format(layout)
+					.map_err(|err| format!("layout {}: {}", l.name, err))?;
+				l.with_time(time);
+			}
+			if let Some(fields) = layout.get("fields") {
+				l.with_schema(load_schema_fields(fields));
+			}
+			// A layout without `from` starts where the previous one ended
+			match layout.get("from").and_then(|from| from.as_i64()) {
+				Some(from) => {
+					let to = layout.get("to").and_then(|to| to.as_i64());
+					l.with_range(from as usize, to.map_or(usize::MAX, |to| to as usize))
+				}
+				None => l.chained(),
+			};
+			t.push_layout(l.build());
 		}
 		layout_templates.push(t);
 	}
 
-	layout_templates
+	Ok(layout_templates)
+}
+
+// Template as it is loaded by `load_templates`, layouts whose schema couldn't be loaded are
+// refused as they would be saved without it
+fn template_json(template: &LayoutTemplate) -> Result<Value> {
+	let mut layouts = vec![];
+	for layout in template.layout.iter() {
+		let mut json = json!({ "name": layout.name, "variant": layout.variant.to_string() });
+		if !layout.chained {
+			json["from"] = layout.from.into();
+			if layout.to != usize::MAX {
+				json["to"] = layout.to.into();
+			}
+		}
+		match &layout.proto {
+			Some(Ok(proto)) => {
+				json["proto"] = proto.path.clone().into();
+				json["message"] = proto.message.clone().into();
+			}
+			Some(Err(err)) => return Err(anyhow!("layout {}: {}", layout.name, err)),
+			None => {}
+		}
+		match &layout.schema {
+			Some(Ok(fields)) => json["fields"] = schema_fields_json(fields),
+			Some(Err(err)) => return Err(anyhow!("layout {}: {}", layout.name, err)),
+			None => {}
+		}
+		if let LayoutVariant::Timestamp | LayoutVariant::Date = layout.variant {
+			let time = &layout.time;
+			json["unit"] = time.unit.to_string().into();
+			json["endian"] = match time.little_endian {
+				true => "le".into(),
+				false => "be".into(),
+			};
+			if let Some(size) = time.size {
+				json["size"] = size.into();
+			}
+			if !time.signed {
+				json["signed"] = false.into();
+			}
+			json["timezone"] = time.timezone.to_string().into();
+		}
+		layouts.push(json);
+	}
+	let mut json = json!({ "name": template.name, "layouts": layouts });
+	if let Some(compression) = template.compression {
+		json["compression"] = compression.to_string().into();
+	}
+	Ok(json)
+}
+
+// Unit, endianness, size, signedness and timezone of timestamp and date layouts, all optional
+fn load_time_format(layout: &Value) -> Result<TimeFormat, String> {
+	let mut time = TimeFormat::default();
+	let text = |key: &str| layout.get(key).and_then(|value| value.as_str());
+	if let Some(unit) = text("unit") {
+		time.set_unit(unit)?;
+	}
+	if let Some(endian) = text("endian") {
+		time.set_endian(endian)?;
+	}
+	if let Some(size) = layout.get("size").and_then(|size| size.as_u64()) {
+		time.set_size(size)?;
+	}
+	if let Some(signed) = layout.get("signed").and_then(|signed| signed.as_bool()) {
+		time.signed = signed;
+	}
+	if let Some(timezone) = text("timezone") {
+		time.set_timezone(timezone)?;
+	}
+	Ok(time)
+}
+
+/// Load storage plugins from JSON config file
+fn load_plugins(json_plugins: &Value) -> Result<(), String> {
+	let plugins = json_plugins.as_array().ok_or("plugins must be an array")?;
+	for plugin in plugins.iter() {
+		let (name, path) = match (plugin.get("name"), plugin.get("path")) {
+			(Some(name), Some(path)) => (sanitize(&name.to_string()), sanitize(&path.to_string())),
+			_ => return Err("plugin without name or path".to_string()),
+		};
+		if let Err(err) = db::load_plugin(&name, &path) {
+			return Err(format!("unable to load plugin {} from {}: {}", name, path, err));
+		}
+	}
+	Ok(())
+}
+
+/// Load bindings of templates to keyspaces from JSON config file
+fn load_bindings(json_bindings: &Value, config: &Config) -> Result<Vec<TemplateBinding>, String> {
+	let bindings = json_bindings.as_array().ok_or("bindings must be an array")?;
+	let mut template_bindings = Vec::<TemplateBinding>::new();
+	for binding in bindings.iter() {
+		let text = |key: &str| binding.get(key).and_then(|value| value.as_str());
+		let template = |key: &str| match text(key) {
+			Some(name) => match config.template(name) {
+				Some(template) => Ok(Some(template.name.clone())),
+				None => Err(format!("unknown template {} of binding", name)),
+			},
+			None => Ok(None),
+		};
+		template_bindings.push(TemplateBinding {
+			database: text("database").map(absolute_db_path),
+			keyspace: text("keyspace").map(|keyspace| keyspace.to_string()),
+			prefix: text("prefix").unwrap_or_default().as_bytes().to_vec(),
+			key_template: template("key_template")?,
+			value_template: template("value_template")?,
+		});
+	}
+
+	Ok(template_bindings)
 }
 
 /// Load databases from JSON config file
-fn load_databases(json_database: &Value) -> HashMap<String, Vec<DatabaseConfig>> {
-	let databases = json_database.as_array();
+fn load_databases(json_database: &Value) -> Result<HashMap<String, Vec<DatabaseConfig>>, String> {
+	let databases = json_database.as_array().ok_or("databases must be an array")?;
 	let mut databases_config = HashMap::<String, Vec<DatabaseConfig>>::new();
-	for database in databases.unwrap().iter() {
-		let path = sanitize(&database.get("path").unwrap().to_string());
-		let name = sanitize(&database.get("name").unwrap().to_string());
+	for database in databases.iter() {
+		let (path, name) = match (database.get("path"), database.get("name")) {
+			(Some(path), Some(name)) => (sanitize(&path.to_string()), sanitize(&name.to_string())),
+			_ => return Err("database without name or path".to_string()),
+		};
+		let options = match database.get("options") {
+			Some(options) => serde_json::from_value(options.clone())
+				.map_err(|err| format!("invalid options for database {}: {}", path, err))?,
+			None => DatastoreOptions::default(),
+		};
 		databases_config.entry(name).or_default().push(DatabaseConfig {
 			path,
+			options,
 		});
 	}
 
-	databases_config
+	Ok(databases_config)
+}
+
+#[cfg(test)]
+mod test {
+	use std::{env::temp_dir, fs, path::PathBuf};
+
+	use serde_json::{json, Value};
+
+	use super::{load_config, load_templates, CliConfig, Config};
+
+	fn cli(name: &str, text: &str) -> CliConfig {
+		let path = temp_dir().join(format!("edma-{}-{}.json", name, std::process::id()));
+		fs::write(&path, text).unwrap();
+		CliConfig {
+			config_path: path,
+			create_config: false,
+		}
+	}
+
+	fn config(name: &str, text: &str) -> (Config, PathBuf) {
+		let cli = cli(name, text);
+		(Config::new(&cli), cli.config_path)
+	}
+
+	#[test]
+	fn should_save_templates_in_place() {
+		let proto = temp_dir().join(format!("edma-save-{}.proto", std::process::id()));
+		fs::write(&proto, "message User { string name = 1; }").unwrap();
+		let text = r#"{
+    "databases": [{ "name": "sled", "path": "../temp" }],
+    "templates": [
+        { "name": "keep", "layouts": [{ "name": "id", "variant": "UInt64BE", "from": 0 }] },
+        { "name": "users", "layouts": [] }
+    ],
+    "bindings": []
+}
+"#;
+		let (config, path) = config("save-templates", text);
+		let templates = load_templates(&json!([{
+			"name": "users",
+			"layouts": [
+				{ "name": "user", "variant": "Protobuf", "from": 0, "to": 4, "proto": proto, "message": "User" },
+				{ "name": "at", "variant": "Timestamp", "unit": "ms", "size": 4, "signed": false },
+				{ "name": "rest", "variant": "Bincode", "fields": [
+					{ "name": "id", "type": "u64" },
+					{ "name": "tags", "type": "vec<struct>", "fields": [{ "name": "tag", "type": "string" }] }
+				] }
+			]
+		}]))
+		.unwrap();
+		config.save_templates(&templates).unwrap();
+		let saved = fs::read_to_string(&path).unwrap();
+		assert!(saved
+			.starts_with("{\n    \"databases\": [\n        {\n            \"name\": \"sled\","));
+		assert!(saved.ends_with("\n    ],\n    \"bindings\": []\n}\n"));
+
+		let saved: Value = serde_json::from_str(&saved).unwrap();
+		let keys: Vec<_> = saved.as_object().unwrap().keys().collect();
+		assert_eq!(keys, vec!["databases", "templates", "bindings"]);
+		let saved = saved.get("templates").unwrap().as_array().unwrap();
+		assert_eq!(saved.len(), 2);
+		assert_eq!(saved[0].get("name").unwrap(), "keep");
+		assert_eq!(
+			saved[1].get("layouts").unwrap(),
+			&json!([
+				{ "name": "user", "variant": "Protobuf", "from": 0, "to": 4, "proto": proto, "message": "User" },
+				{ "name": "at", "variant": "Timestamp", "unit": "ms", "endian": "be", "size": 4, "signed": false, "timezone": "UTC" },
+				{ "name": "rest", "variant": "Bincode", "fields": [
+					{ "name": "id", "type": "u64" },
+					{ "name": "tags", "type": "vec<struct>", "fields": [{ "name": "tag", "type": "string" }] }
+				] }
+			])
+		);
+		fs::remove_file(path).unwrap();
+		fs::remove_file(proto).unwrap();
+	}
+
+	#[test]
+	fn should_add_templates_entry() {
+		let templates = load_templates(&json!([{ "name": "ids", "layouts": [] }])).unwrap();
+		let (config, path) = config("add-templates", "{\n\t\"databases\": []\n}\n");
+		config.save_templates(&templates).unwrap();
+		let saved = fs::read_to_string(&path).unwrap();
+		let expected = "{\n\t\"databases\": [],\n\t\"templates\": [\n\t\t{\n\t\t\t\"name\": \"ids\",\n\t\t\t\"layouts\": []\n\t\t}\n\t]\n}\n";
+		assert_eq!(saved, expected);
+
+		fs::write(&path, "{}").unwrap();
+		config.save_templates(&templates).unwrap();
+		let saved: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+		assert_eq!(saved, json!({ "templates": [{ "name": "ids", "layouts": [] }] }));
+
+		fs::write(&path, "{ \"templates\": {} }").unwrap();
+		assert!(config.save_templates(&templates).is_err());
+		fs::remove_file(path).unwrap();
+	}
+
+	#[test]
+	fn should_bind_system_templates_without_templates() {
+		let text = r#"{ "bindings": [{ "prefix": "user:", "key_template": "SYSTEM:String" }] }"#;
+		let cli = cli("system-templates", text);
+		let config = load_config(&cli).unwrap();
+		assert!(config.template("SYSTEM:Timestamp").is_some());
+		let binding = config.binding("sled:./db", None, b"user:1").unwrap();
+		assert_eq!(binding.key_template.as_deref(), Some("SYSTEM:String"));
+		fs::remove_file(cli.config_path).unwrap();
+	}
+
+	#[test]
+	fn should_reject_unknown_binding_templates() {
+		let text = r#"{ "bindings": [{ "value_template": "User" }] }"#;
+		let cli = cli("unknown-template", text);
+		let err = load_config(&cli).err().unwrap();
+		assert_eq!(err.to_string(), "Invalid config: unknown template User of binding");
+		fs::remove_file(cli.config_path).unwrap();
+	}
+
+	#[test]
+	fn should_reject_invalid_plugins_and_databases() {
+		let text = r#"{ "plugins": [{ "name": "kv" }] }"#;
+		let cli = cli("invalid-plugin", text);
+		let err = load_config(&cli).err().unwrap();
+		assert_eq!(err.to_string(), "Invalid config: plugin without name or path");
+
+		let text = r#"{ "databases": [{ "name": "sled", "path": "../temp", "options": 1 }] }"#;
+		fs::write(&cli.config_path, text).unwrap();
+		let err = load_config(&cli).err().unwrap();
+		assert!(err.to_string().starts_with("Invalid config: invalid options for database"));
+		fs::write(&cli.config_path, r#"{ "databases": {} }"#).unwrap();
+		let err = load_config(&cli).err().unwrap();
+		assert_eq!(err.to_string(), "Invalid config: databases must be an array");
+		fs::remove_file(cli.config_path).unwrap();
+	}
+
+	#[test]
+	fn should_reject_unknown_variants() {
+		let templates = load_templates(&json!([{
+			"name": "ids",
+			"layouts": [{ "name": "id", "variant": "Int32" }, { "name": "count", "variant": "Int64BE" }]
+		}]))
+		.unwrap();
+		let variants: Vec<_> =
+			templates[0].layout.iter().map(|layout| layout.variant.to_string()).collect();
+		assert_eq!(variants, vec!["Int32BE", "Int64BE"]);
+
+		let text = r#"{ "templates": [{ "name": "ids", "layouts": [{ "name": "id", "variant": "Int23" }] }] }"#;
+		let cli = cli("unknown-variant", text);
+		let err = load_config(&cli).err().unwrap();
+		assert_eq!(err.to_string(), "Invalid config: layout id: unknown variant Int23");
+		fs::remove_file(cli.config_path).unwrap();
+	}
 }
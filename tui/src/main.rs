@@ -21,7 +21,10 @@ mod utils;
 #[tokio::main]
 async fn main() -> Result<()> {
 	let value = crate::cli::parse();
-	let config = load_config(&value.config);
+	let config = load_config(&value.config)?;
+	if let Some(command) = value.command {
+		return cli::run(&config, command).await;
+	}
 
 	setup_terminal()?;
 
@@ -51,7 +54,7 @@ async fn main() -> Result<()> {
 				Err(_) => unimplemented!(),
 			},
 
-			Event::Tick => {}
+			Event::Tick => app.tick().await,
 		}
 	}
 
@@ -0,0 +1,111 @@
+use std::ops::Range;
+
+use tui::{
+	buffer::Buffer,
+	layout::Rect,
+	style::{Color, Modifier, Style},
+	widgets::{Block, Widget},
+};
+
+// Colors of consecutive fields, bytes outside every field keep the default style
+const FIELD_COLORS: [Color; 5] =
+	[Color::Cyan, Color::Yellow, Color::Magenta, Color::Green, Color::LightBlue];
+
+// Offset of the first byte of a row, followed by two spaces
+const OFFSET_WIDTH: u16 = 10;
+
+/// Hex dump of bytes: the offset of each row, its bytes in hex and their ASCII characters
+/// side by side. Bytes are colored by the field reading them and the selected byte is shown
+/// reversed, rows are scrolled so it stays visible.
+pub struct HexDump<'a> {
+	raw: &'a [u8],
+	fields: Vec<Range<usize>>,
+	selected: Option<usize>,
+	block: Option<Block<'a>>,
+}
+
+impl<'a> HexDump<'a> {
+	pub fn new(raw: &'a [u8]) -> Self {
+		HexDump {
+			raw,
+			fields: vec![],
+			selected: None,
+			block: None,
+		}
+	}
+
+	pub fn fields(mut self, fields: Vec<Range<usize>>) -> Self {
+		self.fields = fields;
+		self
+	}
+
+	pub fn select(mut self, selected: Option<usize>) -> Self {
+		self.selected = selected;
+		self
+	}
+
+	pub fn block(mut self, block: Block<'a>) -> Self {
+		self.block = Some(block);
+		self
+	}
+
+	/// Bytes shown on a row of the given width, 16 at most and a multiple of 4 when possible.
+	/// Each byte takes three columns of hex and one of ASCII.
+	pub fn row_len(width: u16) -> usize {
+		let len = (width.saturating_sub(OFFSET_WIDTH + 1) / 4).clamp(1, 16) as usize;
+		match len {
+			4.. => len - len % 4,
+			_ => len,
+		}
+	}
+
+	fn style(&self, index: usize) -> Style {
+		let field = self.fields.iter().position(|field| field.contains(&index));
+		let style = match field {
+			Some(field) => Style::default().fg(FIELD_COLORS[field % FIELD_COLORS.len()]),
+			None => Style::default(),
+		};
+		match self.selected == Some(index) {
+			true => style.add_modifier(Modifier::REVERSED),
+			false => style,
+		}
+	}
+}
+
+impl Widget for HexDump<'_> {
+	fn render(mut self, area: Rect, buf: &mut Buffer) {
+		let area = match self.block.take() {
+			Some(block) => {
+				let inner = block.inner(area);
+				block.render(area, buf);
+				inner
+			}
+			None => area,
+		};
+		if area.height == 0 || area.width <= OFFSET_WIDTH {
+			return;
+		}
+		let row_len = HexDump::row_len(area.width);
+		let height = area.height as usize;
+		let selected_row = self.selected.map_or(0, |selected| selected / row_len);
+		let first_row = (selected_row + 1).saturating_sub(height);
+		let rows = self.raw.chunks(row_len).enumerate().skip(first_row).take(height);
+		let offset_style = Style::default().fg(Color::DarkGray);
+		for (line, (row, bytes)) in rows.enumerate() {
+			let y = area.y + line as u16;
+			let start = row * row_len;
+			buf.set_string(area.x, y, format!("{:08x}", start), offset_style);
+			let ascii_x = area.x + OFFSET_WIDTH + row_len as u16 * 3 + 1;
+			for (column, byte) in bytes.iter().enumerate() {
+				let style = self.style(start + column);
+				let hex_x = area.x + OFFSET_WIDTH + column as u16 * 3;
+				buf.set_string(hex_x, y, format!("{:02x}", byte), style);
+				let char = match byte {
+					0x20..=0x7e => *byte as char,
+					_ => '.',
+				};
+				buf.set_string(ascii_x + column as u16, y, char.to_string(), style);
+			}
+		}
+	}
+}
@@ -1,5 +1,7 @@
+mod hex_dump;
 mod list;
 mod table;
 
+pub use hex_dump::*;
 pub use list::*;
 pub use table::*;
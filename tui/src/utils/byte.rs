@@ -1,10 +1,54 @@
+use std::{borrow::Cow, ops::Range};
+
 use uuid::Uuid;
 
+use super::{
+	decode_cbor, decode_json, decode_msgpack, decode_proto, decode_schema, Compression,
+	ProtoLayout, SchemaCodec, SchemaField, TimeFormat,
+};
+
+// Integers wider than a byte name their endianness, `Int32` and `Int64` are read as `Int32BE`
+// and `Int64BE`
 #[derive(Clone, Debug)]
 pub enum LayoutVariant {
 	String,
-	Int32,
-	Int64,
+	Int8,
+	UInt8,
+	Int16BE,
+	Int16LE,
+	UInt16BE,
+	UInt16LE,
+	Int32BE,
+	Int32LE,
+	UInt32BE,
+	UInt32LE,
+	Int64BE,
+	Int64LE,
+	UInt64BE,
+	UInt64LE,
+	Int128BE,
+	Int128LE,
+	UInt128BE,
+	UInt128LE,
+	// Unsigned LEB128, as protobuf varints
+	Varint,
+	// Signed LEB128 mapped with zigzag encoding, as protobuf sint64
+	ZigZagVarint,
+	// Varint length followed by as many bytes, shown as text when they are UTF-8
+	LengthPrefixed,
+	// Protobuf message, decoded with the schema of the layout or from its wire format
+	Protobuf,
+	Json,
+	Cbor,
+	MessagePack,
+	// Decoded with the fields of the layout
+	Bincode,
+	Borsh,
+	Scale,
+	// Units since the Unix epoch, shown as a date in the timezone of the layout
+	Timestamp,
+	// Days since the Unix epoch
+	Date,
 	UuidV4,
 	Float32,
 	Float64,
@@ -19,14 +63,17 @@ impl Default for LayoutVariant {
 }
 
 macro_rules! impl_convert_string {
-	($(($code:ident, $str:expr)),*) => {
+	($(($code:ident, $str:expr)),*; $(($alias_code:ident, $alias:expr)),*) => {
 impl LayoutVariant {
-	pub fn from_string(s: &str) -> LayoutVariant {
+	pub fn from_string(s: &str) -> Result<LayoutVariant, String> {
 		match s {
 		$(
-			$str => LayoutVariant::$code,
+			$str => Ok(LayoutVariant::$code),
+		)*
+		$(
+			$alias => Ok(LayoutVariant::$alias_code),
 		)*
-		_ => unimplemented!(),
+		_ => Err(format!("unknown variant {}", s)),
 	}
 	}
 	pub fn to_string(&self) -> String {
@@ -42,21 +89,179 @@ impl LayoutVariant {
 
 impl_convert_string!(
 	(String, "String"),
-	(Int32, "Int32"),
-	(Int64, "Int64"),
+	(Int8, "Int8"),
+	(UInt8, "UInt8"),
+	(Int16BE, "Int16BE"),
+	(Int16LE, "Int16LE"),
+	(UInt16BE, "UInt16BE"),
+	(UInt16LE, "UInt16LE"),
+	(Int32BE, "Int32BE"),
+	(Int32LE, "Int32LE"),
+	(UInt32BE, "UInt32BE"),
+	(UInt32LE, "UInt32LE"),
+	(Int64BE, "Int64BE"),
+	(Int64LE, "Int64LE"),
+	(UInt64BE, "UInt64BE"),
+	(UInt64LE, "UInt64LE"),
+	(Int128BE, "Int128BE"),
+	(Int128LE, "Int128LE"),
+	(UInt128BE, "UInt128BE"),
+	(UInt128LE, "UInt128LE"),
+	(Varint, "Varint"),
+	(ZigZagVarint, "ZigZagVarint"),
+	(LengthPrefixed, "LengthPrefixed"),
+	(Protobuf, "Protobuf"),
+	(Json, "Json"),
+	(Cbor, "Cbor"),
+	(MessagePack, "MessagePack"),
+	(Bincode, "Bincode"),
+	(Borsh, "Borsh"),
+	(Scale, "Scale"),
+	(Timestamp, "Timestamp"),
+	(Date, "Date"),
 	(UuidV4, "UuidV4"),
 	(Float32, "Float32"),
 	(Float64, "Float64"),
 	(Boolean, "Boolean"),
-	(Bytes, "Bytes")
+	(Bytes, "Bytes");
+	(Int32BE, "Int32"),
+	(Int64BE, "Int64")
 );
 
+impl LayoutVariant {
+	// Codec of variants decoded with the fields of their layout
+	fn codec(&self) -> Option<SchemaCodec> {
+		match self {
+			LayoutVariant::Bincode => Some(SchemaCodec::Bincode),
+			LayoutVariant::Borsh => Some(SchemaCodec::Borsh),
+			LayoutVariant::Scale => Some(SchemaCodec::Scale),
+			_ => None,
+		}
+	}
+
+	// Bytes taken by a value, variable size variants read their size from the data and
+	// others take every byte they are given
+	fn field_len(&self, raw: &[u8]) -> Option<usize> {
+		match self {
+			LayoutVariant::Int8 | LayoutVariant::UInt8 => Some(1),
+			LayoutVariant::Int16BE
+			| LayoutVariant::Int16LE
+			| LayoutVariant::UInt16BE
+			| LayoutVariant::UInt16LE => Some(2),
+			LayoutVariant::Int32BE
+			| LayoutVariant::Int32LE
+			| LayoutVariant::UInt32BE
+			| LayoutVariant::UInt32LE
+			| LayoutVariant::Float32 => Some(4),
+			LayoutVariant::Int64BE
+			| LayoutVariant::Int64LE
+			| LayoutVariant::UInt64BE
+			| LayoutVariant::UInt64LE
+			| LayoutVariant::Float64 => Some(8),
+			LayoutVariant::Int128BE
+			| LayoutVariant::Int128LE
+			| LayoutVariant::UInt128BE
+			| LayoutVariant::UInt128LE
+			| LayoutVariant::UuidV4 => Some(16),
+			LayoutVariant::Varint | LayoutVariant::ZigZagVarint => {
+				decode_varint(raw).map(|(_, len)| len)
+			}
+			LayoutVariant::LengthPrefixed => {
+				decode_varint(raw).map(|(value, len)| len.saturating_add(value as usize))
+			}
+			_ => None,
+		}
+	}
+}
+
+/// Read an unsigned LEB128 value, with the number of bytes it takes
+pub fn decode_varint(raw: &[u8]) -> Option<(u64, usize)> {
+	let mut value: u64 = 0;
+	for (index, byte) in raw.iter().enumerate().take(10) {
+		value |= ((byte & 0x7f) as u64) << (index * 7);
+		if byte & 0x80 == 0 {
+			return Some((value, index + 1));
+		}
+	}
+	None
+}
+
+pub fn decode_zigzag(value: u64) -> i64 {
+	(value >> 1) as i64 ^ -((value & 1) as i64)
+}
+
+/// Decoded field, values with a structure like protobuf messages have children
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FieldNode {
+	pub name: String,
+	pub value: String,
+	pub children: Vec<FieldNode>,
+	// Children are items of a sequence rather than named fields
+	pub list: bool,
+}
+
+impl FieldNode {
+	pub fn new(name: &str, value: &str) -> Self {
+		FieldNode {
+			name: name.to_string(),
+			value: value.to_string(),
+			children: vec![],
+			list: false,
+		}
+	}
+
+	pub fn with_children(mut self, children: Vec<FieldNode>) -> Self {
+		self.children = children;
+		self
+	}
+
+	pub fn with_items(mut self, items: Vec<FieldNode>) -> Self {
+		self.children = items;
+		self.list = true;
+		self
+	}
+
+	/// Value on a single line, fields are listed in braces and items in brackets after the
+	/// value of the node, e.g. the variant of an enum
+	pub fn compact_value(&self) -> String {
+		if self.children.is_empty() {
+			return self.value.clone();
+		}
+		let children = match self.list {
+			true => {
+				let items: Vec<_> = self.children.iter().map(|item| item.compact_value()).collect();
+				format!("[{}]", items.join(", "))
+			}
+			false => {
+				let children: Vec<_> = self
+					.children
+					.iter()
+					.map(|child| format!("{}: {}", child.name, child.compact_value()))
+					.collect();
+				format!("{{{}}}", children.join(", "))
+			}
+		};
+		match self.value.is_empty() {
+			true => children,
+			false => format!("{} {}", self.value, children),
+		}
+	}
+}
+
 #[derive(Clone, Debug)]
 pub struct ByteLayout {
 	pub variant: LayoutVariant,
 	pub name: String,
 	pub from: usize,
 	pub to: usize,
+	// The field starts where the previous one ended, `from` and `to` are not used
+	pub chained: bool,
+	// Schema of `Protobuf` layouts, or the reason it couldn't be loaded
+	pub proto: Option<Result<ProtoLayout, String>>,
+	// Fields of `Bincode`, `Borsh` and `Scale` layouts, or the reason they couldn't be read
+	pub schema: Option<Result<Vec<SchemaField>, String>>,
+	// Unit, encoding and timezone of `Timestamp` and `Date` layouts
+	pub time: TimeFormat,
 }
 
 impl Default for ByteLayout {
@@ -66,6 +271,10 @@ impl Default for ByteLayout {
 			name: "*".to_string(),
 			from: usize::MIN,
 			to: usize::MAX,
+			chained: false,
+			proto: None,
+			schema: None,
+			time: TimeFormat::default(),
 		}
 	}
 }
@@ -74,6 +283,8 @@ impl Default for ByteLayout {
 pub struct LayoutTemplate {
 	pub name: String,
 	pub layout: Vec<ByteLayout>,
+	// Values are decompressed before their layouts decode them
+	pub compression: Option<Compression>,
 }
 
 impl LayoutTemplate {
@@ -81,6 +292,7 @@ impl LayoutTemplate {
 		LayoutTemplate {
 			name: name.to_string(),
 			layout,
+			compression: None,
 		}
 	}
 
@@ -88,9 +300,124 @@ impl LayoutTemplate {
 		self.name = name.to_string();
 	}
 
+	pub fn set_compression(&mut self, compression: Option<Compression>) {
+		self.compression = compression;
+	}
+
 	pub fn push_layout(&mut self, layout: ByteLayout) {
 		self.layout.push(layout);
 	}
+
+	/// Encode a date with the first timestamp or date layout of the template, `None` when the
+	/// template has none
+	pub fn encode_time(&self, text: &str) -> Option<Result<Vec<u8>, String>> {
+		self.layout.iter().find_map(|layout| layout.encode_time(text))
+	}
+
+	/// Decode the fields of the template from raw bytes, as pairs of field name and value
+	pub fn decode(&self, raw: &[u8]) -> Vec<(String, String)> {
+		let nodes = self.decode_tree(raw).into_iter();
+		nodes.map(|node| (node.name.clone(), node.compact_value())).collect()
+	}
+
+	/// Decompress a value with the compression of the template, values are kept as they are
+	/// without one
+	pub fn decompress<'a>(&self, raw: &'a [u8]) -> Result<Cow<'a, [u8]>, String> {
+		match self.compression {
+			Some(compression) => compression.decompress(raw),
+			None => Ok(raw.into()),
+		}
+	}
+
+	// Bytes read by each layout, `None` for layouts starting past the end of the value. Fields
+	// end where their value ends when its size is known, so chained fields can follow varints
+	// and length-prefixed values.
+	fn field_bounds(&self, raw: &[u8]) -> Vec<Option<Range<usize>>> {
+		let mut bounds = vec![];
+		// End of the previous field, where chained fields start
+		let mut cursor = 0;
+		for item in self.layout.iter() {
+			let (start, limit) = match item.chained {
+				true => (cursor, raw.len()),
+				false => (item.from, std::cmp::min(item.to, raw.len())),
+			};
+			if start > limit {
+				bounds.push(None);
+				continue;
+			}
+			let end = match item.field_len(&raw[start..limit]) {
+				Some(len) => std::cmp::min(start.saturating_add(len), limit),
+				None => limit,
+			};
+			bounds.push(Some(start..end));
+			cursor = end;
+		}
+		bounds
+	}
+
+	/// Names of the fields and the bytes of the decompressed value they are read from
+	pub fn field_ranges(&self, raw: &[u8]) -> Vec<(String, Range<usize>)> {
+		let bounds = self.layout.iter().zip(self.field_bounds(raw));
+		bounds.filter_map(|(item, range)| Some((item.name.clone(), range?))).collect()
+	}
+
+	/// Decode the fields of the template from raw bytes, protobuf messages keep their fields
+	pub fn decode_tree(&self, raw: &[u8]) -> Vec<FieldNode> {
+		let raw = match self.decompress(raw) {
+			Ok(bytes) => bytes,
+			Err(err) => {
+				return vec![FieldNode::new("*", &format!("Unable to decompress: {}", err))]
+			}
+		};
+		let mut items: Vec<FieldNode> = vec![];
+		for (item, range) in self.layout.iter().zip(self.field_bounds(&raw)) {
+			let Some(range) = range else {
+				items.push(FieldNode::new(&item.name, "OVERFLOW"));
+				continue;
+			};
+			let slice = raw[range].to_vec();
+			let schema = item.variant.codec().zip(item.schema.as_ref());
+			let node = match (&item.variant, &item.proto, schema) {
+				(LayoutVariant::Protobuf, Some(Err(err)), _) | (_, _, Some((_, Err(err)))) => {
+					FieldNode::new(&item.name, &format!("Unable to load schema: {}", err))
+				}
+				(_, _, Some((codec, Ok(fields)))) => {
+					named_node(&item.name, decode_schema(&slice, fields, codec))
+				}
+				(LayoutVariant::Protobuf, proto, _) => {
+					let proto = proto.as_ref().and_then(|proto| proto.as_ref().ok());
+					let fields = decode_proto(&slice, proto);
+					named_node(&item.name, fields.map(|f| FieldNode::new("", "").with_children(f)))
+				}
+				(LayoutVariant::Timestamp, _, _) => {
+					FieldNode::new(&item.name, &item.time.timestamp(&slice))
+				}
+				(LayoutVariant::Date, _, _) => FieldNode::new(&item.name, &item.time.date(&slice)),
+				(LayoutVariant::Json, _, _) => named_node(&item.name, decode_json(&slice)),
+				(LayoutVariant::Cbor, _, _) => named_node(&item.name, decode_cbor(&slice)),
+				(LayoutVariant::MessagePack, _, _) => {
+					named_node(&item.name, decode_msgpack(&slice))
+				}
+				(_, _, None) if item.variant.codec().is_some() => {
+					FieldNode::new(&item.name, "Unable to parse: layout without fields")
+				}
+				_ => FieldNode::new(&item.name, &slice.from_variant(item.variant.clone())),
+			};
+			items.push(node);
+		}
+		items
+	}
+}
+
+// Decoded tree named after its layout, or the reason it couldn't be decoded
+fn named_node(name: &str, node: Result<FieldNode, String>) -> FieldNode {
+	match node {
+		Ok(mut node) => {
+			node.name = name.to_string();
+			node
+		}
+		Err(err) => FieldNode::new(name, &format!("Unable to parse: {}", err)),
+	}
 }
 
 impl ByteLayout {
@@ -113,6 +440,54 @@ impl ByteLayout {
 		self.to = to;
 		self
 	}
+
+	pub fn chained(&mut self) -> &mut Self {
+		self.chained = true;
+		self
+	}
+
+	pub fn with_proto(&mut self, proto: Result<ProtoLayout, String>) -> &mut Self {
+		self.proto = Some(proto);
+		self
+	}
+
+	pub fn with_schema(&mut self, fields: Result<Vec<SchemaField>, String>) -> &mut Self {
+		self.schema = Some(fields);
+		self
+	}
+
+	pub fn with_time(&mut self, time: TimeFormat) -> &mut Self {
+		self.time = time;
+		self
+	}
+
+	// Size of timestamps and dates is set by their layout
+	fn field_len(&self, raw: &[u8]) -> Option<usize> {
+		match self.variant {
+			LayoutVariant::Timestamp => Some(self.time.timestamp_size()),
+			LayoutVariant::Date => Some(self.time.date_size()),
+			_ => self.variant.field_len(raw),
+		}
+	}
+
+	/// Encode a date as the layout would read it, `None` for layouts which aren't dates
+	pub fn encode_time(&self, text: &str) -> Option<Result<Vec<u8>, String>> {
+		match self.variant {
+			LayoutVariant::Timestamp => Some(self.time.encode_timestamp(text)),
+			LayoutVariant::Date => Some(self.time.encode_date(text)),
+			_ => None,
+		}
+	}
+}
+
+// Read an integer from the first bytes, extra bytes are ignored
+macro_rules! parse_int {
+	($bytes:expr, $default:expr, $ty:ty, $from:ident) => {
+		match $bytes.get(0..std::mem::size_of::<$ty>()) {
+			Some(bytes) => <$ty>::$from(bytes.try_into().unwrap()).to_string(),
+			None => format!("Unable to parse: {}", $default),
+		}
+	};
 }
 
 pub trait FromLayoutVariant {
@@ -130,20 +505,24 @@ impl FromLayoutVariant for Vec<u8> {
 					Err(_) => format!("Unable to parse: {}", default_value),
 				}
 			}
-			LayoutVariant::Int64 => {
-				if self.len() < 8 {
-					return format!("Unable to parse: {}", default_value);
-				}
-				let c = i64::from_be_bytes(self[0..8].try_into().unwrap());
-				c.to_string()
-			}
-			LayoutVariant::Int32 => {
-				if self.len() < 4 {
-					return format!("Unable to parse: {}", default_value);
-				}
-				let c = i32::from_be_bytes(self[0..4].try_into().unwrap());
-				c.to_string()
-			}
+			LayoutVariant::Int8 => parse_int!(self, default_value, i8, from_be_bytes),
+			LayoutVariant::UInt8 => parse_int!(self, default_value, u8, from_be_bytes),
+			LayoutVariant::Int16BE => parse_int!(self, default_value, i16, from_be_bytes),
+			LayoutVariant::Int16LE => parse_int!(self, default_value, i16, from_le_bytes),
+			LayoutVariant::UInt16BE => parse_int!(self, default_value, u16, from_be_bytes),
+			LayoutVariant::UInt16LE => parse_int!(self, default_value, u16, from_le_bytes),
+			LayoutVariant::Int32BE => parse_int!(self, default_value, i32, from_be_bytes),
+			LayoutVariant::Int32LE => parse_int!(self, default_value, i32, from_le_bytes),
+			LayoutVariant::UInt32BE => parse_int!(self, default_value, u32, from_be_bytes),
+			LayoutVariant::UInt32LE => parse_int!(self, default_value, u32, from_le_bytes),
+			LayoutVariant::Int64BE => parse_int!(self, default_value, i64, from_be_bytes),
+			LayoutVariant::Int64LE => parse_int!(self, default_value, i64, from_le_bytes),
+			LayoutVariant::UInt64BE => parse_int!(self, default_value, u64, from_be_bytes),
+			LayoutVariant::UInt64LE => parse_int!(self, default_value, u64, from_le_bytes),
+			LayoutVariant::Int128BE => parse_int!(self, default_value, i128, from_be_bytes),
+			LayoutVariant::Int128LE => parse_int!(self, default_value, i128, from_le_bytes),
+			LayoutVariant::UInt128BE => parse_int!(self, default_value, u128, from_be_bytes),
+			LayoutVariant::UInt128LE => parse_int!(self, default_value, u128, from_le_bytes),
 			LayoutVariant::Float64 => {
 				if self.len() < 8 {
 					return format!("Unable to parse: {}", default_value);
@@ -178,7 +557,46 @@ impl FromLayoutVariant for Vec<u8> {
 				}
 				format!("{:?}", value)
 			}
+			LayoutVariant::Varint => match decode_varint(self) {
+				Some((value, _)) => value.to_string(),
+				None => format!("Unable to parse: {}", default_value),
+			},
+			LayoutVariant::ZigZagVarint => match decode_varint(self) {
+				Some((value, _)) => decode_zigzag(value).to_string(),
+				None => format!("Unable to parse: {}", default_value),
+			},
+			LayoutVariant::LengthPrefixed => match decode_varint(self) {
+				Some((value, len)) if self.len() - len >= value as usize => {
+					let bytes = &self[len..len + value as usize];
+					match std::str::from_utf8(bytes) {
+						Ok(text) => text.to_string(),
+						Err(_) => format!("{:?}", bytes),
+					}
+				}
+				_ => format!("Unable to parse: {}", default_value),
+			},
+			LayoutVariant::Protobuf => match decode_proto(self, None) {
+				Ok(fields) => FieldNode::new("", "").with_children(fields).compact_value(),
+				Err(_) => format!("Unable to parse: {}", default_value),
+			},
+			LayoutVariant::Timestamp => TimeFormat::default().timestamp(self),
+			LayoutVariant::Date => TimeFormat::default().date(self),
+			LayoutVariant::Json => match decode_json(self) {
+				Ok(node) => node.compact_value(),
+				Err(_) => format!("Unable to parse: {}", default_value),
+			},
+			LayoutVariant::Cbor => match decode_cbor(self) {
+				Ok(node) => node.compact_value(),
+				Err(_) => format!("Unable to parse: {}", default_value),
+			},
+			LayoutVariant::MessagePack => match decode_msgpack(self) {
+				Ok(node) => node.compact_value(),
+				Err(_) => format!("Unable to parse: {}", default_value),
+			},
 			LayoutVariant::UuidV4 => {
+				if self.len() < 16 {
+					return format!("Unable to parse: {}", default_value);
+				}
 				let mut vec: [u8; 16] = Default::default();
 				vec.copy_from_slice(&self[0..16]);
 				let uuid = Uuid::from_bytes(vec);
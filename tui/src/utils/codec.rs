@@ -0,0 +1,634 @@
+use serde_json::{json, Value};
+
+use super::{
+	serde_formats::{entry, list_node, map_node, text, Reader, MAX_DEPTH},
+	FieldNode,
+};
+
+/// Formats which don't describe their data, they are decoded with the fields of the layout
+/// and differ by how lengths and enum variants are written
+#[derive(Clone, Copy, Debug)]
+pub enum SchemaCodec {
+	// Default bincode options: u64 lengths and u32 variants
+	Bincode,
+	// u32 lengths and u8 variants
+	Borsh,
+	// Parity SCALE codec: compact lengths and u8 variants
+	Scale,
+}
+
+#[derive(Clone, Debug)]
+pub enum SchemaType {
+	Bool,
+	U8,
+	U16,
+	U32,
+	U64,
+	U128,
+	I8,
+	I16,
+	I32,
+	I64,
+	I128,
+	F32,
+	F64,
+	Char,
+	String,
+	Bytes,
+	// SCALE compact integer
+	Compact,
+	Option(Box<SchemaType>),
+	Vec(Box<SchemaType>),
+	Array(Box<SchemaType>, usize),
+	Map(Box<SchemaType>, Box<SchemaType>),
+	Struct(Vec<SchemaField>),
+	Enum(Vec<SchemaVariant>),
+}
+
+impl SchemaType {
+	// Fewest bytes a value takes with any codec, lengths, tags and variants take at least one
+	fn min_size(&self) -> u64 {
+		match self {
+			SchemaType::Bool | SchemaType::U8 | SchemaType::I8 => 1,
+			SchemaType::U16 | SchemaType::I16 => 2,
+			SchemaType::U32 | SchemaType::I32 | SchemaType::F32 => 4,
+			SchemaType::U64 | SchemaType::I64 | SchemaType::F64 => 8,
+			SchemaType::U128 | SchemaType::I128 => 16,
+			SchemaType::Char
+			| SchemaType::String
+			| SchemaType::Bytes
+			| SchemaType::Compact
+			| SchemaType::Option(_)
+			| SchemaType::Vec(_)
+			| SchemaType::Map(_, _)
+			| SchemaType::Enum(_) => 1,
+			SchemaType::Array(ty, len) => ty.min_size().saturating_mul(*len as u64),
+			SchemaType::Struct(fields) => {
+				fields.iter().fold(0, |size, field| size.saturating_add(field.ty.min_size()))
+			}
+		}
+	}
+}
+
+// Elements are read as many times as a length read from the value, elements without bytes
+// would be read endlessly from a corrupted length
+fn sized(ty: SchemaType, schema: &str) -> Result<SchemaType, String> {
+	match ty.min_size() {
+		0 => Err(format!("elements of {} take no bytes", schema)),
+		_ => Ok(ty),
+	}
+}
+
+#[derive(Clone, Debug)]
+pub struct SchemaField {
+	pub name: String,
+	pub ty: SchemaType,
+}
+
+#[derive(Clone, Debug)]
+pub struct SchemaVariant {
+	pub name: String,
+	pub index: u32,
+	// Unit variants have no value
+	pub ty: Option<SchemaType>,
+}
+
+// Split the arguments of a generic type on commas outside of nested types
+fn type_arguments(ty: &str) -> Vec<&str> {
+	let mut arguments = vec![];
+	let (mut depth, mut start) = (0, 0);
+	for (index, c) in ty.char_indices() {
+		match c {
+			'<' | '[' => depth += 1,
+			'>' | ']' => depth -= 1,
+			',' if depth == 0 => {
+				arguments.push(ty[start..index].trim());
+				start = index + 1;
+			}
+			_ => {}
+		}
+	}
+	arguments.push(ty[start..].trim());
+	arguments
+}
+
+// Types are written as in Rust, `struct` takes the `fields` of the layout and `enum` its
+// `variants`, e.g. `option<struct>`
+fn schema_type(ty: &str, layout: &Value) -> Result<SchemaType, String> {
+	let ty = ty.trim();
+	let inner = |prefix: &str| ty.strip_prefix(prefix).and_then(|ty| ty.strip_suffix('>'));
+	if let Some(inner) = inner("option<") {
+		return Ok(SchemaType::Option(Box::new(schema_type(inner, layout)?)));
+	}
+	if let Some(inner) = inner("vec<") {
+		return Ok(SchemaType::Vec(Box::new(sized(schema_type(inner, layout)?, ty)?)));
+	}
+	if let Some(inner) = inner("map<") {
+		let arguments = type_arguments(inner);
+		let [key, value] = arguments.as_slice() else {
+			return Err(format!("invalid map {}", ty));
+		};
+		let (key, value) = (schema_type(key, layout)?, schema_type(value, layout)?);
+		return match entry_size(&key, &value) {
+			0 => Err(format!("entries of {} take no bytes", ty)),
+			_ => Ok(SchemaType::Map(Box::new(key), Box::new(value))),
+		};
+	}
+	if inner("compact<").is_some() {
+		return Ok(SchemaType::Compact);
+	}
+	let array = ty.strip_prefix('[').and_then(|ty| ty.strip_suffix(']'));
+	if let Some((inner, len)) = array.and_then(|ty| ty.rsplit_once(';')) {
+		let len = len.trim().parse().map_err(|_| format!("invalid array length in {}", ty))?;
+		return Ok(SchemaType::Array(Box::new(sized(schema_type(inner, layout)?, ty)?), len));
+	}
+	let ty = match ty {
+		"bool" => SchemaType::Bool,
+		"u8" => SchemaType::U8,
+		"u16" => SchemaType::U16,
+		"u32" => SchemaType::U32,
+		"u64" | "usize" => SchemaType::U64,
+		"u128" => SchemaType::U128,
+		"i8" => SchemaType::I8,
+		"i16" => SchemaType::I16,
+		"i32" => SchemaType::I32,
+		"i64" | "isize" => SchemaType::I64,
+		"i128" => SchemaType::I128,
+		"f32" => SchemaType::F32,
+		"f64" => SchemaType::F64,
+		"char" => SchemaType::Char,
+		"string" | "String" => SchemaType::String,
+		"bytes" => SchemaType::Bytes,
+		"compact" => SchemaType::Compact,
+		"struct" => {
+			let fields = layout.get("fields").ok_or("struct without fields")?;
+			SchemaType::Struct(load_schema_fields(fields)?)
+		}
+		"enum" => {
+			let variants = layout.get("variants").ok_or("enum without variants")?;
+			SchemaType::Enum(load_schema_variants(variants)?)
+		}
+		_ => return Err(format!("unknown type {}", ty)),
+	};
+	Ok(ty)
+}
+
+// Type of a field or a variant, nested `fields` without a type are a struct
+fn value_type(layout: &Value) -> Option<Result<SchemaType, String>> {
+	match (layout.get("type").and_then(|ty| ty.as_str()), layout.get("fields")) {
+		(Some(ty), _) => Some(schema_type(ty, layout)),
+		(None, Some(_)) => Some(schema_type("struct", layout)),
+		(None, None) => None,
+	}
+}
+
+fn name(layout: &Value) -> Result<&str, String> {
+	layout.get("name").and_then(|name| name.as_str()).ok_or("missing name".to_string())
+}
+
+/// Read the fields of a layout, as objects with a `name` and a `type`
+pub fn load_schema_fields(fields: &Value) -> Result<Vec<SchemaField>, String> {
+	let fields = fields.as_array().ok_or("fields must be an array")?;
+	let mut schema = vec![];
+	for field in fields.iter() {
+		let name = name(field)?;
+		let ty = value_type(field).unwrap_or(Err("missing type".to_string()));
+		schema.push(SchemaField {
+			name: name.to_string(),
+			ty: ty.map_err(|err| format!("{}: {}", name, err))?,
+		});
+	}
+	Ok(schema)
+}
+
+// Variants are numbered in order unless they set their `index`
+fn load_schema_variants(variants: &Value) -> Result<Vec<SchemaVariant>, String> {
+	let variants = variants.as_array().ok_or("variants must be an array")?;
+	let mut schema = vec![];
+	for (position, variant) in variants.iter().enumerate() {
+		let name = name(variant)?;
+		let index = variant.get("index").and_then(|index| index.as_u64());
+		let ty = value_type(variant).transpose().map_err(|err| format!("{}: {}", name, err))?;
+		schema.push(SchemaVariant {
+			name: name.to_string(),
+			index: index.unwrap_or(position as u64) as u32,
+			ty,
+		});
+	}
+	Ok(schema)
+}
+
+// Type as it is written in a layout, the fields or variants of a struct or an enum are set on
+// the layout
+fn type_json(ty: &SchemaType, layout: &mut Value) -> String {
+	let ty = match ty {
+		SchemaType::Bool => "bool",
+		SchemaType::U8 => "u8",
+		SchemaType::U16 => "u16",
+		SchemaType::U32 => "u32",
+		SchemaType::U64 => "u64",
+		SchemaType::U128 => "u128",
+		SchemaType::I8 => "i8",
+		SchemaType::I16 => "i16",
+		SchemaType::I32 => "i32",
+		SchemaType::I64 => "i64",
+		SchemaType::I128 => "i128",
+		SchemaType::F32 => "f32",
+		SchemaType::F64 => "f64",
+		SchemaType::Char => "char",
+		SchemaType::String => "string",
+		SchemaType::Bytes => "bytes",
+		SchemaType::Compact => "compact",
+		SchemaType::Option(ty) => return format!("option<{}>", type_json(ty, layout)),
+		SchemaType::Vec(ty) => return format!("vec<{}>", type_json(ty, layout)),
+		SchemaType::Array(ty, len) => return format!("[{}; {}]", type_json(ty, layout), len),
+		SchemaType::Map(key, value) => {
+			let key = type_json(key, layout);
+			return format!("map<{}, {}>", key, type_json(value, layout));
+		}
+		SchemaType::Struct(fields) => {
+			layout["fields"] = schema_fields_json(fields);
+			"struct"
+		}
+		SchemaType::Enum(variants) => {
+			let variants = variants.iter().map(|variant| {
+				let mut json = json!({ "name": variant.name, "index": variant.index });
+				if let Some(ty) = &variant.ty {
+					json["type"] = type_json(ty, &mut json).into();
+				}
+				json
+			});
+			layout["variants"] = variants.collect();
+			"enum"
+		}
+	};
+	ty.to_string()
+}
+
+/// Fields as they are read by `load_schema_fields`
+pub fn schema_fields_json(fields: &[SchemaField]) -> Value {
+	let fields = fields.iter().map(|field| {
+		let mut json = json!({ "name": field.name });
+		json["type"] = type_json(&field.ty, &mut json).into();
+		json
+	});
+	fields.collect()
+}
+
+// Compact integers keep their mode in the two lowest bits of the first byte
+fn decode_compact(reader: &mut Reader) -> Result<u128, String> {
+	let first = reader.peek().ok_or("truncated value")?;
+	let value = match first & 0b11 {
+		0 => (reader.byte()? >> 2) as u128,
+		1 => (u16::from_le_bytes(reader.array()?) >> 2) as u128,
+		2 => (u32::from_le_bytes(reader.array()?) >> 2) as u128,
+		_ => {
+			reader.byte()?;
+			let len = (first >> 2) as u64 + 4;
+			if len > 16 {
+				return Err("compact integer wider than 128 bits".to_string());
+			}
+			let bytes = reader.take(len)?;
+			bytes.iter().rev().fold(0, |value, byte| value << 8 | *byte as u128)
+		}
+	};
+	Ok(value)
+}
+
+fn entry_size(key: &SchemaType, value: &SchemaType) -> u64 {
+	key.min_size().saturating_add(value.min_size())
+}
+
+// A length is read from the value, more elements than the bytes left can hold is a corrupted
+// length rather than a truncated value
+fn check_len(reader: &Reader, len: u64, size: u64) -> Result<(), String> {
+	let remaining = reader.remaining() as u64;
+	match len > remaining / size.max(1) {
+		true => Err(format!("length {} longer than the {} bytes left", len, remaining)),
+		false => Ok(()),
+	}
+}
+
+impl SchemaCodec {
+	fn len(self, reader: &mut Reader) -> Result<u64, String> {
+		match self {
+			SchemaCodec::Bincode => Ok(u64::from_le_bytes(reader.array()?)),
+			SchemaCodec::Borsh => Ok(u32::from_le_bytes(reader.array()?) as u64),
+			SchemaCodec::Scale => {
+				let len = decode_compact(reader)?;
+				u64::try_from(len).map_err(|_| "invalid length".to_string())
+			}
+		}
+	}
+
+	fn variant(self, reader: &mut Reader) -> Result<u32, String> {
+		match self {
+			SchemaCodec::Bincode => Ok(u32::from_le_bytes(reader.array()?)),
+			SchemaCodec::Borsh | SchemaCodec::Scale => Ok(reader.byte()? as u32),
+		}
+	}
+
+	// Bincode writes chars as UTF-8, others as a u32 code point
+	fn char(self, reader: &mut Reader) -> Result<char, String> {
+		if let SchemaCodec::Borsh | SchemaCodec::Scale = self {
+			let value = u32::from_le_bytes(reader.array()?);
+			return char::from_u32(value).ok_or(format!("invalid char {}", value));
+		}
+		let len = match reader.peek().ok_or("truncated value")? {
+			0x00..=0x7f => 1,
+			0xc0..=0xdf => 2,
+			0xe0..=0xef => 3,
+			_ => 4,
+		};
+		let bytes = reader.take(len)?;
+		let text = std::str::from_utf8(bytes).map_err(|_| "invalid char".to_string())?;
+		text.chars().next().ok_or("invalid char".to_string())
+	}
+
+	fn fields(
+		self,
+		reader: &mut Reader,
+		fields: &[SchemaField],
+		depth: usize,
+	) -> Result<Vec<FieldNode>, String> {
+		let mut nodes = vec![];
+		for field in fields.iter() {
+			nodes.push(entry(field.name.clone(), self.value(reader, &field.ty, depth + 1)?));
+		}
+		Ok(nodes)
+	}
+
+	fn items(
+		self,
+		reader: &mut Reader,
+		ty: &SchemaType,
+		len: u64,
+		depth: usize,
+	) -> Result<FieldNode, String> {
+		check_len(reader, len, ty.min_size())?;
+		let mut items = vec![];
+		for _ in 0..len {
+			items.push(self.value(reader, ty, depth + 1)?);
+		}
+		Ok(list_node(items))
+	}
+
+	// Variants with a single value show it next to their name
+	fn enumeration(
+		self,
+		reader: &mut Reader,
+		variants: &[SchemaVariant],
+		depth: usize,
+	) -> Result<FieldNode, String> {
+		let index = self.variant(reader)?;
+		let variant = variants.iter().find(|variant| variant.index == index);
+		let variant = variant.ok_or(format!("unknown variant {}", index))?;
+		let mut node = match &variant.ty {
+			Some(ty) => self.value(reader, ty, depth + 1)?,
+			None => return Ok(FieldNode::new("", &variant.name)),
+		};
+		node.value = match node.children.is_empty() {
+			true => format!("{}({})", variant.name, node.value),
+			false => variant.name.clone(),
+		};
+		Ok(node)
+	}
+
+	fn value(
+		self,
+		reader: &mut Reader,
+		ty: &SchemaType,
+		depth: usize,
+	) -> Result<FieldNode, String> {
+		if depth > MAX_DEPTH {
+			return Err("nested too deep".to_string());
+		}
+		let value = match ty {
+			SchemaType::Bool => match reader.byte()? {
+				0 => "false".to_string(),
+				1 => "true".to_string(),
+				byte => return Err(format!("invalid bool {}", byte)),
+			},
+			SchemaType::U8 => reader.byte()?.to_string(),
+			SchemaType::U16 => u16::from_le_bytes(reader.array()?).to_string(),
+			SchemaType::U32 => u32::from_le_bytes(reader.array()?).to_string(),
+			SchemaType::U64 => u64::from_le_bytes(reader.array()?).to_string(),
+			SchemaType::U128 => u128::from_le_bytes(reader.array()?).to_string(),
+			SchemaType::I8 => i8::from_le_bytes(reader.array()?).to_string(),
+			SchemaType::I16 => i16::from_le_bytes(reader.array()?).to_string(),
+			SchemaType::I32 => i32::from_le_bytes(reader.array()?).to_string(),
+			SchemaType::I64 => i64::from_le_bytes(reader.array()?).to_string(),
+			SchemaType::I128 => i128::from_le_bytes(reader.array()?).to_string(),
+			SchemaType::F32 => f32::from_le_bytes(reader.array()?).to_string(),
+			SchemaType::F64 => f64::from_le_bytes(reader.array()?).to_string(),
+			SchemaType::Char => format!("{:?}", self.char(reader)?),
+			SchemaType::String => {
+				let len = self.len(reader)?;
+				text(reader.take(len)?)
+			}
+			SchemaType::Bytes => {
+				let len = self.len(reader)?;
+				format!("{:?}", reader.take(len)?)
+			}
+			SchemaType::Compact => decode_compact(reader)?.to_string(),
+			SchemaType::Option(ty) => match reader.byte()? {
+				0 => "None".to_string(),
+				1 => return self.value(reader, ty, depth + 1),
+				byte => return Err(format!("invalid option tag {}", byte)),
+			},
+			SchemaType::Vec(ty) => {
+				let len = self.len(reader)?;
+				return self.items(reader, ty, len, depth);
+			}
+			// Byte arrays like hashes and keys are kept on a single line
+			SchemaType::Array(ty, len) => match ty.as_ref() {
+				SchemaType::U8 => format!("{:?}", reader.take(*len as u64)?),
+				_ => return self.items(reader, ty, *len as u64, depth),
+			},
+			SchemaType::Map(key, value) => {
+				let len = self.len(reader)?;
+				check_len(reader, len, entry_size(key, value))?;
+				let mut entries = vec![];
+				for _ in 0..len {
+					let name = self.value(reader, key, depth + 1)?.compact_value();
+					// String keys are shown without quotes
+					let name = match key.as_ref() {
+						SchemaType::String => name.trim_matches('"').to_string(),
+						_ => name,
+					};
+					entries.push(entry(name, self.value(reader, value, depth + 1)?));
+				}
+				return Ok(map_node(entries));
+			}
+			SchemaType::Struct(fields) => {
+				return Ok(map_node(self.fields(reader, fields, depth)?));
+			}
+			SchemaType::Enum(variants) => return self.enumeration(reader, variants, depth),
+		};
+		Ok(FieldNode::new("", &value))
+	}
+}
+
+/// Decode a struct of the fields with the codec, bytes after the last field are ignored
+pub fn decode_schema(
+	raw: &[u8],
+	fields: &[SchemaField],
+	codec: SchemaCodec,
+) -> Result<FieldNode, String> {
+	let mut reader = Reader::new(raw);
+	Ok(map_node(codec.fields(&mut reader, fields, 0)?))
+}
+
+#[cfg(test)]
+mod test {
+	use std::collections::BTreeMap;
+
+	use borsh::BorshSerialize;
+	use parity_scale_codec::{Compact, Encode};
+	use serde::Serialize;
+	use serde_json::json;
+
+	use super::{decode_schema, load_schema_fields, schema_fields_json, SchemaCodec, SchemaField};
+
+	#[derive(Serialize, BorshSerialize, Encode)]
+	struct Account {
+		id: u64,
+		name: String,
+		balance: i32,
+		owner: Option<[u8; 4]>,
+		scores: Vec<u16>,
+		kind: Kind,
+		labels: BTreeMap<String, u8>,
+		active: bool,
+	}
+
+	#[derive(Serialize, BorshSerialize, Encode)]
+	enum Kind {
+		#[allow(dead_code)]
+		Closed,
+		Amount(u32),
+	}
+
+	const DECODED: &str = r#"{id: 7, name: "ada", balance: -3, owner: [1, 2, 3, 4], scores: [1, 2], kind: Amount(9), labels: {a: 1}, active: true}"#;
+
+	fn account() -> Account {
+		Account {
+			id: 7,
+			name: "ada".to_string(),
+			balance: -3,
+			owner: Some([1, 2, 3, 4]),
+			scores: vec![1, 2],
+			kind: Kind::Amount(9),
+			labels: BTreeMap::from([("a".to_string(), 1)]),
+			active: true,
+		}
+	}
+
+	fn schema() -> Vec<SchemaField> {
+		let fields = json!([
+			{"name": "id", "type": "u64"},
+			{"name": "name", "type": "string"},
+			{"name": "balance", "type": "i32"},
+			{"name": "owner", "type": "option<[u8; 4]>"},
+			{"name": "scores", "type": "vec<u16>"},
+			{"name": "kind", "type": "enum", "variants": [{"name": "Closed"}, {"name": "Amount", "type": "u32"}]},
+			{"name": "labels", "type": "map<string, u8>"},
+			{"name": "active", "type": "bool"},
+		]);
+		load_schema_fields(&fields).unwrap()
+	}
+
+	fn encoded() -> [(Vec<u8>, SchemaCodec); 3] {
+		[
+			(bincode::serialize(&account()).unwrap(), SchemaCodec::Bincode),
+			(account().try_to_vec().unwrap(), SchemaCodec::Borsh),
+			(account().encode(), SchemaCodec::Scale),
+		]
+	}
+
+	#[test]
+	fn should_decode_codecs() {
+		for (raw, codec) in encoded() {
+			let node = decode_schema(&raw, &schema(), codec).unwrap();
+			assert_eq!(node.compact_value(), DECODED, "{:?}", codec);
+		}
+	}
+
+	#[test]
+	fn should_decode_scale_compact() {
+		let fields = load_schema_fields(&json!([{"name": "a", "type": "compact"}])).unwrap();
+		for value in [0, 63, 64, 1 << 14, 1 << 30, u64::MAX as u128, u128::MAX] {
+			let raw = Compact(value).encode();
+			let node = decode_schema(&raw, &fields, SchemaCodec::Scale).unwrap();
+			assert_eq!(node.compact_value(), format!("{{a: {}}}", value));
+		}
+	}
+
+	#[test]
+	fn should_reject_truncated_values() {
+		for (raw, codec) in encoded() {
+			for len in 0..raw.len() {
+				assert!(decode_schema(&raw[..len], &schema(), codec).is_err(), "{:?}", codec);
+			}
+		}
+		let fields = json!([
+			{"name": "kind", "type": "enum", "variants": [{"name": "Closed"}]},
+			{"name": "active", "type": "bool"},
+		]);
+		let fields = load_schema_fields(&fields).unwrap();
+		assert!(decode_schema(&[0, 1], &fields, SchemaCodec::Scale).is_ok());
+		// Unknown variant and invalid bool
+		assert!(decode_schema(&[1, 1], &fields, SchemaCodec::Scale).is_err());
+		assert!(decode_schema(&[0, 2], &fields, SchemaCodec::Scale).is_err());
+	}
+
+	#[test]
+	fn should_reject_elements_without_bytes() {
+		let fields =
+			|ty: &str| load_schema_fields(&json!([{"name": "a", "type": ty, "fields": []}]));
+		assert!(fields("vec<struct>").is_err());
+		assert!(fields("[[u8; 0]; 4]").is_err());
+		assert!(fields("map<struct, struct>").is_err());
+		assert!(fields("map<u8, struct>").is_ok());
+	}
+
+	#[test]
+	fn should_reject_lengths_past_the_value() {
+		let fields = load_schema_fields(&json!([{"name": "a", "type": "vec<u32>"}])).unwrap();
+		let raw = [u64::MAX.to_le_bytes().as_slice(), &[0; 8]].concat();
+		assert!(decode_schema(&raw, &fields, SchemaCodec::Bincode).is_err());
+
+		let fields = load_schema_fields(&json!([{"name": "a", "type": "map<u8, u8>"}])).unwrap();
+		let raw = [u32::MAX.to_le_bytes().as_slice(), &[1, 2]].concat();
+		assert!(decode_schema(&raw, &fields, SchemaCodec::Borsh).is_err());
+	}
+
+	#[test]
+	fn should_write_fields_as_loaded() {
+		let fields = json!([
+			{ "name": "id", "type": "u64" },
+			{ "name": "balance", "type": "option<compact>" },
+			{ "name": "tags", "type": "map<string, [u8; 4]>" },
+			{ "name": "owner", "fields": [{ "name": "name", "type": "string" }] },
+			{ "name": "state", "type": "vec<enum>", "variants": [
+				{ "name": "Idle" },
+				{ "name": "Busy", "index": 4, "type": "u32" }
+			] }
+		]);
+		let json = schema_fields_json(&load_schema_fields(&fields).unwrap());
+		assert_eq!(
+			json,
+			json!([
+				{ "name": "id", "type": "u64" },
+				{ "name": "balance", "type": "option<compact>" },
+				{ "name": "tags", "type": "map<string, [u8; 4]>" },
+				{ "name": "owner", "type": "struct", "fields": [{ "name": "name", "type": "string" }] },
+				{ "name": "state", "type": "vec<enum>", "variants": [
+					{ "name": "Idle", "index": 0 },
+					{ "name": "Busy", "index": 4, "type": "u32" }
+				] }
+			])
+		);
+		assert_eq!(schema_fields_json(&load_schema_fields(&json).unwrap()), json);
+	}
+}
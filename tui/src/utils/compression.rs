@@ -0,0 +1,228 @@
+use std::{borrow::Cow, fmt, io::Read};
+
+// Streams are cut after this many bytes and larger blocks are refused, as a few compressed
+// bytes can expand to gigabytes
+const MAX_DECOMPRESSED_LEN: u64 = 64 * 1024 * 1024;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+const LZ4_FRAME_MAGIC: &[u8] = &[0x04, 0x22, 0x4d, 0x18];
+const SNAPPY_FRAME_MAGIC: &[u8] = b"\xff\x06\x00\x00sNaPpY";
+
+/// Compression of values, applied before the layouts of a template decode them
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+	// Detected from the magic bytes of the value, values without known magic bytes are kept
+	Auto,
+	Gzip,
+	Zlib,
+	// Raw deflate stream, without header
+	Deflate,
+	Zstd,
+	// LZ4 frame, or a block prefixed with its decompressed size as a little endian u32
+	Lz4,
+	// Snappy frame, or a raw block
+	Snappy,
+}
+
+impl fmt::Display for Compression {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let name = match self {
+			Compression::Auto => "auto",
+			Compression::Gzip => "gzip",
+			Compression::Zlib => "zlib",
+			Compression::Deflate => "deflate",
+			Compression::Zstd => "zstd",
+			Compression::Lz4 => "lz4",
+			Compression::Snappy => "snappy",
+		};
+		write!(f, "{}", name)
+	}
+}
+
+impl Compression {
+	pub fn from_string(s: &str) -> Option<Compression> {
+		let compression = match s.to_lowercase().as_str() {
+			"auto" => Compression::Auto,
+			"gzip" => Compression::Gzip,
+			"zlib" => Compression::Zlib,
+			"deflate" => Compression::Deflate,
+			"zstd" => Compression::Zstd,
+			"lz4" => Compression::Lz4,
+			"snappy" => Compression::Snappy,
+			_ => return None,
+		};
+		Some(compression)
+	}
+
+	/// Compression found from the magic bytes of a value. Zlib is recognized by its header
+	/// checksum, raw deflate streams and blocks have no magic bytes
+	pub fn detect(raw: &[u8]) -> Option<Compression> {
+		match raw {
+			_ if raw.starts_with(GZIP_MAGIC) => Some(Compression::Gzip),
+			_ if raw.starts_with(ZSTD_MAGIC) => Some(Compression::Zstd),
+			_ if raw.starts_with(LZ4_FRAME_MAGIC) => Some(Compression::Lz4),
+			_ if raw.starts_with(SNAPPY_FRAME_MAGIC) => Some(Compression::Snappy),
+			[cmf, flg, ..] if is_zlib_header(*cmf, *flg) => Some(Compression::Zlib),
+			_ => None,
+		}
+	}
+
+	/// Decompress a value, `Auto` keeps values which don't look compressed
+	pub fn decompress<'a>(&self, raw: &'a [u8]) -> Result<Cow<'a, [u8]>, String> {
+		let bytes = match self {
+			// Two bytes of zlib header are often found in plain values
+			Compression::Auto => match Compression::detect(raw) {
+				Some(Compression::Zlib) => {
+					let bytes = Compression::Zlib.decompress(raw);
+					return Ok(bytes.unwrap_or(Cow::Borrowed(raw)));
+				}
+				Some(compression) => return compression.decompress(raw),
+				None => return Ok(Cow::Borrowed(raw)),
+			},
+			Compression::Gzip => read_all(flate2::read::GzDecoder::new(raw))?,
+			Compression::Zlib => read_all(flate2::read::ZlibDecoder::new(raw))?,
+			Compression::Deflate => read_all(flate2::read::DeflateDecoder::new(raw))?,
+			Compression::Zstd => {
+				let mut source = raw;
+				let decoder = ruzstd::StreamingDecoder::new(&mut source);
+				read_all(decoder.map_err(|err| err.to_string())?)?
+			}
+			Compression::Lz4 if raw.starts_with(LZ4_FRAME_MAGIC) => {
+				read_all(lz4_flex::frame::FrameDecoder::new(raw))?
+			}
+			Compression::Lz4 => {
+				let (len, _) =
+					lz4_flex::block::uncompressed_size(raw).map_err(|e| e.to_string())?;
+				check_len(len as u64)?;
+				lz4_flex::decompress_size_prepended(raw).map_err(|err| err.to_string())?
+			}
+			Compression::Snappy if raw.starts_with(SNAPPY_FRAME_MAGIC) => {
+				read_all(snap::read::FrameDecoder::new(raw))?
+			}
+			Compression::Snappy => {
+				let len = snap::raw::decompress_len(raw).map_err(|err| err.to_string())?;
+				check_len(len as u64)?;
+				snap::raw::Decoder::new().decompress_vec(raw).map_err(|err| err.to_string())?
+			}
+		};
+		Ok(Cow::Owned(bytes))
+	}
+}
+
+// Deflate method with a window up to 32K, no preset dictionary and a valid checksum
+fn is_zlib_header(cmf: u8, flg: u8) -> bool {
+	cmf & 0x0f == 8
+		&& cmf >> 4 <= 7
+		&& flg & 0x20 == 0
+		&& (cmf as u16 * 256 + flg as u16).is_multiple_of(31)
+}
+
+fn check_len(len: u64) -> Result<(), String> {
+	match len > MAX_DECOMPRESSED_LEN {
+		true => Err(format!("decompressed value larger than {} bytes", MAX_DECOMPRESSED_LEN)),
+		false => Ok(()),
+	}
+}
+
+// A byte past the limit is read, so a stream cut at the limit isn't taken for the whole value
+fn read_all<R: Read>(reader: R) -> Result<Vec<u8>, String> {
+	let mut bytes = vec![];
+	let limit = MAX_DECOMPRESSED_LEN + 1;
+	reader.take(limit).read_to_end(&mut bytes).map_err(|err| err.to_string())?;
+	check_len(bytes.len() as u64)?;
+	Ok(bytes)
+}
+
+#[cfg(test)]
+mod test {
+	use std::io::Write;
+
+	use flate2::{
+		write::{DeflateEncoder, GzEncoder, ZlibEncoder},
+		Compression as Level,
+	};
+
+	use super::{Compression, MAX_DECOMPRESSED_LEN};
+
+	const VALUE: &[u8] = b"{\"name\": \"ada\", \"tags\": [\"a\", \"a\", \"a\", \"a\", \"a\"]}";
+
+	fn finish<W: Write>(
+		mut encoder: W,
+		finish: impl FnOnce(W) -> std::io::Result<Vec<u8>>,
+	) -> Vec<u8> {
+		encoder.write_all(VALUE).unwrap();
+		finish(encoder).unwrap()
+	}
+
+	fn compressed() -> Vec<(Compression, Vec<u8>)> {
+		let mut lz4_frame = lz4_flex::frame::FrameEncoder::new(vec![]);
+		lz4_frame.write_all(VALUE).unwrap();
+		let mut snappy_frame = snap::write::FrameEncoder::new(vec![]);
+		snappy_frame.write_all(VALUE).unwrap();
+		vec![
+			(
+				Compression::Gzip,
+				finish(GzEncoder::new(vec![], Level::default()), GzEncoder::finish),
+			),
+			(
+				Compression::Zlib,
+				finish(ZlibEncoder::new(vec![], Level::default()), ZlibEncoder::finish),
+			),
+			(
+				Compression::Deflate,
+				finish(DeflateEncoder::new(vec![], Level::default()), DeflateEncoder::finish),
+			),
+			(Compression::Zstd, zstd::encode_all(VALUE, 3).unwrap()),
+			(Compression::Lz4, lz4_frame.finish().unwrap()),
+			(Compression::Lz4, lz4_flex::compress_prepend_size(VALUE)),
+			(Compression::Snappy, snappy_frame.into_inner().unwrap()),
+			(Compression::Snappy, snap::raw::Encoder::new().compress_vec(VALUE).unwrap()),
+		]
+	}
+
+	#[test]
+	fn should_decompress_encoders() {
+		for (compression, raw) in compressed() {
+			assert_eq!(compression.decompress(&raw).unwrap().as_ref(), VALUE, "{}", compression);
+		}
+	}
+
+	#[test]
+	fn should_detect_magic_bytes() {
+		for (compression, raw) in compressed() {
+			let detected = Compression::Auto.decompress(&raw).unwrap();
+			match compression {
+				// Raw deflate streams and blocks have no magic bytes, they are kept
+				Compression::Deflate => assert_eq!(detected.as_ref(), raw.as_slice()),
+				_ if Compression::detect(&raw).is_none() => {
+					assert_eq!(detected.as_ref(), raw.as_slice())
+				}
+				_ => assert_eq!(detected.as_ref(), VALUE, "{}", compression),
+			}
+		}
+		// Plain values starting like a zlib header are kept
+		assert_eq!(Compression::Auto.decompress(b"x^plain").unwrap().as_ref(), b"x^plain");
+		assert_eq!(Compression::Auto.decompress(VALUE).unwrap().as_ref(), VALUE);
+	}
+
+	#[test]
+	fn should_reject_malformed_values() {
+		for (compression, raw) in compressed() {
+			let truncated = &raw[..raw.len() / 2];
+			let decompressed = compression.decompress(truncated);
+			assert!(decompressed.map(|bytes| bytes != VALUE).unwrap_or(true), "{}", compression);
+		}
+		assert!(Compression::Gzip.decompress(VALUE).is_err());
+		assert!(Compression::Zstd.decompress(b"\x28\xb5\x2f\xfd\xff").is_err());
+		// Blocks whose size is larger than a value can decompress to are refused
+		let size = (MAX_DECOMPRESSED_LEN as u32 + 1).to_le_bytes();
+		assert!(Compression::Lz4.decompress(&[&size[..], b"\x00"].concat()).is_err());
+		// Streams are cut one byte past the limit
+		let zeros = vec![0; MAX_DECOMPRESSED_LEN as usize + 1];
+		let mut gzip = GzEncoder::new(vec![], Level::fast());
+		gzip.write_all(&zeros).unwrap();
+		let err = Compression::Gzip.decompress(&gzip.finish().unwrap()).err().unwrap();
+		assert!(err.starts_with("decompressed value larger than"));
+	}
+}
@@ -0,0 +1,456 @@
+use std::ops::Range;
+
+use db::KeyValuePair;
+use serde_json::Value;
+
+use super::{
+	decode_cbor, decode_msgpack, ByteLayout, Compression, LayoutTemplate, LayoutVariant,
+	TimeFormat, TimeUnit,
+};
+
+/// Pairs read to suggest templates, spread over the whole scan
+pub const SUGGEST_SAMPLES: usize = 256;
+
+// Bytes ending a text segment of keys such as `user:42` or `tenant\0event`
+const SEPARATORS: &[u8] = b":\0/|#";
+
+// Integers are taken for timestamps when they are all between 2000 and 2100
+const TIMESTAMP_SECONDS: Range<i128> = 946_684_800..4_102_444_800;
+
+// Bytes read by a suggested layout, from the same offset of every sample
+struct Segment {
+	name: &'static str,
+	variant: LayoutVariant,
+	// Length of the segment, `None` when it reads the rest of the samples
+	len: Option<usize>,
+	// A separator byte follows the segment, it is left out of every layout
+	separated: bool,
+	time: Option<TimeFormat>,
+}
+
+impl Segment {
+	fn new(name: &'static str, variant: LayoutVariant, len: Option<usize>) -> Self {
+		Segment {
+			name,
+			variant,
+			len,
+			separated: false,
+			time: None,
+		}
+	}
+}
+
+/// Samples spread evenly over the pairs, `SUGGEST_SAMPLES` at most
+pub fn sample_pairs(pairs: &[KeyValuePair]) -> Vec<&KeyValuePair> {
+	let step = std::cmp::max(pairs.len() / SUGGEST_SAMPLES, 1);
+	pairs.iter().step_by(step).take(SUGGEST_SAMPLES).collect()
+}
+
+/// Template decoding the samples of a keyspace, keys or values. Compressed samples and JSON,
+/// CBOR or MessagePack documents are read as a whole, other samples are split into text
+/// ending with a separator, UUIDs, timestamps and integers of common widths.
+pub fn suggest_template(name: &str, samples: &[&[u8]]) -> LayoutTemplate {
+	let mut template = LayoutTemplate::new(name, vec![]);
+	// A compression is kept when every sample is compressed the same way
+	let detected: Vec<_> = samples.iter().map(|sample| Compression::detect(sample)).collect();
+	let compression = match detected.first() {
+		Some(Some(first)) if detected.iter().all(|c| c == &Some(*first)) => Some(*first),
+		_ => None,
+	};
+	let decompressed = compression.and_then(|compression| {
+		let samples = samples.iter().map(|sample| compression.decompress(sample));
+		samples.collect::<Result<Vec<_>, _>>().ok()
+	});
+	let layouts = match &decompressed {
+		Some(decompressed) => {
+			template.set_compression(compression);
+			let samples: Vec<&[u8]> = decompressed.iter().map(|sample| sample.as_ref()).collect();
+			suggest_layouts(&samples)
+		}
+		None => suggest_layouts(samples),
+	};
+	for layout in layouts {
+		template.push_layout(layout);
+	}
+	template
+}
+
+/// Layouts of a template on a line, e.g. `text[0..4] String, id[5..] UInt64BE`
+pub fn describe_template(template: &LayoutTemplate) -> String {
+	let layouts = template.layout.iter().map(|layout| {
+		let to = match layout.to {
+			usize::MAX => "".to_string(),
+			to => to.to_string(),
+		};
+		format!("{}[{}..{}] {}", layout.name, layout.from, to, layout.variant.to_string())
+	});
+	let layouts = layouts.collect::<Vec<_>>().join(", ");
+	match template.compression {
+		Some(compression) => format!("{} {}", compression, layouts),
+		None => layouts,
+	}
+}
+
+/// Templates formatting the keys and values of a Redb table of a type like `Table<u64, &str>`,
+/// which are stored as big endian integers, UTF-8 strings and bytes. `None` for byte tables
+pub fn table_templates(table_type: &str) -> Option<(LayoutTemplate, LayoutTemplate)> {
+	let types = table_type.split_once('<')?.1.strip_suffix('>')?;
+	// Types of pairs hold a comma as well, the key type ends at the first one outside of them
+	let mut depth = 0;
+	let comma = types.char_indices().find(|(_, c)| {
+		match c {
+			'(' => depth += 1,
+			')' => depth -= 1,
+			_ => {}
+		}
+		*c == ',' && depth == 0
+	});
+	let (key, value) = types.split_at(comma?.0);
+	let (key, value) = (key.trim(), value[1..].trim());
+	if key == "&[u8]" && value == "&[u8]" {
+		return None;
+	}
+	let template = |name: &str, ty: &str| {
+		let layout = |name: &str, variant: LayoutVariant, from: usize, to: usize| {
+			ByteLayout::default()
+				.with_name(name.to_string())
+				.with_variant(variant)
+				.with_range(from, to)
+				.build()
+		};
+		let single = |name: &str, ty: &str, from: usize| match ty {
+			"u64" => Some(layout(name, LayoutVariant::UInt64BE, from, from + 8)),
+			"&str" => Some(layout(name, LayoutVariant::String, from, usize::MAX)),
+			_ => None,
+		};
+		// Strings first in a pair end with a zero byte, the pair is shown as bytes
+		let pair = ty.strip_prefix("(u64,").and_then(|second| second.strip_suffix(')'));
+		let layouts = match (single(name, ty, 0), pair) {
+			(Some(layout), _) => vec![layout],
+			(None, Some(second)) => match single("1", second.trim(), 8) {
+				Some(second) => vec![layout("0", LayoutVariant::UInt64BE, 0, 8), second],
+				None => vec![layout("0", LayoutVariant::Bytes, 0, usize::MAX)],
+			},
+			(None, None) => vec![layout(name, LayoutVariant::Bytes, 0, usize::MAX)],
+		};
+		LayoutTemplate::new(&format!("TABLE:{}", ty), layouts)
+	};
+	Some((template("key", key), template("value", value)))
+}
+
+fn suggest_layouts(samples: &[&[u8]]) -> Vec<ByteLayout> {
+	let layout = |name: &str, variant: LayoutVariant, from: usize, to: usize| {
+		ByteLayout::default()
+			.with_name(name.to_string())
+			.with_variant(variant)
+			.with_range(from, to)
+			.build()
+	};
+	if let Some(variant) = document_variant(samples) {
+		return vec![layout("document", variant, 0, usize::MAX)];
+	}
+	let mut layouts: Vec<ByteLayout> = vec![];
+	let mut offset = 0;
+	while samples.iter().any(|sample| sample.len() > offset) {
+		let rests: Vec<_> =
+			samples.iter().map(|sample| sample.get(offset..).unwrap_or_default()).collect();
+		let segment = next_segment(&rests);
+		let name = unique_name(segment.name, &layouts);
+		let to = segment.len.map_or(usize::MAX, |len| offset + len);
+		let mut layout = layout(&name, segment.variant, offset, to);
+		if let Some(time) = segment.time {
+			layout.with_time(time);
+		}
+		layouts.push(layout);
+		match segment.len {
+			Some(len) => offset += len + segment.separated as usize,
+			None => break,
+		}
+	}
+	if layouts.is_empty() {
+		layouts.push(layout("bytes", LayoutVariant::Bytes, 0, usize::MAX));
+	}
+	layouts
+}
+
+// Names of repeated segments are numbered, e.g. `text` and `text2`
+fn unique_name(name: &str, layouts: &[ByteLayout]) -> String {
+	let same =
+		layouts.iter().filter(|layout| layout.name.trim_end_matches(char::is_numeric) == name);
+	match same.count() {
+		0 => name.to_string(),
+		count => format!("{}{}", name, count + 1),
+	}
+}
+
+fn is_text(raw: &[u8]) -> bool {
+	match std::str::from_utf8(raw) {
+		Ok(text) => !text.chars().any(char::is_control),
+		Err(_) => false,
+	}
+}
+
+// Samples which are all documents of the same format, scalars are left to other layouts
+fn document_variant(samples: &[&[u8]]) -> Option<LayoutVariant> {
+	if samples.is_empty() || samples.iter().any(|sample| sample.is_empty()) {
+		return None;
+	}
+	let json = |raw: &[u8]| {
+		let start = raw.iter().find(|byte| !byte.is_ascii_whitespace());
+		matches!(start, Some(b'{' | b'[')) && serde_json::from_slice::<Value>(raw).is_ok()
+	};
+	// Arrays, maps and tagged items
+	let cbor = |raw: &[u8]| matches!(raw[0], 0x80..=0xdb) && decode_cbor(raw).is_ok();
+	let msgpack =
+		|raw: &[u8]| matches!(raw[0], 0x80..=0x9f | 0xdc..=0xdf) && decode_msgpack(raw).is_ok();
+	let all = |is_format: &dyn Fn(&[u8]) -> bool| samples.iter().all(|s| is_format(s));
+	if all(&json) {
+		Some(LayoutVariant::Json)
+	} else if all(&cbor) {
+		Some(LayoutVariant::Cbor)
+	} else if all(&msgpack) {
+		Some(LayoutVariant::MessagePack)
+	} else {
+		None
+	}
+}
+
+// Next segment of samples which are all read up to the same offset
+fn next_segment(rests: &[&[u8]]) -> Segment {
+	// Samples ending at different offsets are read to the end
+	if rests.iter().any(|rest| rest.is_empty()) {
+		return Segment::new("bytes", LayoutVariant::Bytes, None);
+	}
+	if let Some(segment) = text_segment(rests) {
+		return segment;
+	}
+	if rests.iter().all(|rest| is_uuid_v4(rest)) {
+		return Segment::new("uuid", LayoutVariant::UuidV4, Some(16));
+	}
+	number_segment(rests).unwrap_or_else(|| Segment::new("bytes", LayoutVariant::Bytes, None))
+}
+
+// Text up to a separator at the same offset of every sample, or text to the end
+fn text_segment(rests: &[&[u8]]) -> Option<Segment> {
+	let separator_at = |rest: &[u8]| rest.iter().position(|byte| SEPARATORS.contains(byte));
+	let text_before = |rest: &[u8], at: usize| {
+		is_text(&rest[..at]) && rest[..at].iter().any(u8::is_ascii_alphanumeric)
+	};
+	match separator_at(rests[0]) {
+		Some(at)
+			if rests.iter().all(|rest| separator_at(rest) == Some(at) && text_before(rest, at)) =>
+		{
+			Some(Segment {
+				separated: true,
+				..Segment::new("text", LayoutVariant::String, Some(at))
+			})
+		}
+		_ if rests.iter().all(|rest| is_text(rest)) => {
+			Some(Segment::new("text", LayoutVariant::String, None))
+		}
+		_ => None,
+	}
+}
+
+// Version 4 and variant bits of a random UUID
+fn is_uuid_v4(raw: &[u8]) -> bool {
+	raw.len() >= 16 && raw[6] >> 4 == 4 && raw[8] & 0xc0 == 0x80
+}
+
+// Integers taking the rest of samples of the same length, or a timestamp leading longer samples
+fn number_segment(rests: &[&[u8]]) -> Option<Segment> {
+	let len = rests[0].len();
+	if !rests.iter().all(|rest| rest.len() == len) || !matches!(len, 1 | 2 | 4 | 8 | 16) {
+		let time =
+			rests.iter().all(|rest| rest.len() > 8).then(|| timestamp_format(rests, 8, false));
+		return time.flatten().map(|time| Segment {
+			time: Some(time),
+			..Segment::new("timestamp", LayoutVariant::Timestamp, Some(8))
+		});
+	}
+	// Integers whose last byte is zero more often than their first are little endian
+	let zeros = |index: usize| rests.iter().filter(|rest| rest[index] == 0).count();
+	let little_endian = len > 1 && zeros(len - 1) > zeros(0);
+	if let Some(time) = timestamp_format(rests, len, little_endian) {
+		return Some(Segment {
+			time: Some(time),
+			..Segment::new("timestamp", LayoutVariant::Timestamp, Some(len))
+		});
+	}
+	let variant = match (len, little_endian) {
+		(1, _) => LayoutVariant::UInt8,
+		(2, false) => LayoutVariant::UInt16BE,
+		(2, true) => LayoutVariant::UInt16LE,
+		(4, false) => LayoutVariant::UInt32BE,
+		(4, true) => LayoutVariant::UInt32LE,
+		(8, false) => LayoutVariant::UInt64BE,
+		(8, true) => LayoutVariant::UInt64LE,
+		(_, false) => LayoutVariant::UInt128BE,
+		(_, true) => LayoutVariant::UInt128LE,
+	};
+	Some(Segment::new("id", variant, Some(len)))
+}
+
+// Unit in which the leading integers of every sample are all recent dates
+fn timestamp_format(rests: &[&[u8]], len: usize, little_endian: bool) -> Option<TimeFormat> {
+	if len != 4 && len != 8 {
+		return None;
+	}
+	let mut time = TimeFormat {
+		little_endian,
+		size: Some(len),
+		..Default::default()
+	};
+	let units = [TimeUnit::Seconds, TimeUnit::Millis, TimeUnit::Micros, TimeUnit::Nanos];
+	time.unit = units.into_iter().find(|unit| {
+		let range =
+			TIMESTAMP_SECONDS.start * unit.per_second()..TIMESTAMP_SECONDS.end * unit.per_second();
+		let value = |rest: &[u8]| time.read_int(rest, len);
+		rests.iter().all(|rest| matches!(value(rest), Some(value) if range.contains(&value)))
+	})?;
+	Some(time)
+}
+
+#[cfg(test)]
+mod test {
+	use std::io::Write;
+
+	use flate2::{write::GzEncoder, Compression as Level};
+	use serde::Serialize;
+
+	use super::{
+		describe_template, sample_pairs, suggest_template, table_templates, SUGGEST_SAMPLES,
+	};
+	use crate::utils::{Compression, LayoutVariant, TimeUnit};
+
+	#[derive(Serialize)]
+	struct User {
+		name: String,
+		age: u8,
+	}
+
+	fn users() -> Vec<User> {
+		(0..4)
+			.map(|age| User {
+				name: format!("user{}", age),
+				age,
+			})
+			.collect()
+	}
+
+	fn suggest(samples: &[Vec<u8>]) -> String {
+		let samples: Vec<&[u8]> = samples.iter().map(|sample| sample.as_slice()).collect();
+		describe_template(&suggest_template("suggested", &samples))
+	}
+
+	#[test]
+	fn should_sample_whole_scan() {
+		let pairs: Vec<_> = (0..1000u32).map(|i| (i.to_be_bytes().to_vec(), vec![])).collect();
+		let samples = sample_pairs(&pairs);
+		assert_eq!(samples.len(), SUGGEST_SAMPLES);
+		assert_eq!(samples[1].0, 3u32.to_be_bytes());
+		assert_eq!(sample_pairs(&pairs[..10]).len(), 10);
+	}
+
+	#[test]
+	fn should_suggest_segments() {
+		let keys: Vec<_> =
+			(1..5u64).map(|id| [b"user:".as_slice(), &(id << 40).to_be_bytes()].concat()).collect();
+		assert_eq!(suggest(&keys), "text[0..4] String, id[5..13] UInt64BE");
+
+		let keys: Vec<_> = (1..5u32).map(|id| id.to_le_bytes().to_vec()).collect();
+		assert_eq!(suggest(&keys), "id[0..4] UInt32LE");
+
+		let uuids: Vec<_> = (0..4u8)
+			.map(|i| uuid::Builder::from_random_bytes([i; 16]).into_uuid().as_bytes().to_vec())
+			.collect();
+		assert_eq!(suggest(&uuids), "uuid[0..16] UuidV4");
+
+		let keys: Vec<_> = (0..4).map(|i| format!("tenant{}/event{}", i, i).into_bytes()).collect();
+		assert_eq!(suggest(&keys), "text[0..7] String, text2[8..] String");
+	}
+
+	#[test]
+	fn should_suggest_timestamps() {
+		// Milliseconds of 2023 dates followed by a sequence number
+		let keys: Vec<_> = (0..4i64)
+			.map(|i| [&(1_680_000_000_000 + i).to_be_bytes()[..], b"\x01\x02"].concat())
+			.collect();
+		let samples: Vec<&[u8]> = keys.iter().map(|key| key.as_slice()).collect();
+		let template = suggest_template("events", &samples);
+		assert!(matches!(template.layout[0].variant, LayoutVariant::Timestamp));
+		assert_eq!(template.layout[0].time.unit, TimeUnit::Millis);
+		assert_eq!(describe_template(&template), "timestamp[0..8] Timestamp, id[8..10] UInt16BE");
+
+		let keys: Vec<_> = (0..4u64).map(|i| (1_680_000_000 + i).to_le_bytes().to_vec()).collect();
+		let samples: Vec<&[u8]> = keys.iter().map(|key| key.as_slice()).collect();
+		let template = suggest_template("events", &samples);
+		assert!(matches!(template.layout[0].variant, LayoutVariant::Timestamp));
+		assert!(template.layout[0].time.little_endian);
+	}
+
+	#[test]
+	fn should_suggest_documents() {
+		let json: Vec<_> = users().iter().map(|user| serde_json::to_vec(user).unwrap()).collect();
+		assert_eq!(suggest(&json), "document[0..] Json");
+
+		let cbor: Vec<_> = users()
+			.iter()
+			.map(|user| {
+				let mut raw = vec![];
+				ciborium::ser::into_writer(user, &mut raw).unwrap();
+				raw
+			})
+			.collect();
+		assert_eq!(suggest(&cbor), "document[0..] Cbor");
+
+		let msgpack: Vec<_> =
+			users().iter().map(|user| rmp_serde::to_vec_named(user).unwrap()).collect();
+		assert_eq!(suggest(&msgpack), "document[0..] MessagePack");
+
+		let gzip: Vec<_> = json
+			.iter()
+			.map(|raw| {
+				let mut encoder = GzEncoder::new(vec![], Level::default());
+				encoder.write_all(raw).unwrap();
+				encoder.finish().unwrap()
+			})
+			.collect();
+		let samples: Vec<&[u8]> = gzip.iter().map(|sample| sample.as_slice()).collect();
+		let template = suggest_template("users", &samples);
+		assert_eq!(template.compression, Some(Compression::Gzip));
+		assert_eq!(describe_template(&template), "gzip document[0..] Json");
+	}
+
+	#[test]
+	fn should_suggest_bytes_for_malformed_samples() {
+		assert_eq!(suggest(&[]), "bytes[0..] Bytes");
+		assert_eq!(suggest(&[vec![], vec![1]]), "bytes[0..] Bytes");
+		// Truncated documents and compressed values are read as they are
+		let json: Vec<_> = (0..4).map(|i| serde_json::to_vec(&[i, i + 1]).unwrap()).collect();
+		assert_eq!(suggest(&json), "document[0..] Json");
+		let truncated: Vec<_> = json.iter().map(|raw| raw[..raw.len() - 1].to_vec()).collect();
+		assert_eq!(suggest(&truncated), "text[0..] String");
+		let gzip = vec![b"\x1f\x8b\x08\x00".to_vec(), b"\x1f\x8b\x08\x01".to_vec()];
+		assert_eq!(suggest(&gzip), "id[0..4] UInt32LE");
+		let cbor = vec![vec![0xa2, 0x61], vec![0xa2, 0x62]];
+		assert_eq!(suggest(&cbor), "id[0..2] UInt16BE");
+	}
+
+	#[test]
+	fn should_format_table_types() {
+		let (key, value) = table_templates("Table<u64, &str>").unwrap();
+		assert_eq!(key.name, "TABLE:u64");
+		assert_eq!(describe_template(&key), "key[0..8] UInt64BE");
+		assert_eq!(describe_template(&value), "value[0..] String");
+		assert_eq!(key.decode(&1500u64.to_be_bytes())[0].1, "1500");
+
+		let (key, value) = table_templates("MultimapTable<(u64, &str), &[u8]>").unwrap();
+		assert_eq!(describe_template(&key), "0[0..8] UInt64BE, 1[8..] String");
+		assert_eq!(describe_template(&value), "value[0..] Bytes");
+		let (key, _) = table_templates("Table<(&str, u64), u64>").unwrap();
+		assert_eq!(describe_template(&key), "key[0..] Bytes");
+		assert!(table_templates("Table<&[u8], &[u8]>").is_none());
+		assert!(table_templates("unknown").is_none());
+	}
+}
@@ -0,0 +1,182 @@
+use std::{
+	io::{self, Write},
+	path::Path,
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use db::KeyValuePair;
+use serde_json::{Map, Value};
+
+use super::LayoutTemplate;
+
+/// First bytes of a binary dump, followed by the length-prefixed pairs
+pub const DUMP_MAGIC: &[u8; 8] = b"EDMADUMP";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+	Jsonl,
+	Csv,
+	Dump,
+}
+
+impl ExportFormat {
+	pub fn from_string(s: &str) -> Option<ExportFormat> {
+		match s.to_lowercase().as_str() {
+			"jsonl" => Some(ExportFormat::Jsonl),
+			"csv" => Some(ExportFormat::Csv),
+			"dump" => Some(ExportFormat::Dump),
+			_ => None,
+		}
+	}
+
+	/// Format of a file guessed from its extension
+	pub fn from_path(path: &str) -> Option<ExportFormat> {
+		let extension = Path::new(path).extension()?.to_str()?;
+		ExportFormat::from_string(extension)
+	}
+}
+
+/// Encoding of keys or values in JSON Lines and CSV exports, binary dumps keep raw bytes
+#[derive(Clone, Debug)]
+pub enum ExportEncoding {
+	Hex,
+	Base64,
+	// Fields decoded by a template are exported as an object or as a column each
+	Template(LayoutTemplate),
+}
+
+impl ExportEncoding {
+	/// Parse `hex`, `base64` or the name of a template
+	pub fn from_string(s: &str, templates: &[LayoutTemplate]) -> Option<ExportEncoding> {
+		match s {
+			"hex" => Some(ExportEncoding::Hex),
+			"base64" => Some(ExportEncoding::Base64),
+			name => {
+				let template = templates.iter().find(|template| template.name == name)?;
+				Some(ExportEncoding::Template(template.clone()))
+			}
+		}
+	}
+
+	fn encode(&self, raw: &[u8]) -> Value {
+		match self {
+			ExportEncoding::Template(template) => {
+				let fields = template.decode(raw).into_iter();
+				Value::Object(fields.map(|(name, value)| (name, Value::String(value))).collect())
+			}
+			_ => Value::String(self.cells(raw).concat()),
+		}
+	}
+
+	fn columns(&self, name: &str) -> Vec<String> {
+		match self {
+			ExportEncoding::Template(template) => {
+				template.layout.iter().map(|field| format!("{}.{}", name, field.name)).collect()
+			}
+			_ => vec![name.to_string()],
+		}
+	}
+
+	fn cells(&self, raw: &[u8]) -> Vec<String> {
+		match self {
+			ExportEncoding::Hex => vec![encode_hex(raw)],
+			ExportEncoding::Base64 => vec![encode_base64(raw)],
+			// A value which can't be decompressed decodes as a single error, which is kept in the
+			// first column of the template, the others are left empty
+			ExportEncoding::Template(template) => {
+				let fields = template.decode(raw).into_iter();
+				let mut cells: Vec<_> = fields.map(|(_, value)| value).collect();
+				cells.resize(template.layout.len(), String::new());
+				cells
+			}
+		}
+	}
+}
+
+pub fn encode_hex(raw: &[u8]) -> String {
+	raw.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub fn encode_base64(raw: &[u8]) -> String {
+	STANDARD.encode(raw)
+}
+
+// Quote a CSV cell holding a separator, a quote or a line break
+fn escape_csv(cell: &str) -> String {
+	match cell.contains([',', '"', '\n', '\r']) {
+		true => format!("\"{}\"", cell.replace('"', "\"\"")),
+		false => cell.to_string(),
+	}
+}
+
+/// Write pairs in the format, keys and values are encoded with their own encoding
+pub fn export_pairs<W: Write>(
+	writer: &mut W,
+	pairs: &[KeyValuePair],
+	format: &ExportFormat,
+	key: &ExportEncoding,
+	value: &ExportEncoding,
+) -> io::Result<()> {
+	match format {
+		ExportFormat::Jsonl => {
+			for (k, v) in pairs.iter() {
+				let mut line = Map::new();
+				line.insert("key".to_string(), key.encode(k));
+				line.insert("value".to_string(), value.encode(v));
+				writeln!(writer, "{}", Value::Object(line))?;
+			}
+		}
+		ExportFormat::Csv => {
+			let header = [key.columns("key"), value.columns("value")].concat();
+			let header: Vec<_> = header.iter().map(|column| escape_csv(column)).collect();
+			writeln!(writer, "{}", header.join(","))?;
+			for (k, v) in pairs.iter() {
+				let cells = [key.cells(k), value.cells(v)].concat();
+				let cells: Vec<_> = cells.iter().map(|cell| escape_csv(cell)).collect();
+				writeln!(writer, "{}", cells.join(","))?;
+			}
+		}
+		// Lengths are big endian u32
+		ExportFormat::Dump => {
+			writer.write_all(DUMP_MAGIC)?;
+			for (k, v) in pairs.iter() {
+				for bytes in [k, v] {
+					writer.write_all(&(bytes.len() as u32).to_be_bytes())?;
+					writer.write_all(bytes)?;
+				}
+			}
+		}
+	}
+	writer.flush()
+}
+
+#[cfg(test)]
+mod test {
+	use crate::utils::{ByteLayout, Compression, LayoutTemplate, LayoutVariant};
+
+	use super::{export_pairs, ExportEncoding, ExportFormat};
+
+	#[test]
+	fn should_export_csv_cell_per_field() {
+		let field = |name: &str, from, to| {
+			let mut layout = ByteLayout::default();
+			layout
+				.with_name(name.to_string())
+				.with_range(from, to)
+				.with_variant(LayoutVariant::UInt8);
+			layout
+		};
+		let mut template = LayoutTemplate::new("id", vec![field("a", 0, 1), field("b", 1, 2)]);
+		template.set_compression(Some(Compression::Gzip));
+		let pairs = vec![(b"key".to_vec(), b"not gzip".to_vec())];
+		let mut csv = vec![];
+		let (key, value) = (ExportEncoding::Base64, ExportEncoding::Template(template));
+		export_pairs(&mut csv, &pairs, &ExportFormat::Csv, &key, &value).unwrap();
+
+		let csv = String::from_utf8(csv).unwrap();
+		let lines: Vec<_> = csv.lines().collect();
+		assert_eq!(lines[0], "key,value.a,value.b");
+		assert_eq!(lines[1].split(',').count(), 3);
+		assert!(lines[1].starts_with("a2V5,Unable to decompress"));
+	}
+}
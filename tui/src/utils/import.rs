@@ -0,0 +1,134 @@
+use std::{
+	fs::File,
+	io::{BufRead, BufReader},
+};
+
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use db::KeyValuePair;
+use serde_json::Value;
+
+use super::{ExportEncoding, ExportFormat, DUMP_MAGIC};
+
+pub fn decode_hex(s: &str) -> Result<Vec<u8>> {
+	if !s.len().is_multiple_of(2) {
+		return Err(anyhow!("hex value has an odd length"));
+	}
+	(0..s.len())
+		.step_by(2)
+		.map(|index| {
+			let byte = s.get(index..index + 2).ok_or_else(|| anyhow!("invalid hex value"))?;
+			u8::from_str_radix(byte, 16).map_err(|_| anyhow!("invalid hex value {}", s))
+		})
+		.collect()
+}
+
+pub fn decode_base64(s: &str) -> Result<Vec<u8>> {
+	STANDARD.decode(s).map_err(|_| anyhow!("invalid base64 value {}", s))
+}
+
+impl ExportEncoding {
+	// Fields decoded by a template can't be turned back into bytes
+	fn decode(&self, s: &str) -> Result<Vec<u8>> {
+		match self {
+			ExportEncoding::Hex => decode_hex(s),
+			ExportEncoding::Base64 => decode_base64(s),
+			ExportEncoding::Template(template) => {
+				Err(anyhow!("values decoded with template {} can't be imported", template.name))
+			}
+		}
+	}
+}
+
+// Split a CSV line, quoted cells may hold separators and doubled quotes
+fn split_csv(line: &str) -> Vec<String> {
+	let (mut cells, mut cell, mut quoted) = (vec![], String::new(), false);
+	let mut chars = line.chars().peekable();
+	while let Some(c) = chars.next() {
+		match (c, quoted) {
+			('"', true) if chars.peek() == Some(&'"') => {
+				cell.push('"');
+				chars.next();
+			}
+			('"', _) => quoted = !quoted,
+			(',', false) => cells.push(std::mem::take(&mut cell)),
+			(c, _) => cell.push(c),
+		}
+	}
+	cells.push(cell);
+	cells
+}
+
+// Read a length-prefixed key or value of a dump
+fn read_chunk(rest: &mut &[u8]) -> Result<Vec<u8>> {
+	if rest.len() < 4 {
+		return Err(anyhow!("truncated dump"));
+	}
+	let (len, tail) = rest.split_at(4);
+	let len = u32::from_be_bytes(len.try_into()?) as usize;
+	if tail.len() < len {
+		return Err(anyhow!("truncated dump"));
+	}
+	let (bytes, tail) = tail.split_at(len);
+	*rest = tail;
+	Ok(bytes.to_vec())
+}
+
+/// Read pairs written by `export_pairs` with the same encodings
+pub fn import_pairs<R: BufRead>(
+	mut reader: R,
+	format: &ExportFormat,
+	key: &ExportEncoding,
+	value: &ExportEncoding,
+) -> Result<Vec<KeyValuePair>> {
+	let mut pairs = vec![];
+	match format {
+		ExportFormat::Jsonl => {
+			for (index, line) in reader.lines().enumerate() {
+				let line = line?;
+				if line.trim().is_empty() {
+					continue;
+				}
+				let record: Value = serde_json::from_str(&line)?;
+				let field = |name: &str| {
+					let field = record.get(name).and_then(|field| field.as_str());
+					field.ok_or_else(|| anyhow!("line {}: {} is not a string", index + 1, name))
+				};
+				pairs.push((key.decode(field("key")?)?, value.decode(field("value")?)?));
+			}
+		}
+		ExportFormat::Csv => {
+			for (index, line) in reader.lines().enumerate().skip(1) {
+				let line = line?;
+				if line.trim().is_empty() {
+					continue;
+				}
+				match &split_csv(&line)[..] {
+					[k, v] => pairs.push((key.decode(k)?, value.decode(v)?)),
+					_ => return Err(anyhow!("line {}: expected a key and a value", index + 1)),
+				}
+			}
+		}
+		ExportFormat::Dump => {
+			let mut data = vec![];
+			reader.read_to_end(&mut data)?;
+			let mut rest = data.strip_prefix(DUMP_MAGIC).ok_or_else(|| anyhow!("not a dump"))?;
+			while !rest.is_empty() {
+				let k = read_chunk(&mut rest)?;
+				pairs.push((k, read_chunk(&mut rest)?));
+			}
+		}
+	}
+	Ok(pairs)
+}
+
+/// Read the pairs of an exported file
+pub fn import_file(
+	path: &str,
+	format: &ExportFormat,
+	key: &ExportEncoding,
+	value: &ExportEncoding,
+) -> Result<Vec<KeyValuePair>> {
+	let reader = BufReader::new(File::open(path)?);
+	import_pairs(reader, format, key, value)
+}
@@ -1,8 +1,24 @@
 mod byte;
+mod codec;
+mod compression;
+mod detect;
+mod export;
 mod file;
+mod import;
+mod proto;
+mod serde_formats;
+mod time;
 
 pub use byte::*;
+pub use codec::*;
+pub use compression::*;
+pub use detect::*;
+pub use export::*;
 pub use file::*;
+pub use import::*;
+pub use proto::*;
+pub use serde_formats::*;
+pub use time::*;
 
 use crate::events::Key;
 
@@ -0,0 +1,728 @@
+use std::{collections::HashMap, fs, sync::Arc};
+
+use super::{decode_varint, decode_zigzag, serde_formats::MAX_DEPTH, FieldNode};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProtoType {
+	Double,
+	Float,
+	Int32,
+	Int64,
+	UInt32,
+	UInt64,
+	SInt32,
+	SInt64,
+	Fixed32,
+	Fixed64,
+	SFixed32,
+	SFixed64,
+	Bool,
+	String,
+	Bytes,
+	// Message or enum, resolved by name when decoding
+	Named(String),
+}
+
+impl ProtoType {
+	fn from_name(name: &str) -> ProtoType {
+		match name {
+			"double" => ProtoType::Double,
+			"float" => ProtoType::Float,
+			"int32" => ProtoType::Int32,
+			"int64" => ProtoType::Int64,
+			"uint32" => ProtoType::UInt32,
+			"uint64" => ProtoType::UInt64,
+			"sint32" => ProtoType::SInt32,
+			"sint64" => ProtoType::SInt64,
+			"fixed32" => ProtoType::Fixed32,
+			"fixed64" => ProtoType::Fixed64,
+			"sfixed32" => ProtoType::SFixed32,
+			"sfixed64" => ProtoType::SFixed64,
+			"bool" => ProtoType::Bool,
+			"string" => ProtoType::String,
+			"bytes" => ProtoType::Bytes,
+			name => ProtoType::Named(name.trim_start_matches('.').to_string()),
+		}
+	}
+}
+
+#[derive(Clone, Debug)]
+pub struct ProtoField {
+	pub name: String,
+	pub number: u64,
+	pub ty: ProtoType,
+	pub repeated: bool,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ProtoSchema {
+	// Messages and enums are registered with their full name and with their own name
+	messages: HashMap<String, Vec<ProtoField>>,
+	enums: HashMap<String, HashMap<i64, String>>,
+}
+
+/// Message of a schema values of a layout are decoded with
+#[derive(Clone, Debug)]
+pub struct ProtoLayout {
+	pub schema: Arc<ProtoSchema>,
+	pub message: String,
+	// The .proto file the schema was parsed from
+	pub path: String,
+}
+
+impl ProtoLayout {
+	/// Parse a .proto file, the message must be defined in it
+	pub fn load(path: &str, message: &str) -> Result<ProtoLayout, String> {
+		let source = fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+		let schema = ProtoSchema::parse(&source).map_err(|err| format!("{}: {}", path, err))?;
+		if schema.message(message).is_none() {
+			return Err(format!("{}: message {} not found", path, message));
+		}
+		Ok(ProtoLayout {
+			schema: Arc::new(schema),
+			message: message.to_string(),
+			path: path.to_string(),
+		})
+	}
+}
+
+// Split a .proto file in identifiers, numbers, strings and symbols, comments are dropped
+fn tokenize(source: &str) -> Vec<String> {
+	let mut tokens = vec![];
+	let mut chars = source.chars().peekable();
+	while let Some(c) = chars.next() {
+		match c {
+			c if c.is_whitespace() => {}
+			'/' if chars.peek() == Some(&'/') => {
+				for c in chars.by_ref() {
+					if c == '\n' {
+						break;
+					}
+				}
+			}
+			'/' if chars.peek() == Some(&'*') => {
+				chars.next();
+				let mut previous = ' ';
+				for c in chars.by_ref() {
+					if previous == '*' && c == '/' {
+						break;
+					}
+					previous = c;
+				}
+			}
+			'"' | '\'' => {
+				let mut token = c.to_string();
+				for next in chars.by_ref() {
+					token.push(next);
+					if next == c {
+						break;
+					}
+				}
+				tokens.push(token);
+			}
+			c if c.is_alphanumeric() || c == '_' || c == '.' || c == '-' => {
+				let mut token = c.to_string();
+				while let Some(next) = chars.peek() {
+					if !(next.is_alphanumeric() || *next == '_' || *next == '.') {
+						break;
+					}
+					token.push(*next);
+					chars.next();
+				}
+				tokens.push(token);
+			}
+			c => tokens.push(c.to_string()),
+		}
+	}
+	tokens
+}
+
+struct Parser {
+	tokens: Vec<String>,
+	position: usize,
+	schema: ProtoSchema,
+}
+
+impl Parser {
+	fn next(&mut self) -> Result<String, String> {
+		let token = self.tokens.get(self.position).cloned();
+		self.position += 1;
+		token.ok_or_else(|| "unexpected end of file".to_string())
+	}
+
+	fn peek(&self) -> Option<&str> {
+		self.tokens.get(self.position).map(|token| token.as_str())
+	}
+
+	fn expect(&mut self, expected: &str) -> Result<(), String> {
+		match self.next()? {
+			token if token == expected => Ok(()),
+			token => Err(format!("expected {} but found {}", expected, token)),
+		}
+	}
+
+	// Skip a statement up to its semicolon, or a block with its nested blocks
+	fn skip_statement(&mut self) -> Result<(), String> {
+		let mut depth = 0;
+		loop {
+			match self.next()?.as_str() {
+				";" if depth == 0 => return Ok(()),
+				"{" => depth += 1,
+				"}" if depth == 1 => return Ok(()),
+				"}" => depth -= 1,
+				_ => {}
+			}
+		}
+	}
+
+	fn file(&mut self) -> Result<(), String> {
+		let mut package = String::new();
+		while let Some(token) = self.peek() {
+			match token {
+				"package" => {
+					self.next()?;
+					package = self.next()?;
+					self.expect(";")?;
+				}
+				"message" => {
+					self.next()?;
+					self.message(&package)?;
+				}
+				"enum" => {
+					self.next()?;
+					self.enumeration(&package)?;
+				}
+				_ => self.skip_statement()?,
+			}
+		}
+		Ok(())
+	}
+
+	fn register<T: Clone>(map: &mut HashMap<String, T>, scope: &str, name: &str, value: T) {
+		if !scope.is_empty() {
+			map.insert(format!("{}.{}", scope, name), value.clone());
+		}
+		map.entry(name.to_string()).or_insert(value);
+	}
+
+	fn message(&mut self, scope: &str) -> Result<(), String> {
+		let name = self.next()?;
+		let full_name = match scope {
+			"" => name.clone(),
+			scope => format!("{}.{}", scope, name),
+		};
+		self.expect("{")?;
+		let mut fields = vec![];
+		loop {
+			match self.peek() {
+				Some("}") => {
+					self.next()?;
+					break;
+				}
+				Some("message") => {
+					self.next()?;
+					self.message(&full_name)?;
+				}
+				Some("enum") => {
+					self.next()?;
+					self.enumeration(&full_name)?;
+				}
+				// Fields of a oneof are fields of the message
+				Some("oneof") => {
+					self.next()?;
+					self.next()?;
+					self.expect("{")?;
+					while self.peek() != Some("}") {
+						match self.peek() {
+							Some("option") => self.skip_statement()?,
+							_ => fields.push(self.field(&full_name)?),
+						}
+					}
+					self.next()?;
+				}
+				Some("option") | Some("reserved") | Some("extensions") | Some("extend") => {
+					self.skip_statement()?
+				}
+				Some(";") => {
+					self.next()?;
+				}
+				Some(_) => fields.push(self.field(&full_name)?),
+				None => return Err(format!("message {} is not closed", name)),
+			}
+		}
+		Self::register(&mut self.schema.messages, scope, &name, fields);
+		Ok(())
+	}
+
+	fn field(&mut self, scope: &str) -> Result<ProtoField, String> {
+		let mut repeated = false;
+		let mut ty = self.next()?;
+		match ty.as_str() {
+			"repeated" => {
+				repeated = true;
+				ty = self.next()?;
+			}
+			"optional" | "required" => ty = self.next()?,
+			_ => {}
+		}
+		let field_type = match ty.as_str() {
+			// Map entries are messages with a key and a value field
+			"map" => {
+				self.expect("<")?;
+				let key = ProtoType::from_name(&self.next()?);
+				self.expect(",")?;
+				let value = ProtoType::from_name(&self.next()?);
+				self.expect(">")?;
+				repeated = true;
+				let entry = format!("{}.{}Entry", scope, self.peek().unwrap_or_default());
+				let entry_fields = vec![
+					ProtoField {
+						name: "key".to_string(),
+						number: 1,
+						ty: key,
+						repeated: false,
+					},
+					ProtoField {
+						name: "value".to_string(),
+						number: 2,
+						ty: value,
+						repeated: false,
+					},
+				];
+				self.schema.messages.insert(entry.clone(), entry_fields);
+				ProtoType::Named(entry)
+			}
+			ty => ProtoType::from_name(ty),
+		};
+		let name = self.next()?;
+		self.expect("=")?;
+		let number = self.next()?;
+		let number = number.parse().map_err(|_| format!("invalid field number {}", number))?;
+		self.skip_statement()?;
+		Ok(ProtoField {
+			name,
+			number,
+			ty: field_type,
+			repeated,
+		})
+	}
+
+	fn enumeration(&mut self, scope: &str) -> Result<(), String> {
+		let name = self.next()?;
+		self.expect("{")?;
+		let mut values = HashMap::new();
+		loop {
+			match self.peek() {
+				Some("}") => {
+					self.next()?;
+					break;
+				}
+				Some("option") | Some("reserved") | Some(";") => self.skip_statement()?,
+				Some(_) => {
+					let value_name = self.next()?;
+					self.expect("=")?;
+					let number = self.next()?;
+					let number = number.parse().map_err(|_| format!("invalid value {}", number))?;
+					values.insert(number, value_name);
+					self.skip_statement()?;
+				}
+				None => return Err(format!("enum {} is not closed", name)),
+			}
+		}
+		Self::register(&mut self.schema.enums, scope, &name, values);
+		Ok(())
+	}
+}
+
+impl ProtoSchema {
+	/// Parse the messages and enums of a .proto file, options and services are skipped
+	pub fn parse(source: &str) -> Result<ProtoSchema, String> {
+		let mut parser = Parser {
+			tokens: tokenize(source),
+			position: 0,
+			schema: ProtoSchema::default(),
+		};
+		parser.file()?;
+		Ok(parser.schema)
+	}
+
+	// Types are resolved by full name, then by their own name
+	fn message(&self, name: &str) -> Option<&Vec<ProtoField>> {
+		let short = name.rsplit('.').next().unwrap_or(name);
+		self.messages.get(name).or_else(|| self.messages.get(short))
+	}
+
+	fn enumeration(&self, name: &str) -> Option<&HashMap<i64, String>> {
+		let short = name.rsplit('.').next().unwrap_or(name);
+		self.enums.get(name).or_else(|| self.enums.get(short))
+	}
+}
+
+enum WireValue<'a> {
+	Varint(u64),
+	Fixed64([u8; 8]),
+	Bytes(&'a [u8]),
+	Fixed32([u8; 4]),
+}
+
+// Split a message in its fields, as field number and value
+fn read_fields(raw: &[u8]) -> Result<Vec<(u64, WireValue<'_>)>, String> {
+	let mut fields = vec![];
+	let mut rest = raw;
+	while !rest.is_empty() {
+		let (tag, len) = decode_varint(rest).ok_or("invalid tag")?;
+		rest = &rest[len..];
+		let value = match tag & 7 {
+			0 => {
+				let (value, len) = decode_varint(rest).ok_or("invalid varint")?;
+				rest = &rest[len..];
+				WireValue::Varint(value)
+			}
+			1 => {
+				let bytes = rest.get(..8).ok_or("truncated fixed64")?;
+				rest = &rest[8..];
+				WireValue::Fixed64(bytes.try_into().unwrap())
+			}
+			2 => {
+				let (len, prefix) = decode_varint(rest).ok_or("invalid length")?;
+				let end = prefix.checked_add(len as usize).ok_or("invalid length")?;
+				let bytes = rest.get(prefix..end).ok_or("truncated bytes")?;
+				rest = &rest[end..];
+				WireValue::Bytes(bytes)
+			}
+			5 => {
+				let bytes = rest.get(..4).ok_or("truncated fixed32")?;
+				rest = &rest[4..];
+				WireValue::Fixed32(bytes.try_into().unwrap())
+			}
+			wire => return Err(format!("unsupported wire type {}", wire)),
+		};
+		fields.push((tag >> 3, value));
+	}
+	Ok(fields)
+}
+
+fn is_text(bytes: &[u8]) -> bool {
+	match std::str::from_utf8(bytes) {
+		Ok(text) => text.chars().all(|c| !c.is_control() || c.is_whitespace()),
+		Err(_) => false,
+	}
+}
+
+// Fields without a schema are named after their number and wire type, length-delimited
+// values are shown as text, as a nested message when they parse as one, or as bytes. Values
+// nested deeper than `MAX_DEPTH` are shown as bytes.
+fn raw_node(number: u64, value: &WireValue, depth: usize) -> FieldNode {
+	let (wire, value, children) = match value {
+		WireValue::Varint(value) => ("varint", value.to_string(), vec![]),
+		WireValue::Fixed64(bytes) => ("fixed64", u64::from_le_bytes(*bytes).to_string(), vec![]),
+		WireValue::Fixed32(bytes) => ("fixed32", u32::from_le_bytes(*bytes).to_string(), vec![]),
+		WireValue::Bytes(bytes) if is_text(bytes) => {
+			("bytes", format!("{:?}", String::from_utf8_lossy(bytes)), vec![])
+		}
+		WireValue::Bytes(bytes) => match read_fields(bytes) {
+			Ok(fields) if !fields.is_empty() && depth < MAX_DEPTH => {
+				let children =
+					fields.iter().map(|(number, value)| raw_node(*number, value, depth + 1));
+				("message", String::new(), children.collect())
+			}
+			_ => ("bytes", format!("{:?}", bytes), vec![]),
+		},
+	};
+	FieldNode::new(&format!("{} ({})", number, wire), &value).with_children(children)
+}
+
+/// Decode a message without schema, as `protoc --decode_raw` does
+pub fn decode_raw(raw: &[u8]) -> Result<Vec<FieldNode>, String> {
+	let fields = read_fields(raw)?;
+	Ok(fields.iter().map(|(number, value)| raw_node(*number, value, 0)).collect())
+}
+
+fn scalar(schema: &ProtoSchema, ty: &ProtoType, value: &WireValue) -> Option<String> {
+	let text = match (ty, value) {
+		(ProtoType::Int32, WireValue::Varint(v)) => (*v as i32).to_string(),
+		(ProtoType::Int64, WireValue::Varint(v)) => (*v as i64).to_string(),
+		(ProtoType::UInt32, WireValue::Varint(v)) => (*v as u32).to_string(),
+		(ProtoType::UInt64, WireValue::Varint(v)) => v.to_string(),
+		(ProtoType::SInt32, WireValue::Varint(v)) | (ProtoType::SInt64, WireValue::Varint(v)) => {
+			decode_zigzag(*v).to_string()
+		}
+		(ProtoType::Bool, WireValue::Varint(v)) => (*v != 0).to_string(),
+		(ProtoType::Named(name), WireValue::Varint(v)) => {
+			let values = schema.enumeration(name)?;
+			values.get(&(*v as i64)).cloned().unwrap_or_else(|| v.to_string())
+		}
+		(ProtoType::Double, WireValue::Fixed64(b)) => f64::from_le_bytes(*b).to_string(),
+		(ProtoType::Fixed64, WireValue::Fixed64(b)) => u64::from_le_bytes(*b).to_string(),
+		(ProtoType::SFixed64, WireValue::Fixed64(b)) => i64::from_le_bytes(*b).to_string(),
+		(ProtoType::Float, WireValue::Fixed32(b)) => f32::from_le_bytes(*b).to_string(),
+		(ProtoType::Fixed32, WireValue::Fixed32(b)) => u32::from_le_bytes(*b).to_string(),
+		(ProtoType::SFixed32, WireValue::Fixed32(b)) => i32::from_le_bytes(*b).to_string(),
+		(ProtoType::String, WireValue::Bytes(b)) => format!("{:?}", String::from_utf8_lossy(b)),
+		(ProtoType::Bytes, WireValue::Bytes(b)) => format!("{:?}", b),
+		_ => return None,
+	};
+	Some(text)
+}
+
+// Split packed repeated scalars in values of their wire type
+fn unpack<'a>(ty: &ProtoType, bytes: &'a [u8]) -> Option<Vec<WireValue<'a>>> {
+	let mut values = vec![];
+	let mut rest = bytes;
+	let width = match ty {
+		ProtoType::Double | ProtoType::Fixed64 | ProtoType::SFixed64 => 8,
+		ProtoType::Float | ProtoType::Fixed32 | ProtoType::SFixed32 => 4,
+		ProtoType::String | ProtoType::Bytes => return None,
+		_ => 0,
+	};
+	while !rest.is_empty() {
+		let value = match width {
+			8 => WireValue::Fixed64(rest.get(..8)?.try_into().ok()?),
+			4 => WireValue::Fixed32(rest.get(..4)?.try_into().ok()?),
+			_ => {
+				let (value, len) = decode_varint(rest)?;
+				rest = &rest[len..];
+				values.push(WireValue::Varint(value));
+				continue;
+			}
+		};
+		rest = &rest[width..];
+		values.push(value);
+	}
+	Some(values)
+}
+
+fn decode_message(
+	schema: &ProtoSchema,
+	message: &str,
+	raw: &[u8],
+	depth: usize,
+) -> Result<Vec<FieldNode>, String> {
+	if depth > MAX_DEPTH {
+		return Err("nested too deep".to_string());
+	}
+	let fields = schema.message(message).ok_or(format!("message {} not found", message))?;
+	let mut nodes = vec![];
+	for (number, value) in read_fields(raw)?.iter() {
+		let field = match fields.iter().find(|field| field.number == *number) {
+			Some(field) => field,
+			None => {
+				nodes.push(raw_node(*number, value, depth));
+				continue;
+			}
+		};
+		let nested = match (&field.ty, value) {
+			(ProtoType::Named(name), WireValue::Bytes(bytes)) if schema.message(name).is_some() => {
+				Some(decode_message(schema, name, bytes, depth + 1))
+			}
+			_ => None,
+		};
+		match nested {
+			Some(Ok(children)) => {
+				nodes.push(FieldNode::new(&field.name, "").with_children(children));
+			}
+			Some(Err(err)) => nodes.push(FieldNode::new(&field.name, &format!("<{}>", err))),
+			None => match (scalar(schema, &field.ty, value), value) {
+				(Some(text), _) => nodes.push(FieldNode::new(&field.name, &text)),
+				(None, WireValue::Bytes(bytes)) if field.repeated => {
+					let values = unpack(&field.ty, bytes).unwrap_or_default();
+					for value in values.iter() {
+						let text = scalar(schema, &field.ty, value).unwrap_or_default();
+						nodes.push(FieldNode::new(&field.name, &text));
+					}
+				}
+				(None, value) => {
+					let mut node = raw_node(*number, value, depth);
+					node.name = format!("{} ({})", field.name, node.name);
+					nodes.push(node);
+				}
+			},
+		}
+	}
+	Ok(nodes)
+}
+
+/// Decode a message with the schema of the layout, or without schema
+pub fn decode_proto(raw: &[u8], proto: Option<&ProtoLayout>) -> Result<Vec<FieldNode>, String> {
+	match proto {
+		Some(proto) => decode_message(&proto.schema, &proto.message, raw, 0),
+		None => decode_raw(raw),
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::sync::Arc;
+
+	use prost::Message;
+
+	use super::{decode_proto, decode_raw, ProtoLayout, ProtoSchema};
+	use crate::utils::FieldNode;
+
+	const SCHEMA: &str = r#"
+		syntax = "proto3";
+		package shop;
+
+		/* Users of the shop */
+		message User {
+			uint64 id = 1;
+			string name = 2;
+			sint32 balance = 3;
+			Address address = 4;
+			repeated int32 scores = 5;
+			Status status = 6;
+			double ratio = 7;
+			fixed32 flags = 8;
+		}
+
+		message Address {
+			string city = 1;
+		}
+
+		enum Status {
+			ACTIVE = 0;
+			BANNED = 1;
+		}
+	"#;
+
+	#[derive(Clone, PartialEq, Message)]
+	struct User {
+		#[prost(uint64, tag = "1")]
+		id: u64,
+		#[prost(string, tag = "2")]
+		name: String,
+		#[prost(sint32, tag = "3")]
+		balance: i32,
+		#[prost(message, optional, tag = "4")]
+		address: Option<Address>,
+		#[prost(int32, repeated, tag = "5")]
+		scores: Vec<i32>,
+		#[prost(int32, tag = "6")]
+		status: i32,
+		#[prost(double, tag = "7")]
+		ratio: f64,
+		#[prost(fixed32, tag = "8")]
+		flags: u32,
+	}
+
+	#[derive(Clone, PartialEq, Message)]
+	struct Address {
+		#[prost(string, tag = "1")]
+		city: String,
+	}
+
+	fn user() -> Vec<u8> {
+		let user = User {
+			id: 7,
+			name: "ada".to_string(),
+			balance: -3,
+			address: Some(Address {
+				city: "Paris".to_string(),
+			}),
+			scores: vec![1, -2],
+			status: 1,
+			ratio: 0.5,
+			flags: 9,
+		};
+		user.encode_to_vec()
+	}
+
+	fn layout() -> ProtoLayout {
+		ProtoLayout {
+			schema: Arc::new(ProtoSchema::parse(SCHEMA).unwrap()),
+			message: "shop.User".to_string(),
+			path: "shop.proto".to_string(),
+		}
+	}
+
+	fn fields(nodes: &[FieldNode]) -> Vec<(&str, String)> {
+		nodes.iter().map(|node| (node.name.as_str(), node.compact_value())).collect()
+	}
+
+	#[test]
+	fn should_decode_prost_message() {
+		let nodes = decode_proto(&user(), Some(&layout())).unwrap();
+		assert_eq!(
+			fields(&nodes),
+			vec![
+				("id", "7".to_string()),
+				("name", "\"ada\"".to_string()),
+				("balance", "-3".to_string()),
+				("address", "{city: \"Paris\"}".to_string()),
+				("scores", "1".to_string()),
+				("scores", "-2".to_string()),
+				("status", "BANNED".to_string()),
+				("ratio", "0.5".to_string()),
+				("flags", "9".to_string()),
+			]
+		);
+	}
+
+	#[test]
+	fn should_decode_prost_message_without_schema() {
+		let nodes = decode_raw(&user()).unwrap();
+		assert_eq!(
+			fields(&nodes[..4]),
+			vec![
+				("1 (varint)", "7".to_string()),
+				("2 (bytes)", "\"ada\"".to_string()),
+				("3 (varint)", "5".to_string()),
+				("4 (message)", "{1 (bytes): \"Paris\"}".to_string()),
+			]
+		);
+	}
+
+	#[test]
+	fn should_reject_malformed_messages() {
+		let raw = user();
+		assert!(decode_proto(&raw[..raw.len() - 1], Some(&layout())).is_err());
+		// Unsupported wire type and a length past the end of the message
+		assert!(decode_raw(&[0x0b]).is_err());
+		assert!(decode_raw(&[0x12, 0xff, 0xff, 0xff, 0xff, 0x0f]).is_err());
+		// A nested message which doesn't parse is reported in its field
+		let nodes = decode_proto(&[0x22, 0x01, 0x0f], Some(&layout())).unwrap();
+		assert_eq!(fields(&nodes), vec![("address", "<unsupported wire type 7>".to_string())]);
+	}
+
+	#[test]
+	fn should_limit_nested_messages() {
+		// A message nested in its first field a hundred thousand times, written back to front
+		let mut raw = vec![];
+		for _ in 0..100_000 {
+			let mut len = raw.len() as u64;
+			let mut varint = vec![];
+			while len >= 0x80 {
+				varint.push((len as u8) | 0x80);
+				len >>= 7;
+			}
+			varint.push(len as u8);
+			raw.extend(varint.iter().rev());
+			raw.push(0x0a);
+		}
+		raw.reverse();
+		assert!(decode_raw(&raw).is_ok());
+		let schema = ProtoSchema::parse("message Node { Node child = 1; }").unwrap();
+		let layout = ProtoLayout {
+			schema: Arc::new(schema),
+			message: "Node".to_string(),
+			path: "node.proto".to_string(),
+		};
+		let mut nodes = decode_proto(&raw, Some(&layout)).unwrap();
+		let mut depth = 0;
+		while let Some(node) = nodes.pop() {
+			depth += 1;
+			if node.children.is_empty() {
+				assert_eq!(node.value, "<nested too deep>");
+			}
+			nodes = node.children;
+		}
+		assert!(depth > 100 && depth < 200);
+	}
+
+	#[test]
+	fn should_reject_invalid_schemas() {
+		assert!(ProtoSchema::parse("message User { uint64 id = ; }").is_err());
+		assert!(ProtoSchema::parse("message User { uint64 id = 1;").is_err());
+		assert!(ProtoLayout::load("../temp/missing.proto", "User").is_err());
+	}
+}
@@ -0,0 +1,244 @@
+use ciborium::value::Value as CborValue;
+use rmpv::Value as MsgPackValue;
+use serde_json::Value;
+
+use super::FieldNode;
+
+// Deeper values are not decoded, so crafted data can't exhaust the stack
+pub(super) const MAX_DEPTH: usize = 128;
+
+pub(super) struct Reader<'a> {
+	raw: &'a [u8],
+	pos: usize,
+}
+
+impl<'a> Reader<'a> {
+	pub(super) fn new(raw: &'a [u8]) -> Self {
+		Reader {
+			raw,
+			pos: 0,
+		}
+	}
+
+	pub(super) fn remaining(&self) -> usize {
+		self.raw.len() - self.pos
+	}
+
+	pub(super) fn peek(&self) -> Option<u8> {
+		self.raw.get(self.pos).copied()
+	}
+
+	pub(super) fn take(&mut self, len: u64) -> Result<&'a [u8], String> {
+		let end = usize::try_from(len).ok().and_then(|len| self.pos.checked_add(len));
+		let bytes = end.and_then(|end| self.raw.get(self.pos..end)).ok_or("truncated value")?;
+		self.pos += bytes.len();
+		Ok(bytes)
+	}
+
+	pub(super) fn array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+		Ok(self.take(N as u64)?.try_into().unwrap())
+	}
+
+	pub(super) fn byte(&mut self) -> Result<u8, String> {
+		Ok(self.array::<1>()?[0])
+	}
+}
+
+pub(super) fn text(bytes: &[u8]) -> String {
+	format!("{:?}", String::from_utf8_lossy(bytes))
+}
+
+pub(super) fn map_node(entries: Vec<FieldNode>) -> FieldNode {
+	match entries.is_empty() {
+		true => FieldNode::new("", "{}"),
+		false => FieldNode::new("", "").with_children(entries),
+	}
+}
+
+pub(super) fn list_node(items: Vec<FieldNode>) -> FieldNode {
+	let items = items.into_iter().enumerate().map(|(index, mut item)| {
+		item.name = format!("[{}]", index);
+		item
+	});
+	let items: Vec<_> = items.collect();
+	match items.is_empty() {
+		true => FieldNode::new("", "[]"),
+		false => FieldNode::new("", "").with_items(items),
+	}
+}
+
+pub(super) fn entry(name: String, mut node: FieldNode) -> FieldNode {
+	node.name = name;
+	node
+}
+
+fn json_node(value: &Value) -> FieldNode {
+	match value {
+		Value::Null => FieldNode::new("", "null"),
+		Value::Bool(value) => FieldNode::new("", &value.to_string()),
+		Value::Number(value) => FieldNode::new("", &value.to_string()),
+		Value::String(value) => FieldNode::new("", &format!("{:?}", value)),
+		Value::Array(items) => list_node(items.iter().map(json_node).collect()),
+		Value::Object(entries) => {
+			let entries = entries.iter().map(|(key, value)| entry(key.clone(), json_node(value)));
+			map_node(entries.collect())
+		}
+	}
+}
+
+/// Decode a JSON document
+pub fn decode_json(raw: &[u8]) -> Result<FieldNode, String> {
+	let value: Value = serde_json::from_slice(raw).map_err(|err| err.to_string())?;
+	Ok(json_node(&value))
+}
+
+fn cbor_node(value: &CborValue) -> FieldNode {
+	match value {
+		CborValue::Integer(value) => FieldNode::new("", &i128::from(*value).to_string()),
+		CborValue::Bytes(bytes) => FieldNode::new("", &format!("{:?}", bytes)),
+		CborValue::Float(value) => FieldNode::new("", &value.to_string()),
+		CborValue::Text(value) => FieldNode::new("", &format!("{:?}", value)),
+		CborValue::Bool(value) => FieldNode::new("", &value.to_string()),
+		CborValue::Null => FieldNode::new("", "null"),
+		CborValue::Tag(tag, value) => {
+			let mut node = cbor_node(value);
+			node.value = match node.children.is_empty() {
+				true => format!("{}({})", tag, node.value),
+				false => format!("tag {}", tag),
+			};
+			node
+		}
+		CborValue::Array(items) => list_node(items.iter().map(cbor_node).collect()),
+		CborValue::Map(entries) => {
+			// Text keys are shown without quotes
+			let entries = entries.iter().map(|(key, value)| {
+				let key = match key {
+					CborValue::Text(key) => key.clone(),
+					key => cbor_node(key).compact_value(),
+				};
+				entry(key, cbor_node(value))
+			});
+			map_node(entries.collect())
+		}
+		// The value type is non exhaustive
+		value => FieldNode::new("", &format!("{:?}", value)),
+	}
+}
+
+// Values are decoded until the end of the data
+fn finish(rest: &[u8], node: FieldNode) -> Result<FieldNode, String> {
+	match rest.len() {
+		0 => Ok(node),
+		len => Err(format!("{} trailing bytes", len)),
+	}
+}
+
+/// Decode a single CBOR item
+pub fn decode_cbor(raw: &[u8]) -> Result<FieldNode, String> {
+	let mut rest = raw;
+	let value: CborValue = ciborium::de::from_reader_with_recursion_limit(&mut rest, MAX_DEPTH)
+		.map_err(|err| err.to_string())?;
+	finish(rest, cbor_node(&value))
+}
+
+fn msgpack_node(value: &MsgPackValue) -> FieldNode {
+	match value {
+		MsgPackValue::Nil => FieldNode::new("", "null"),
+		MsgPackValue::Boolean(value) => FieldNode::new("", &value.to_string()),
+		MsgPackValue::Integer(value) => FieldNode::new("", &value.to_string()),
+		MsgPackValue::F32(value) => FieldNode::new("", &value.to_string()),
+		MsgPackValue::F64(value) => FieldNode::new("", &value.to_string()),
+		MsgPackValue::String(value) => FieldNode::new("", &text(value.as_bytes())),
+		MsgPackValue::Binary(bytes) => FieldNode::new("", &format!("{:?}", bytes)),
+		MsgPackValue::Array(items) => list_node(items.iter().map(msgpack_node).collect()),
+		MsgPackValue::Map(entries) => {
+			// String keys are shown without quotes
+			let entries = entries.iter().map(|(key, value)| {
+				let key = match key {
+					MsgPackValue::String(key) => {
+						String::from_utf8_lossy(key.as_bytes()).to_string()
+					}
+					key => msgpack_node(key).compact_value(),
+				};
+				entry(key, msgpack_node(value))
+			});
+			map_node(entries.collect())
+		}
+		MsgPackValue::Ext(ty, bytes) => FieldNode::new("", &format!("ext({}, {:?})", ty, bytes)),
+	}
+}
+
+/// Decode a single MessagePack value
+pub fn decode_msgpack(raw: &[u8]) -> Result<FieldNode, String> {
+	let mut rest = raw;
+	let value = rmpv::decode::read_value_with_max_depth(&mut rest, MAX_DEPTH)
+		.map_err(|err| err.to_string())?;
+	finish(rest, msgpack_node(&value))
+}
+
+#[cfg(test)]
+mod test {
+	use serde::Serialize;
+
+	use super::{decode_cbor, decode_json, decode_msgpack};
+
+	#[derive(Serialize)]
+	struct Order {
+		id: u32,
+		name: String,
+		note: Option<String>,
+		paid: bool,
+		price: f64,
+		tags: Vec<String>,
+	}
+
+	const DECODED: &str =
+		r#"{id: 7, name: "ada", note: null, paid: true, price: 1.5, tags: ["a", "b"]}"#;
+
+	fn order() -> Order {
+		Order {
+			id: 7,
+			name: "ada".to_string(),
+			note: None,
+			paid: true,
+			price: 1.5,
+			tags: vec!["a".to_string(), "b".to_string()],
+		}
+	}
+
+	fn cbor() -> Vec<u8> {
+		let mut raw = vec![];
+		ciborium::ser::into_writer(&order(), &mut raw).unwrap();
+		raw
+	}
+
+	#[test]
+	fn should_decode_serde_formats() {
+		let json = serde_json::to_vec(&order()).unwrap();
+		assert_eq!(decode_json(&json).unwrap().compact_value(), DECODED);
+		assert_eq!(decode_cbor(&cbor()).unwrap().compact_value(), DECODED);
+		let msgpack = rmp_serde::to_vec_named(&order()).unwrap();
+		assert_eq!(decode_msgpack(&msgpack).unwrap().compact_value(), DECODED);
+	}
+
+	#[test]
+	fn should_reject_malformed_cbor() {
+		let raw = cbor();
+		assert!(decode_cbor(&raw[..raw.len() - 1]).is_err());
+		assert!(decode_cbor(&[raw.as_slice(), &[0]].concat()).is_err());
+		// Array of 2^64 - 1 items and an indefinite array without its break
+		assert!(decode_cbor(&[0x9b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01]).is_err());
+		assert!(decode_cbor(&[0x9f, 0x01]).is_err());
+		assert!(decode_cbor(&[0x81; 4096]).is_err());
+	}
+
+	#[test]
+	fn should_reject_malformed_msgpack() {
+		let raw = rmp_serde::to_vec_named(&order()).unwrap();
+		assert!(decode_msgpack(&raw[..raw.len() - 1]).is_err());
+		assert!(decode_msgpack(&[raw.as_slice(), &[0]].concat()).is_err());
+		assert!(decode_msgpack(&[0xdd, 0xff, 0xff, 0xff, 0xff, 0x01]).is_err());
+		assert!(decode_msgpack(&[0xd9]).is_err());
+		assert!(decode_msgpack(&[0x91; 4096]).is_err());
+	}
+}
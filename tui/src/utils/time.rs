@@ -0,0 +1,364 @@
+use std::fmt;
+
+use chrono::{
+	DateTime, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, SecondsFormat, TimeZone as _,
+	Utc,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeUnit {
+	Seconds,
+	Millis,
+	Micros,
+	Nanos,
+}
+
+impl TimeUnit {
+	pub(super) fn per_second(&self) -> i128 {
+		match self {
+			TimeUnit::Seconds => 1,
+			TimeUnit::Millis => 1_000,
+			TimeUnit::Micros => 1_000_000,
+			TimeUnit::Nanos => 1_000_000_000,
+		}
+	}
+
+	fn seconds_format(&self) -> SecondsFormat {
+		match self {
+			TimeUnit::Seconds => SecondsFormat::Secs,
+			TimeUnit::Millis => SecondsFormat::Millis,
+			TimeUnit::Micros => SecondsFormat::Micros,
+			TimeUnit::Nanos => SecondsFormat::Nanos,
+		}
+	}
+}
+
+impl fmt::Display for TimeUnit {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let unit = match self {
+			TimeUnit::Seconds => "s",
+			TimeUnit::Millis => "ms",
+			TimeUnit::Micros => "us",
+			TimeUnit::Nanos => "ns",
+		};
+		write!(f, "{}", unit)
+	}
+}
+
+/// Timezone dates are shown in, and dates without offset are read in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeZone {
+	Utc,
+	Local,
+	Fixed(FixedOffset),
+}
+
+impl fmt::Display for TimeZone {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			TimeZone::Utc => write!(f, "UTC"),
+			TimeZone::Local => write!(f, "local"),
+			TimeZone::Fixed(offset) => write!(f, "{}", offset),
+		}
+	}
+}
+
+/// Encoding of `Timestamp` and `Date` layouts. Timestamps count units since the Unix epoch and
+/// dates count days, both as signed or unsigned integers of 4 or 8 bytes
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimeFormat {
+	pub unit: TimeUnit,
+	pub little_endian: bool,
+	// Bytes of the integer, 8 for timestamps and 4 for dates unless set
+	pub size: Option<usize>,
+	pub signed: bool,
+	pub timezone: TimeZone,
+}
+
+impl Default for TimeFormat {
+	fn default() -> Self {
+		TimeFormat {
+			unit: TimeUnit::Seconds,
+			little_endian: false,
+			size: None,
+			signed: true,
+			timezone: TimeZone::Utc,
+		}
+	}
+}
+
+fn epoch() -> NaiveDate {
+	NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()
+}
+
+impl TimeFormat {
+	pub fn set_unit(&mut self, unit: &str) -> Result<(), String> {
+		self.unit = match unit {
+			"s" => TimeUnit::Seconds,
+			"ms" => TimeUnit::Millis,
+			"us" => TimeUnit::Micros,
+			"ns" => TimeUnit::Nanos,
+			_ => return Err(format!("unknown unit {}, use s, ms, us or ns", unit)),
+		};
+		Ok(())
+	}
+
+	pub fn set_endian(&mut self, endian: &str) -> Result<(), String> {
+		self.little_endian = match endian.to_lowercase().as_str() {
+			"be" | "big" => false,
+			"le" | "little" => true,
+			_ => return Err(format!("unknown endianness {}, use be or le", endian)),
+		};
+		Ok(())
+	}
+
+	pub fn set_size(&mut self, size: u64) -> Result<(), String> {
+		match size {
+			4 | 8 => self.size = Some(size as usize),
+			_ => return Err(format!("unsupported size {}, use 4 or 8", size)),
+		}
+		Ok(())
+	}
+
+	/// Timezone as `UTC`, `local` or an offset like `+02:00`
+	pub fn set_timezone(&mut self, timezone: &str) -> Result<(), String> {
+		let invalid = || format!("unknown timezone {}, use UTC, local or +HH:MM", timezone);
+		self.timezone = match timezone.to_lowercase().as_str() {
+			"utc" | "z" => TimeZone::Utc,
+			"local" => TimeZone::Local,
+			offset => {
+				let (sign, rest) = match (offset.strip_prefix('+'), offset.strip_prefix('-')) {
+					(Some(rest), _) => (1, rest),
+					(_, Some(rest)) => (-1, rest),
+					_ => return Err(invalid()),
+				};
+				let digits = rest.replace(':', "");
+				let (hours, minutes) = match digits.len() {
+					_ if !digits.is_ascii() => (None, None),
+					2 => (digits.parse::<i32>().ok(), Some(0)),
+					4 => (digits[..2].parse::<i32>().ok(), digits[2..].parse::<i32>().ok()),
+					_ => (None, None),
+				};
+				let seconds = hours.zip(minutes).map(|(h, m)| sign * (h * 3600 + m * 60));
+				let offset = seconds.and_then(FixedOffset::east_opt).ok_or_else(invalid)?;
+				TimeZone::Fixed(offset)
+			}
+		};
+		Ok(())
+	}
+
+	pub fn timestamp_size(&self) -> usize {
+		self.size.unwrap_or(8)
+	}
+
+	pub fn date_size(&self) -> usize {
+		self.size.unwrap_or(4)
+	}
+
+	pub(super) fn read_int(&self, raw: &[u8], size: usize) -> Option<i128> {
+		let bytes = raw.get(..size)?;
+		let mut buf = [0; 8];
+		match self.little_endian {
+			true => buf[..size].copy_from_slice(bytes),
+			false => buf[8 - size..].copy_from_slice(bytes),
+		}
+		let value = match (size, self.signed, self.little_endian) {
+			(4, true, true) => i32::from_le_bytes(buf[..4].try_into().ok()?) as i128,
+			(4, true, false) => i32::from_be_bytes(buf[4..].try_into().ok()?) as i128,
+			(_, true, true) => i64::from_le_bytes(buf) as i128,
+			(_, true, false) => i64::from_be_bytes(buf) as i128,
+			(_, false, true) => u64::from_le_bytes(buf) as i128,
+			(_, false, false) => u64::from_be_bytes(buf) as i128,
+		};
+		Some(value)
+	}
+
+	// None when the value doesn't fit the size and signedness of the format
+	fn write_int(&self, value: i128, size: usize) -> Option<Vec<u8>> {
+		let bits = size as u32 * 8;
+		let range = match self.signed {
+			true => -(1 << (bits - 1))..1 << (bits - 1),
+			false => 0..1 << bits,
+		};
+		if !range.contains(&value) {
+			return None;
+		}
+		let bytes = (value as u64).to_be_bytes();
+		let mut bytes = bytes[8 - size..].to_vec();
+		if self.little_endian {
+			bytes.reverse();
+		}
+		Some(bytes)
+	}
+
+	fn format(&self, time: DateTime<Utc>, format: SecondsFormat) -> String {
+		match self.timezone {
+			TimeZone::Utc => time.to_rfc3339_opts(format, true),
+			TimeZone::Local => time.with_timezone(&Local).to_rfc3339_opts(format, false),
+			TimeZone::Fixed(offset) => time.with_timezone(&offset).to_rfc3339_opts(format, false),
+		}
+	}
+
+	/// Timestamp as an RFC 3339 date in the timezone of the format
+	pub fn timestamp(&self, raw: &[u8]) -> String {
+		let value = match self.read_int(raw, self.timestamp_size()) {
+			Some(value) => value,
+			None => return format!("Unable to parse: {:?}", raw),
+		};
+		let per_second = self.unit.per_second();
+		let (seconds, units) = (value.div_euclid(per_second), value.rem_euclid(per_second));
+		let nanos = units * (1_000_000_000 / per_second);
+		let time = i64::try_from(seconds)
+			.ok()
+			.and_then(|seconds| Utc.timestamp_opt(seconds, nanos as u32).single());
+		match time {
+			Some(time) => self.format(time, self.unit.seconds_format()),
+			None => format!("Unable to parse: {}", value),
+		}
+	}
+
+	/// Days since the Unix epoch as a `YYYY-MM-DD` date
+	pub fn date(&self, raw: &[u8]) -> String {
+		let days = match self.read_int(raw, self.date_size()) {
+			Some(days) => days,
+			None => return format!("Unable to parse: {:?}", raw),
+		};
+		// Durations of more days panic, dates are out of range long before
+		let date = match days.abs() < 1 << 31 {
+			true => epoch().checked_add_signed(Duration::days(days as i64)),
+			false => None,
+		};
+		match date {
+			Some(date) => date.format("%Y-%m-%d").to_string(),
+			None => format!("Unable to parse: {}", days),
+		}
+	}
+
+	// Dates with an offset are read as they are, others in the timezone of the format
+	fn parse(&self, text: &str) -> Result<DateTime<Utc>, String> {
+		if let Ok(time) = DateTime::parse_from_rfc3339(text) {
+			return Ok(time.with_timezone(&Utc));
+		}
+		let naive = ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"]
+			.iter()
+			.find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+			.or_else(|| {
+				let date = NaiveDate::parse_from_str(text, "%Y-%m-%d").ok()?;
+				date.and_hms_opt(0, 0, 0)
+			})
+			.ok_or(format!("invalid date {}, use YYYY-MM-DD or RFC 3339", text))?;
+		let time = match self.timezone {
+			TimeZone::Utc => Some(Utc.from_utc_datetime(&naive)),
+			TimeZone::Local => Local.from_local_datetime(&naive).earliest().map(|t| t.into()),
+			TimeZone::Fixed(offset) => {
+				offset.from_local_datetime(&naive).single().map(|t| t.into())
+			}
+		};
+		time.ok_or(format!("invalid date {} in the timezone", text))
+	}
+
+	/// Encode a date, or a number of units, as a timestamp
+	pub fn encode_timestamp(&self, text: &str) -> Result<Vec<u8>, String> {
+		let value = match text.parse::<i128>() {
+			Ok(value) => value,
+			Err(_) => {
+				let time = self.parse(text)?;
+				let per_second = self.unit.per_second();
+				let nanos = time.timestamp_subsec_nanos() as i128 / (1_000_000_000 / per_second);
+				time.timestamp() as i128 * per_second + nanos
+			}
+		};
+		self.write_int(value, self.timestamp_size())
+			.ok_or(format!("timestamp {} out of range", text))
+	}
+
+	/// Encode a date, or a number of days, as days since the Unix epoch
+	pub fn encode_date(&self, text: &str) -> Result<Vec<u8>, String> {
+		let days = match text.parse::<i128>() {
+			Ok(days) => days,
+			Err(_) => {
+				let time = self.parse(text)?;
+				time.date_naive().signed_duration_since(epoch()).num_days() as i128
+			}
+		};
+		self.write_int(days, self.date_size()).ok_or(format!("date {} out of range", text))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use chrono::{NaiveDate, TimeZone as _, Utc};
+
+	use super::{TimeFormat, TimeUnit};
+
+	#[test]
+	fn should_encode_chrono_timestamps() {
+		let time = Utc.with_ymd_and_hms(2023, 3, 14, 15, 9, 26).unwrap();
+		let mut format = TimeFormat::default();
+		let raw = format.encode_timestamp("2023-03-14T15:09:26Z").unwrap();
+		assert_eq!(raw, time.timestamp().to_be_bytes());
+		assert_eq!(format.timestamp(&raw), "2023-03-14T15:09:26Z");
+
+		format.set_unit("ms").unwrap();
+		format.set_endian("le").unwrap();
+		let raw = format.encode_timestamp("2023-03-14 15:09:26.535").unwrap();
+		assert_eq!(raw, (time.timestamp_millis() + 535).to_le_bytes());
+		assert_eq!(format.timestamp(&raw), "2023-03-14T15:09:26.535Z");
+
+		format.set_timezone("+02:00").unwrap();
+		assert_eq!(format.timestamp(&raw), "2023-03-14T17:09:26.535+02:00");
+		assert_eq!(format.encode_timestamp("2023-03-14 17:09:26.535").unwrap(), raw);
+	}
+
+	#[test]
+	fn should_encode_chrono_dates() {
+		let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+		let days = NaiveDate::from_ymd_opt(1969, 7, 20).unwrap().signed_duration_since(epoch);
+		let format = TimeFormat::default();
+		let raw = format.encode_date("1969-07-20").unwrap();
+		assert_eq!(raw, (days.num_days() as i32).to_be_bytes());
+		assert_eq!(format.date(&raw), "1969-07-20");
+	}
+
+	#[test]
+	fn should_read_unsigned_timestamps() {
+		let mut format = TimeFormat {
+			size: Some(4),
+			..Default::default()
+		};
+		let raw = u32::MAX.to_be_bytes();
+		assert_eq!(format.timestamp(&raw), "1969-12-31T23:59:59Z");
+		format.signed = false;
+		assert_eq!(format.timestamp(&raw), "2106-02-07T06:28:15Z");
+		assert_eq!(format.encode_timestamp("2106-02-07T06:28:15Z").unwrap(), raw);
+		assert!(format.encode_timestamp("-1").is_err());
+
+		format.size = Some(8);
+		format.unit = TimeUnit::Nanos;
+		let raw = u64::MAX.to_be_bytes();
+		assert_eq!(format.timestamp(&raw), "2554-07-21T23:34:33.709551615Z");
+	}
+
+	#[test]
+	fn should_reject_malformed_times() {
+		let format = TimeFormat::default();
+		assert_eq!(format.timestamp(&[0, 1, 2]), "Unable to parse: [0, 1, 2]");
+		assert_eq!(format.date(&[0, 1]), "Unable to parse: [0, 1]");
+		assert_eq!(
+			format.timestamp(&i64::MAX.to_be_bytes()),
+			format!("Unable to parse: {}", i64::MAX)
+		);
+		assert_eq!(format.date(&i32::MIN.to_be_bytes()), format!("Unable to parse: {}", i32::MIN));
+		assert!(format.encode_timestamp("2023-13-01").is_err());
+		assert!(format.encode_date("yesterday").is_err());
+		let err = format.encode_date("9999999999").unwrap_err();
+		assert_eq!(err, "date 9999999999 out of range");
+		let err = format.encode_timestamp("99999999999999999999").unwrap_err();
+		assert_eq!(err, "timestamp 99999999999999999999 out of range");
+
+		let mut format = TimeFormat::default();
+		assert!(format.set_unit("h").is_err());
+		assert!(format.set_size(2).is_err());
+		assert!(format.set_timezone("+25:00").is_err());
+	}
+}